@@ -0,0 +1,277 @@
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// A margin comment that is not part of the manuscript.
+///
+/// Written as `{>>comment<<}`, or as `{==text==}{>>comment<<}` to annotate a part
+/// of the text. A comment ends on the line it starts on.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Comment {
+    /// Byte range of the whole markup
+    pub range: Range<usize>,
+    /// Byte range of the annotated text, inside of `range`
+    pub annotated: Option<Range<usize>>,
+    pub text: String,
+    /// Line the comment is on, starting at 0
+    pub line: usize,
+}
+
+/// The comments of a chapter, for listing them without opening it
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChapterComments {
+    pub chapter: String,
+    pub comments: Vec<Comment>,
+}
+
+/// A footnote reference `[^label]` or the start of its definition `[^label]: `
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Footnote {
+    /// Byte range of the label, without the brackets
+    pub label: Range<usize>,
+    pub definition: bool,
+}
+
+fn is_fence(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("```") || trimmed.starts_with("~~~")
+}
+
+/// Lines outside of fenced code blocks with their byte offset and line number
+fn prose_lines(text: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    let mut offset = 0;
+    let mut in_fence = false;
+
+    text.split('\n')
+        .enumerate()
+        .filter_map(move |(number, line)| {
+            let start = offset;
+            offset += line.len() + 1;
+
+            if is_fence(line) {
+                in_fence = !in_fence;
+                return None;
+            }
+            (!in_fence).then_some((start, number, line))
+        })
+}
+
+/// End of the code span starting with the backticks at `start`, if it is closed
+pub fn code_span_end(line: &str, start: usize) -> Option<usize> {
+    let ticks = line[start..].chars().take_while(|c| *c == '`').count();
+    let mut position = start + ticks;
+    while let Some(found) = line[position..].find('`') {
+        let run_start = position + found;
+        let run = line[run_start..].chars().take_while(|c| *c == '`').count();
+        if run == ticks {
+            return Some(run_start + run);
+        }
+        position = run_start + run;
+    }
+    None
+}
+
+/// Parses the comment starting at byte `start` of the line beginning at `offset`
+fn comment_at(line: &str, start: usize, offset: usize, number: usize) -> Option<Comment> {
+    let (annotated, comment_start) = if let Some(rest) = line[start..].strip_prefix("{==") {
+        let end = start + 3 + rest.find("==}")?;
+        (Some(offset + start + 3..offset + end), end + 3)
+    } else {
+        (None, start)
+    };
+
+    // Any other `{` is just text
+    let rest = line[comment_start..].strip_prefix("{>>")?;
+    let text_start = comment_start + 3;
+    let text_end = text_start + rest.find("<<}")?;
+    Some(Comment {
+        range: offset + start..offset + text_end + 3,
+        annotated,
+        text: line[text_start..text_end].trim().to_string(),
+        line: number,
+    })
+}
+
+/// Finds all comments in `text` outside of code, ordered by position
+pub fn find_comments(text: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+
+    for (offset, number, line) in prose_lines(text) {
+        let mut position = 0;
+        while let Some(found) = line[position..].find(['`', '{']) {
+            let start = position + found;
+            if line[start..].starts_with('`') {
+                position = code_span_end(line, start).unwrap_or(start + 1);
+                continue;
+            }
+
+            if let Some(comment) = comment_at(line, start, offset, number) {
+                position = comment.range.end - offset;
+                comments.push(comment);
+            } else {
+                position = start + 1;
+            }
+        }
+    }
+
+    comments
+}
+
+/// Replaces every comment with what `replace` returns for it
+pub fn replace_comments(text: &str, mut replace: impl FnMut(usize, &Comment) -> String) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut last_end = 0;
+
+    for (index, comment) in find_comments(text).iter().enumerate() {
+        output.push_str(&text[last_end..comment.range.start]);
+        output.push_str(&replace(index, comment));
+        last_end = comment.range.end;
+    }
+    output.push_str(&text[last_end..]);
+
+    output
+}
+
+/// The text without comments, annotated text is kept
+pub fn strip_comments(text: &str) -> String {
+    replace_comments(text, |_, comment| {
+        comment
+            .annotated
+            .clone()
+            .map(|range| text[range].to_string())
+            .unwrap_or_default()
+    })
+}
+
+/// Finds all footnote references and definitions in `text` outside of code
+pub fn find_footnotes(text: &str) -> Vec<Footnote> {
+    let mut footnotes = Vec::new();
+
+    for (offset, _, line) in prose_lines(text) {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let mut position = 0;
+        while let Some(found) = line[position..].find(['`', '[']) {
+            let start = position + found;
+            if line[start..].starts_with('`') {
+                position = code_span_end(line, start).unwrap_or(start + 1);
+                continue;
+            }
+
+            let label_end = line[start..]
+                .strip_prefix("[^")
+                .and_then(|rest| rest.find(']'))
+                .map(|end| start + 2 + end)
+                .filter(|end| {
+                    let label = &line[start + 2..*end];
+                    !label.is_empty() && !label.contains(char::is_whitespace)
+                });
+            let Some(label_end) = label_end else {
+                position = start + 1;
+                continue;
+            };
+
+            let definition =
+                start == indent && indent <= 3 && line[label_end + 1..].starts_with(':');
+            footnotes.push(Footnote {
+                label: offset + start + 2..offset + label_end,
+                definition,
+            });
+            position = label_end + 1;
+        }
+    }
+
+    footnotes
+}
+
+/// Labels of the footnotes in the order they are numbered: referenced ones
+/// by their first reference, then those that are only defined
+pub fn footnote_order(text: &str) -> Vec<String> {
+    let footnotes = find_footnotes(text);
+    let mut order: Vec<String> = Vec::new();
+
+    for definitions in [false, true] {
+        for footnote in footnotes.iter().filter(|x| x.definition == definitions) {
+            let label = &text[footnote.label.clone()];
+            if !order.iter().any(|x| x == label) {
+                order.push(label.to_string());
+            }
+        }
+    }
+
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn braces_that_are_no_comments() {
+        assert!(find_comments("fn() {").is_empty());
+        assert!(find_comments("a {€b<<}").is_empty());
+        assert!(find_comments("a {x <<} b").is_empty());
+        assert!(find_comments("{==text==} and {>>").is_empty());
+        assert!(find_comments("{==text").is_empty());
+        assert!(find_comments("{>>unterminated").is_empty());
+    }
+
+    #[test]
+    fn comments() {
+        let text = "a {>> note <<} b {==text==}{>>why<<} {€";
+        let comments = find_comments(text);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].text, "note");
+        assert_eq!(&text[comments[0].range.clone()], "{>> note <<}");
+        assert_eq!(&text[comments[1].annotated.clone().unwrap()], "text");
+        assert_eq!(comments[1].text, "why");
+        assert_eq!(strip_comments(text), "a  b text {€");
+    }
+
+    #[test]
+    fn comments_in_code() {
+        let text = "`{>>span<<}` {>>kept<<}\n```\n{>>fenced<<}\n```\n``a ` {>>b<<}`` end";
+        let comments = find_comments(text);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].text, "kept");
+        assert_eq!(strip_comments(text), text.replacen("{>>kept<<}", "", 1));
+    }
+
+    #[test]
+    fn comments_after_the_first_line() {
+        let text = "first\nsecond {>>one<<}\n\nx {==part==}{>>two<<}";
+        let comments = find_comments(text);
+        assert_eq!(comments.len(), 2);
+        assert_eq!(comments[0].line, 1);
+        assert_eq!(&text[comments[0].range.clone()], "{>>one<<}");
+        assert_eq!(comments[1].line, 3);
+        assert_eq!(&text[comments[1].annotated.clone().unwrap()], "part");
+        assert_eq!(&text[comments[1].range.clone()], "{==part==}{>>two<<}");
+    }
+
+    #[test]
+    fn footnotes() {
+        let text = "Text[^b] and `[^code]` and [^a].\n\n```\n[^fenced]\n```\n[^a]: A\n[^b]: B\n   [^only]: defined\n    [^indented]: no";
+        let footnotes = find_footnotes(text);
+        let labels: Vec<(&str, bool)> = footnotes
+            .iter()
+            .map(|x| (&text[x.label.clone()], x.definition))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("b", false),
+                ("a", false),
+                ("a", true),
+                ("b", true),
+                ("only", true),
+                ("indented", false),
+            ]
+        );
+        assert_eq!(footnote_order(text), ["b", "a", "indented", "only"]);
+    }
+
+    #[test]
+    fn labels_that_are_no_footnotes() {
+        assert!(find_footnotes("[^] [^with space] [^open").is_empty());
+        assert!(footnote_order("").is_empty());
+    }
+}
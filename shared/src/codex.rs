@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::is_reserved_name;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+pub enum CodexKind {
+    #[default]
+    Character,
+    Location,
+    Item,
+    Faction,
+}
+
+impl CodexKind {
+    pub const ALL: [Self; 4] = [Self::Character, Self::Location, Self::Item, Self::Faction];
+
+    /// Name of the folder below `Extras/` the entries of this kind are stored in.
+    pub const fn folder(self) -> &'static str {
+        match self {
+            Self::Character => "Characters",
+            Self::Location => "Locations",
+            Self::Item => "Items",
+            Self::Faction => "Factions",
+        }
+    }
+}
+
+impl fmt::Display for CodexKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Character => "Character",
+            Self::Location => "Location",
+            Self::Item => "Item",
+            Self::Faction => "Faction",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct CodexField {
+    pub name: String,
+    pub value: String,
+}
+
+/// An entry of the project codex, stored as `Extras/<Kind>/<Name>/Entry.json`
+/// next to a `Notes.md` and the images of the entry.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct CodexEntry {
+    pub name: String,
+    pub kind: CodexKind,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub fields: Vec<CodexField>,
+    /// File names of images inside the entry folder
+    #[serde(default)]
+    pub images: Vec<String>,
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Characters Windows doesn't allow in file and folder names
+const FORBIDDEN_CHARACTERS: [char; 9] = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Why `name` can't be the name of an entry, it is used as the name of its folder
+pub fn codex_name_error(name: &str) -> Option<&'static str> {
+    if name.trim().is_empty() {
+        Some("Please enter a name.")
+    } else if name.trim() != name {
+        Some("Names cannot start or end with spaces.")
+    } else if name.contains(FORBIDDEN_CHARACTERS) || name.contains(char::is_control) {
+        Some("Names cannot contain / \\ : * ? \" < > |")
+    } else if name.ends_with('.') {
+        Some("Names cannot end with a dot.")
+    } else if is_reserved_name(name) {
+        Some("This name is reserved by the system.")
+    } else {
+        None
+    }
+}
+
+impl CodexEntry {
+    /// Folder of the entry, only for names without a [`codex_name_error`]
+    pub fn dir(&self, project_path: &Path) -> PathBuf {
+        let mut path = project_path.to_path_buf();
+        path.push("Extras");
+        path.push(self.kind.folder());
+        path.push(&self.name);
+        path
+    }
+
+    /// The name and all aliases the entry can be mentioned by
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.name.as_str())
+            .chain(self.aliases.iter().map(String::as_str))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct CodexMention {
+    pub chapter: String,
+    pub count: usize,
+}
+
+fn is_word_boundary(c: Option<char>) -> bool {
+    c.is_none_or(|c| !c.is_alphanumeric())
+}
+
+/// Finds all whole word mentions of any of the `entries` in `text`.
+///
+/// Returns the byte ranges together with the index of the mentioned entry,
+/// ordered by position. Overlapping names prefer the longest match.
+pub fn find_mentions(text: &str, entries: &[CodexEntry]) -> Vec<(Range<usize>, usize)> {
+    let mut candidates: Vec<(Range<usize>, usize)> = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        for name in entry.names() {
+            for (start, _) in text.match_indices(name) {
+                let end = start + name.len();
+                if is_word_boundary(text[..start].chars().next_back())
+                    && is_word_boundary(text[end..].chars().next())
+                {
+                    candidates.push((start..end, index));
+                }
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| a.0.start.cmp(&b.0.start).then(b.0.end.cmp(&a.0.end)));
+
+    let mut mentions: Vec<(Range<usize>, usize)> = Vec::new();
+    for candidate in candidates {
+        if mentions
+            .last()
+            .is_none_or(|last| last.0.end <= candidate.0.start)
+        {
+            mentions.push(candidate);
+        }
+    }
+
+    mentions
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::Project;
+
+/// A folder in `Chapters` and which of the chapter files it has
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ChapterFolder {
+    pub name: String,
+    pub content: bool,
+    pub note: bool,
+}
+
+/// Something in the project file that doesn't match the project folder
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub enum ProjectProblem {
+    /// The chapter is listed but its folder is gone
+    MissingFolder(String),
+    /// The folder is in `Chapters` but not listed
+    OrphanedFolder(String),
+    /// The chapter is listed more than once
+    DuplicateChapter(String),
+    MissingContent(String),
+    MissingNote(String),
+    /// The open chapter points past the end of the chapter list
+    InvalidActiveChapter(usize),
+    /// Metadata of a chapter that isn't listed
+    StaleMetadata(String),
+}
+
+impl ProjectProblem {
+    /// What repairing the problem did, for the report
+    pub fn repaired(&self) -> String {
+        match self {
+            Self::MissingFolder(chapter) => {
+                format!("Removed \"{chapter}\" from the chapter list")
+            }
+            Self::OrphanedFolder(chapter) => {
+                format!("Added \"{chapter}\" to the end of the chapter list")
+            }
+            Self::DuplicateChapter(chapter) => {
+                format!("Removed the second \"{chapter}\" from the chapter list")
+            }
+            Self::MissingContent(chapter) => {
+                format!("Created an empty Content.md for \"{chapter}\"")
+            }
+            Self::MissingNote(chapter) => format!("Created an empty Note.md for \"{chapter}\""),
+            Self::InvalidActiveChapter(_) => "Opened the first chapter".to_string(),
+            Self::StaleMetadata(chapter) => format!("Removed the metadata of \"{chapter}\""),
+        }
+    }
+}
+
+impl fmt::Display for ProjectProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFolder(chapter) => {
+                write!(f, "The folder of \"{chapter}\" is missing")
+            }
+            Self::OrphanedFolder(chapter) => {
+                write!(f, "The folder \"{chapter}\" is not in the chapter list")
+            }
+            Self::DuplicateChapter(chapter) => {
+                write!(f, "\"{chapter}\" is in the chapter list more than once")
+            }
+            Self::MissingContent(chapter) => write!(f, "\"{chapter}\" has no Content.md"),
+            Self::MissingNote(chapter) => write!(f, "\"{chapter}\" has no Note.md"),
+            Self::InvalidActiveChapter(index) => {
+                write!(f, "The open chapter ({}) doesn't exist", index + 1)
+            }
+            Self::StaleMetadata(chapter) => {
+                write!(
+                    f,
+                    "There is metadata for \"{chapter}\", which is not a chapter"
+                )
+            }
+        }
+    }
+}
+
+/// What the project doctor repaired
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DoctorReport {
+    pub project: Project,
+    /// One line per repaired problem
+    pub changes: Vec<String>,
+}
+
+/// Compares the project file with the chapter `folders` on disk
+pub fn diagnose(project: &Project, folders: &[ChapterFolder]) -> Vec<ProjectProblem> {
+    let mut problems = Vec::new();
+
+    for (index, chapter) in project.chapters.iter().enumerate() {
+        if project.chapters[..index].contains(chapter) {
+            problems.push(ProjectProblem::DuplicateChapter(chapter.clone()));
+        } else if !folders.iter().any(|folder| folder.name == *chapter) {
+            problems.push(ProjectProblem::MissingFolder(chapter.clone()));
+        }
+    }
+    for folder in folders {
+        if !project.chapters.contains(&folder.name) {
+            problems.push(ProjectProblem::OrphanedFolder(folder.name.clone()));
+        }
+        if !folder.content {
+            problems.push(ProjectProblem::MissingContent(folder.name.clone()));
+        }
+        if !folder.note {
+            problems.push(ProjectProblem::MissingNote(folder.name.clone()));
+        }
+    }
+    if let Some(active) = project.active_chapter {
+        if active >= project.chapters.len() {
+            problems.push(ProjectProblem::InvalidActiveChapter(active));
+        }
+    }
+    for chapter in project.metadata.keys() {
+        let exists = project.chapters.contains(chapter)
+            || folders.iter().any(|folder| folder.name == *chapter);
+        if !exists {
+            problems.push(ProjectProblem::StaleMetadata(chapter.clone()));
+        }
+    }
+
+    problems
+}
+
+/// Fixes the chapter list for `problems`. The missing files are left to the caller.
+pub fn repair(project: &mut Project, problems: &[ProjectProblem]) {
+    let active = project
+        .active_chapter
+        .and_then(|index| project.chapters.get(index).cloned());
+
+    let mut chapters: Vec<String> = Vec::new();
+    for chapter in &project.chapters {
+        let missing = problems.contains(&ProjectProblem::MissingFolder(chapter.clone()));
+        if !missing && !chapters.contains(chapter) {
+            chapters.push(chapter.clone());
+        }
+    }
+    for problem in problems {
+        match problem {
+            ProjectProblem::OrphanedFolder(chapter) => chapters.push(chapter.clone()),
+            ProjectProblem::MissingFolder(chapter) | ProjectProblem::StaleMetadata(chapter) => {
+                project.metadata.remove(chapter);
+            }
+            _ => {}
+        }
+    }
+    project.chapters = chapters;
+
+    if project.active_chapter.is_some() {
+        project.active_chapter = active
+            .and_then(|active| project.chapters.iter().position(|x| *x == active))
+            .or_else(|| (!project.chapters.is_empty()).then_some(0));
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether the chapters and notes of a project are encrypted, and if they can be read
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum ProjectLock {
+    #[default]
+    Unencrypted,
+    /// Encrypted, the passphrase is needed to open the chapters and notes
+    Locked,
+    /// Encrypted, the files are decrypted and encrypted when they are read and written
+    Unlocked,
+}
+
+/// Files inside the project that are encrypted, the chapters and notes, the
+/// notes of codex entries and the histories and tracked changes made from them
+pub const ENCRYPTED_FILES: [&str; 5] = [
+    "Content.md",
+    "Note.md",
+    "Notes.md",
+    ".history.json",
+    ".revision.json",
+];
+
+pub fn is_encrypted_file(name: &str) -> bool {
+    ENCRYPTED_FILES.contains(&name)
+}
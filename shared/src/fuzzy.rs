@@ -0,0 +1,45 @@
+/// Scores how well `query` matches `text` when all of its characters appear in
+/// `text` in the same order, ignoring case. Characters that follow each other or
+/// start a word count more. Returns `None` when `query` doesn't match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let Some(first) = query.first() else {
+        return Some(0);
+    };
+
+    // Matching from the first fitting character could miss a better match later
+    // on, like the start of a word, so every start is tried
+    (0..text.len())
+        .filter(|start| text[*start] == *first)
+        .filter_map(|start| score_from(&query, &text, start))
+        .max()
+}
+
+fn score_from(query: &[char], text: &[char], start: usize) -> Option<u32> {
+    let mut score = 0;
+    let mut position = start;
+    let mut last_match: Option<usize> = None;
+
+    for wanted in query {
+        let offset = text[position..].iter().position(|c| c == wanted)?;
+        let index = position + offset;
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == index) {
+            score += 4;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 2;
+        }
+
+        last_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
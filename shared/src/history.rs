@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+/// Edits closer together than this (in milliseconds) are undone as one step
+const COALESCE_WINDOW: u64 = 1000;
+/// Maximum number of undo steps kept per document
+const HISTORY_LIMIT: usize = 1000;
+
+/// A change of a document: `removed` was replaced by `inserted` at the byte offset `position`.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct TextEdit {
+    pub position: usize,
+    pub removed: String,
+    pub inserted: String,
+    /// When the edit was made, only used to merge edits while typing
+    #[serde(skip)]
+    pub time: u64,
+}
+
+impl TextEdit {
+    /// The smallest edit turning `old` into `new`, if they differ.
+    pub fn between(old: &str, new: &str) -> Option<Self> {
+        if old == new {
+            return None;
+        }
+
+        let prefix: usize = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        let max_suffix = old.len().min(new.len()) - prefix;
+        let suffix: usize = old[prefix..]
+            .chars()
+            .rev()
+            .zip(new[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .scan(0, |total, len| {
+                *total += len;
+                (*total <= max_suffix).then_some(len)
+            })
+            .sum();
+
+        Some(Self {
+            position: prefix,
+            removed: old[prefix..old.len() - suffix].to_string(),
+            inserted: new[prefix..new.len() - suffix].to_string(),
+            time: 0,
+        })
+    }
+
+    #[must_use]
+    pub fn inverse(&self) -> Self {
+        Self {
+            position: self.position,
+            removed: self.inserted.clone(),
+            inserted: self.removed.clone(),
+            time: self.time,
+        }
+    }
+
+    /// Applies the edit to `text` and returns the byte offset right after the inserted text.
+    /// Returns `None` without touching `text` if it does not contain the removed text.
+    pub fn apply(&self, text: &mut String) -> Option<usize> {
+        let range = self.position..self.position + self.removed.len();
+        if text.get(range.clone())? != self.removed {
+            return None;
+        }
+        text.replace_range(range, &self.inserted);
+
+        Some(self.position + self.inserted.len())
+    }
+
+    /// Merges `next` into this edit if both belong to the same run of typing or deleting.
+    fn merge(&mut self, next: &Self) -> bool {
+        if next.time.saturating_sub(self.time) > COALESCE_WINDOW {
+            return false;
+        }
+
+        let typing = self.removed.is_empty()
+            && next.removed.is_empty()
+            && next.position == self.position + self.inserted.len()
+            && !next.inserted.contains('\n')
+            // Start a new step with every word
+            && (!next.inserted.starts_with(char::is_whitespace)
+                || self.inserted.ends_with(char::is_whitespace));
+        let backspacing = self.inserted.is_empty()
+            && next.inserted.is_empty()
+            && next.position + next.removed.len() == self.position;
+        let deleting =
+            self.inserted.is_empty() && next.inserted.is_empty() && next.position == self.position;
+
+        if typing {
+            self.inserted.push_str(&next.inserted);
+        } else if backspacing {
+            self.removed.insert_str(0, &next.removed);
+            self.position = next.position;
+        } else if deleting {
+            self.removed.push_str(&next.removed);
+        } else {
+            return false;
+        }
+
+        self.time = next.time;
+        true
+    }
+}
+
+/// Undo and redo stacks of a single document.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct EditHistory {
+    pub undo: Vec<TextEdit>,
+    pub redo: Vec<TextEdit>,
+    /// Checksum of the text the history ends in, to detect changes made elsewhere
+    #[serde(default)]
+    pub checksum: Option<u64>,
+}
+
+impl EditHistory {
+    pub fn record(&mut self, edit: TextEdit) {
+        self.redo.clear();
+
+        if let Some(last) = self.undo.last_mut() {
+            if last.merge(&edit) {
+                return;
+            }
+        }
+
+        self.undo.push(edit);
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Reverts the last edit through `apply` and returns where the cursor should go.
+    /// `apply` returns `None` if the edit does not fit the document.
+    pub fn undo(&mut self, apply: impl FnOnce(&TextEdit) -> Option<usize>) -> Option<usize> {
+        let edit = self.undo.pop()?;
+        let Some(cursor) = apply(&edit.inverse()) else {
+            // The text does not match the history anymore
+            *self = Self::default();
+            return None;
+        };
+        self.redo.push(edit);
+
+        Some(cursor)
+    }
+
+    /// Reapplies the last undone edit through `apply` and returns where the cursor should go.
+    pub fn redo(&mut self, apply: impl FnOnce(&TextEdit) -> Option<usize>) -> Option<usize> {
+        let edit = self.redo.pop()?;
+        let Some(cursor) = apply(&edit) else {
+            *self = Self::default();
+            return None;
+        };
+        self.undo.push(edit);
+
+        Some(cursor)
+    }
+
+    /// Whether the history ends in the text with the given `checksum`.
+    /// Histories without a checksum fit any text.
+    pub fn fits(&self, checksum: u64) -> bool {
+        self.checksum.is_none_or(|own| own == checksum)
+    }
+}
+
+/// FNV-1a hash of a text given in `chunks`, stable across platforms and versions.
+pub fn text_checksum<'a>(chunks: impl IntoIterator<Item = &'a str>) -> u64 {
+    fnv_hash(chunks.into_iter().flat_map(str::bytes))
+}
+
+/// Like [`text_checksum`], for files that aren't text
+pub fn bytes_checksum(bytes: &[u8]) -> u64 {
+    fnv_hash(bytes.iter().copied())
+}
+
+fn fnv_hash(bytes: impl Iterator<Item = u8>) -> u64 {
+    bytes.fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
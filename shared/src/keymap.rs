@@ -0,0 +1,199 @@
+use std::collections::BTreeMap;
+
+/// Every command of the app, with its label and default shortcuts
+pub const DEFAULT_KEYMAP: [(&str, &str, &[&str]); 30] = [
+    ("command_palette", "Command Palette", &["Ctrl+Shift+P"]),
+    ("save", "Save", &["Ctrl+S"]),
+    ("create_project", "Create Project", &[]),
+    ("save_as_template", "Save Project as Template", &[]),
+    ("load_project", "Load Project", &["Ctrl+O"]),
+    ("open_archive", "Open Project Archive", &[]),
+    ("pack_project", "Pack Project Into Archive", &[]),
+    ("export", "Export", &["Ctrl+E"]),
+    ("settings", "Open Settings", &["Ctrl+,"]),
+    ("statistics", "Open Statistics", &[]),
+    ("show_log", "Show Log", &[]),
+    ("check_project", "Check Project", &[]),
+    ("encryption", "Project Encryption", &[]),
+    ("lock_project", "Lock Project", &["Ctrl+Shift+L"]),
+    ("new_chapter", "New Chapter", &["Ctrl+N"]),
+    ("rename_chapter", "Rename Chapter", &["F2"]),
+    ("rename_project", "Rename Project", &[]),
+    ("undo", "Undo", &["Ctrl+Z"]),
+    ("redo", "Redo", &["Ctrl+Y", "Ctrl+Shift+Z"]),
+    ("bold", "Bold", &["Ctrl+B"]),
+    ("italic", "Italic", &["Ctrl+I"]),
+    ("underline", "Underline", &["Ctrl+U"]),
+    ("highlight", "Highlight", &["Ctrl+Shift+H"]),
+    ("zoom_in", "Zoom In", &["Ctrl+="]),
+    ("zoom_out", "Zoom Out", &["Ctrl+-"]),
+    ("next_chapter", "Next Chapter", &["Ctrl+PageDown"]),
+    ("previous_chapter", "Previous Chapter", &["Ctrl+PageUp"]),
+    ("corkboard", "Corkboard", &["Ctrl+Shift+C"]),
+    ("outliner", "Outliner", &["Ctrl+Shift+O"]),
+    ("notes", "Notes", &["Ctrl+Shift+N"]),
+];
+
+/// Cmd on macOS counts as Ctrl, so shortcuts work the same everywhere
+const MODIFIERS: [&str; 3] = ["Ctrl", "Alt", "Shift"];
+
+/// Brings a shortcut like `shift+ctrl+s` into the form `Ctrl+Shift+S`.
+/// Returns `None` for unknown modifiers and for characters without Ctrl or Alt,
+/// which couldn't be typed anymore.
+pub fn normalize_shortcut(shortcut: &str) -> Option<String> {
+    let shortcut = shortcut.trim();
+    // Splitting on '+' would lose a '+' key, so the key is taken from the end
+    let (modifiers, key) = match shortcut.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => shortcut.rsplit_once('+').unwrap_or(("", shortcut)),
+    };
+
+    let mut pressed = [false; 3];
+    for modifier in modifiers.split('+') {
+        let modifier = match modifier.trim().to_lowercase().as_str() {
+            "ctrl" | "control" | "cmd" | "command" | "meta" => "Ctrl",
+            "alt" | "option" => "Alt",
+            "shift" => "Shift",
+            "" if modifiers.is_empty() => continue,
+            _ => return None,
+        };
+        let index = MODIFIERS.iter().position(|x| *x == modifier)?;
+        pressed[index] = true;
+    }
+
+    let key = normalize_key(key.trim());
+    let character = key.chars().count() == 1;
+    if key.is_empty()
+        || MODIFIERS.contains(&key.as_str())
+        || (character && !pressed[0] && !pressed[1])
+    {
+        return None;
+    }
+
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .zip(pressed)
+        .filter(|(_, pressed)| *pressed)
+        .map(|(modifier, _)| (*modifier).to_string())
+        .collect();
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+/// Single characters are upper case, named keys like `PageDown` keep their name
+fn normalize_key(key: &str) -> String {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c.to_uppercase().collect(),
+        _ => match key.to_lowercase().as_str() {
+            "control" | "meta" => "Ctrl".to_string(),
+            "alt" => "Alt".to_string(),
+            "shift" => "Shift".to_string(),
+            "plus" => "+".to_string(),
+            "space" => " ".to_string(),
+            _ => key.to_string(),
+        },
+    }
+}
+
+/// The shortcut of a key press, `key` is the key as the browser names it
+pub fn shortcut_of(key: &str, ctrl: bool, alt: bool, shift: bool) -> String {
+    let mut parts: Vec<String> = MODIFIERS
+        .iter()
+        .zip([ctrl, alt, shift])
+        .filter(|(_, pressed)| *pressed)
+        .map(|(modifier, _)| (*modifier).to_string())
+        .collect();
+    parts.push(normalize_key(key));
+    parts.join("+")
+}
+
+/// The label of `command` in the default keymap
+pub fn command_label(command: &str) -> Option<&'static str> {
+    DEFAULT_KEYMAP
+        .iter()
+        .find(|(id, _, _)| *id == command)
+        .map(|(_, label, _)| *label)
+}
+
+/// The default keymap with the user's shortcuts in place of the defaults
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Keymap {
+    /// Shortcut and command, a shortcut can be listed more than once when it conflicts
+    bindings: Vec<(String, String)>,
+}
+
+impl Keymap {
+    /// `overrides` replace the default shortcuts of their command, an empty
+    /// override unbinds it
+    pub fn new(overrides: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut bindings = Vec::new();
+        for (command, _, defaults) in DEFAULT_KEYMAP {
+            match overrides.get(command) {
+                Some(shortcuts) => bindings.extend(
+                    shortcuts
+                        .iter()
+                        .filter_map(|shortcut| normalize_shortcut(shortcut))
+                        .map(|shortcut| (shortcut, command.to_string())),
+                ),
+                None => bindings.extend(
+                    defaults
+                        .iter()
+                        .map(|shortcut| ((*shortcut).to_string(), command.to_string())),
+                ),
+            }
+        }
+
+        Self { bindings }
+    }
+
+    /// The command bound to `shortcut`, the first one if there is a conflict
+    pub fn command(&self, shortcut: &str) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound == shortcut)
+            .map(|(_, command)| command.as_str())
+    }
+
+    pub fn shortcuts(&self, command: &str) -> Vec<&str> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| bound == command)
+            .map(|(shortcut, _)| shortcut.as_str())
+            .collect()
+    }
+
+    /// Shortcuts bound to more than one command, with those commands
+    pub fn conflicts(&self) -> BTreeMap<&str, Vec<&str>> {
+        let mut commands: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (shortcut, command) in &self.bindings {
+            let bound = commands.entry(shortcut.as_str()).or_default();
+            if !bound.contains(&command.as_str()) {
+                bound.push(command.as_str());
+            }
+        }
+        commands.retain(|_, commands| commands.len() > 1);
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn punctuation_shortcuts() {
+        let overrides = BTreeMap::from([
+            ("settings".to_string(), vec!["Ctrl+,".to_string()]),
+            (
+                "zoom_in".to_string(),
+                vec!["Ctrl++".to_string(), "Ctrl+=".to_string()],
+            ),
+        ]);
+        let keymap = Keymap::new(&overrides);
+        assert_eq!(keymap.shortcuts("settings"), ["Ctrl+,"]);
+        assert_eq!(keymap.shortcuts("zoom_in"), ["Ctrl++", "Ctrl+="]);
+        assert_eq!(keymap.command("Ctrl+,"), Some("settings"));
+        assert_eq!(keymap.command("Ctrl++"), Some("zoom_in"));
+    }
+}
@@ -1,7 +1,50 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::PathBuf;
 
+mod codex;
+pub use codex::*;
+
+mod history;
+pub use history::*;
+
+mod annotations;
+pub use annotations::*;
+
+mod revision;
+pub use revision::*;
+
+mod theme;
+pub use theme::*;
+
+mod settings;
+pub use settings::*;
+
+mod keymap;
+pub use keymap::*;
+
+mod fuzzy;
+pub use fuzzy::*;
+
+mod template;
+pub use template::*;
+
+mod doctor;
+pub use doctor::*;
+
+mod encryption;
+pub use encryption::*;
+
+mod logging;
+pub use logging::*;
+
+mod recovery;
+pub use recovery::*;
+
+mod notes;
+pub use notes::*;
+
 #[derive(Serialize, Deserialize)]
 pub struct FileWriteData {
     pub path: String,
@@ -9,42 +52,211 @@ pub struct FileWriteData {
     pub content: String,
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+pub enum WritingStatus {
+    #[default]
+    Idea,
+    FirstDraft,
+    Revised,
+    Final,
+}
+
+impl WritingStatus {
+    pub const ALL: [Self; 4] = [Self::Idea, Self::FirstDraft, Self::Revised, Self::Final];
+}
+
+impl fmt::Display for WritingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Idea => "Idea",
+            Self::FirstDraft => "First Draft",
+            Self::Revised => "Revised",
+            Self::Final => "Final",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+/// Planning information for a chapter, shown on the corkboard and in the outliner.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct ChapterMeta {
+    /// Shown instead of the folder name, empty for chapters whose folder is their title
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub synopsis: String,
+    #[serde(default)]
+    pub status: WritingStatus,
+    #[serde(default)]
+    pub pov: String,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
-pub struct Settings {
-    pub theme: String,
-    pub interval: u32,
+pub struct Project {
+    pub path: PathBuf,
+    pub chapters: Vec<String>,
+    pub active_chapter: Option<usize>,
+    /// Keyed by chapter name, chapters without an entry use the default metadata.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, ChapterMeta>,
 }
 
-impl Settings {
-    pub fn new(theme: String, interval: u32) -> Settings {
-        Self { theme, interval }
+impl Project {
+    pub fn meta(&self, chapter: &str) -> ChapterMeta {
+        self.metadata.get(chapter).cloned().unwrap_or_default()
+    }
+
+    /// The name the chapter in the folder `chapter` is shown with
+    pub fn title(&self, chapter: &str) -> String {
+        self.metadata
+            .get(chapter)
+            .map(|meta| meta.title.trim())
+            .filter(|title| !title.is_empty())
+            .unwrap_or(chapter)
+            .to_string()
+    }
+
+    /// Renaming only changes the title, the folder of the chapter stays the same
+    pub fn rename_chapter(&mut self, chapter: &str, title: &str) {
+        let mut meta = self.meta(chapter);
+        title.clone_into(&mut meta.title);
+        if meta == ChapterMeta::default() {
+            self.metadata.remove(chapter);
+        } else {
+            self.metadata.insert(chapter.to_string(), meta);
+        }
+    }
+
+    pub fn remove_chapter(&mut self, chapter: &str) {
+        let Some(index) = self.chapters.iter().position(|x| x == chapter) else {
+            return;
+        };
+        self.chapters.remove(index);
+        self.metadata.remove(chapter);
+
+        self.active_chapter = match self.active_chapter {
+            Some(active) if active == index => None,
+            Some(active) if active > index => Some(active - 1),
+            active => active,
+        };
+    }
+
+    /// Inserts a chapter at `position`, or at the end if the project got shorter
+    pub fn insert_chapter(&mut self, position: usize, chapter: String, meta: ChapterMeta) {
+        let position = position.min(self.chapters.len());
+        self.chapters.insert(position, chapter.clone());
+        if meta != ChapterMeta::default() {
+            self.metadata.insert(chapter, meta);
+        }
+
+        if let Some(active) = self.active_chapter {
+            if active >= position {
+                self.active_chapter = Some(active + 1);
+            }
+        }
+    }
+
+    /// Moves the chapter at `from` so that it ends up in front of the chapter
+    /// currently at `to`, keeping `active_chapter` pointed at the same chapter.
+    pub fn move_chapter(&mut self, from: usize, to: usize) {
+        if from >= self.chapters.len() || to > self.chapters.len() {
+            return;
+        }
+
+        // Adjust index if it would be moved by removing the chapter
+        let adjusted = if from < to { to - 1 } else { to };
+
+        let chapter = self.chapters.remove(from);
+        self.chapters.insert(adjusted, chapter);
+
+        self.active_chapter = self.active_chapter.map(|active| {
+            if active == from {
+                adjusted
+            } else if from < active && active <= adjusted {
+                active - 1
+            } else if adjusted <= active && active < from {
+                active + 1
+            } else {
+                active
+            }
+        });
     }
 }
 
-impl Default for Settings {
-    fn default() -> Self {
-        Self {
-            theme: String::from("Light"),
-            interval: 300_000,
+/// Names Windows doesn't allow for files and folders, whatever their extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+pub fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default().trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(stem))
+}
+
+/// Folder name for a new chapter called `title` that works on every file system.
+/// It only has lowercase ASCII letters, digits and dashes, titles without any
+/// of them get a folder called `chapter`.
+pub fn chapter_folder_name(title: &str) -> String {
+    let mut name = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.is_empty() && !name.ends_with('-') {
+            name.push('-');
         }
     }
+    name.truncate(40);
+    let name = name.trim_end_matches('-');
+
+    if name.is_empty() {
+        "chapter".to_string()
+    } else if is_reserved_name(name) {
+        format!("{name}-chapter")
+    } else {
+        name.to_string()
+    }
 }
 
-impl fmt::Display for Settings {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "Settings:")?;
-        writeln!(f, "Theme: {:?}", self.theme)?;
-        writeln!(f, "Interval: {:?}", self.interval)?;
+/// A chapter that was moved to the `.trash/` folder of a project
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct TrashItem {
+    /// Name of the folder inside `.trash/`
+    pub id: String,
+    pub name: String,
+    /// Index in `Project.chapters` before deletion
+    pub position: usize,
+    /// Unix timestamp of the deletion
+    pub deleted: i64,
+    #[serde(default)]
+    pub meta: ChapterMeta,
+}
 
-        Ok(())
+impl TrashItem {
+    /// The name the chapter was shown with
+    pub fn title(&self) -> &str {
+        match self.meta.title.trim() {
+            "" => &self.name,
+            title => title,
+        }
     }
 }
 
+/// A project on the start screen, from the recent projects in the data folder
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
-pub struct Project {
+pub struct RecentProject {
     pub path: PathBuf,
-    pub chapters: Vec<String>,
-    pub active_chapter: Option<usize>,
+    pub name: String,
+    /// Pinned projects come first and are never dropped from the list
+    pub pinned: bool,
+    /// Unix timestamp of when the project was last opened
+    pub opened: i64,
+    /// Unix timestamp of the last change to a chapter or the project
+    pub edited: Option<i64>,
+    pub words: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
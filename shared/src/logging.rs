@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How important a log message is, from the most to the least important
+#[derive(Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|level| level.label() == label)
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Where a log message comes from
+pub const BACKEND: &str = "backend";
+pub const FRONTEND: &str = "frontend";
+
+/// Lines of a message after the first are indented by this in the log file
+const CONTINUATION: &str = "    ";
+
+/// One message of the log file
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct LogEntry {
+    /// Local time like `2025-01-31 18:04:59.123`
+    pub time: String,
+    pub level: LogLevel,
+    /// `BACKEND` or `FRONTEND`
+    pub source: String,
+    /// Module the message was logged in
+    pub target: String,
+    pub message: String,
+    /// Id of the command the frontend invoked, shared by the messages of frontend
+    /// and backend about it
+    #[serde(default)]
+    pub call: Option<u32>,
+}
+
+impl LogEntry {
+    /// The source with the call id, like `backend#12`
+    pub fn origin(&self) -> String {
+        match self.call {
+            Some(call) => format!("{}#{call}", self.source),
+            None => self.source.clone(),
+        }
+    }
+
+    /// The entry as it is written to the log file, one line per line of the message
+    pub fn to_line(&self) -> String {
+        format!(
+            "{} {:<5} {} {}: {}\n",
+            self.time,
+            self.level,
+            self.origin(),
+            self.target,
+            self.message
+                .trim_end()
+                .replace('\n', &format!("\n{CONTINUATION}"))
+        )
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        // The time has a space between date and time itself
+        let (date, rest) = line.split_once(' ')?;
+        let (clock, rest) = rest.split_once(' ')?;
+        let mut parts = rest.trim_start().splitn(3, ' ');
+        let level = LogLevel::from_label(parts.next()?)?;
+        let origin = parts.next()?;
+        let (source, call) = match origin.split_once('#') {
+            Some((source, call)) => (source, Some(call.parse().ok()?)),
+            None => (origin, None),
+        };
+        let (target, message) = parts.next()?.trim_start().split_once(": ")?;
+        Some(Self {
+            time: format!("{date} {clock}"),
+            level,
+            source: source.to_string(),
+            target: target.to_string(),
+            message: message.to_string(),
+            call,
+        })
+    }
+}
+
+/// Reads the entries of a log file. Lines that aren't entries are added to the
+/// message before them, or skipped at the start.
+pub fn parse_log(content: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for line in content.lines().filter(|line| !line.is_empty()) {
+        let entry = if line.starts_with(CONTINUATION) {
+            None
+        } else {
+            LogEntry::parse(line)
+        };
+        if let Some(entry) = entry {
+            entries.push(entry);
+        } else if let Some(last) = entries.last_mut() {
+            last.message.push('\n');
+            last.message
+                .push_str(line.strip_prefix(CONTINUATION).unwrap_or(line));
+        }
+    }
+    entries
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Lines of a match are cut to this many characters around the searched text
+const CONTEXT: usize = 40;
+
+/// A line of a note that contains the searched text
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct NoteMatch {
+    /// Folder of the chapter whose note it is, `None` for the note of the project
+    pub chapter: Option<String>,
+    pub line: usize,
+    /// The line around the searched text
+    pub text: String,
+}
+
+/// The lines of `content` containing `query`, ignoring case, with their number
+/// and the text around the first occurrence
+pub fn search_lines(content: &str, query: &str) -> Vec<(usize, String)> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(number, line)| {
+            let chars: Vec<char> = line.chars().collect();
+            let lowercase: Vec<String> = chars.iter().map(|c| c.to_lowercase().collect()).collect();
+            // Searched per character, lowercasing may change the length of the line
+            let start = (0..chars.len()).find(|&start| {
+                let mut rest = query.as_str();
+                for part in &lowercase[start..] {
+                    if rest.is_empty() {
+                        break;
+                    }
+                    match rest.strip_prefix(part.as_str()) {
+                        Some(after) => rest = after,
+                        None => return false,
+                    }
+                }
+                rest.is_empty()
+            })?;
+            let from = start.saturating_sub(CONTEXT);
+            let to = (start + query.chars().count() + CONTEXT).min(chars.len());
+            let mut text: String = chars[from..to].iter().collect();
+            if from > 0 {
+                text.insert(0, '…');
+            }
+            if to < chars.len() {
+                text.push('…');
+            }
+            Some((number, text.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Splits `[[target|label]]` into its target and the text shown for it
+pub fn split_link(link: &str) -> (&str, &str) {
+    match link.split_once('|') {
+        Some((target, label)) => (target.trim(), label.trim()),
+        None => (link.trim(), link.trim()),
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Text of a chapter that wasn't saved when PaperSmith last closed without shutting down properly
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RecoveredChapter {
+    pub id: String,
+    pub project: PathBuf,
+    /// Folder of the chapter
+    pub chapter: String,
+    pub title: String,
+    /// Unix timestamp of when the text was last written to the journal
+    pub saved: i64,
+}
+
+/// The recovered text next to the one in `Content.md`, to compare them
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct RecoveredText {
+    pub recovered: String,
+    pub saved: String,
+}
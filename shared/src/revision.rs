@@ -0,0 +1,364 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A piece of the text in revision mode. Text that is neither inserted nor
+/// deleted was already in the baseline.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct RevisionSpan {
+    pub text: String,
+    /// Round the text was inserted in
+    #[serde(default)]
+    pub inserted: Option<u32>,
+    /// Round the text was deleted in
+    #[serde(default)]
+    pub deleted: Option<u32>,
+}
+
+impl RevisionSpan {
+    pub const fn is_change(&self) -> bool {
+        self.inserted.is_some() || self.deleted.is_some()
+    }
+
+    /// The round the change was made in
+    pub fn round(&self) -> Option<u32> {
+        self.deleted.or(self.inserted)
+    }
+}
+
+/// Tracked changes of a chapter against a baseline snapshot of its text.
+///
+/// The spans hold the baseline together with all insertions and deletions
+/// since, in the order they appear in the text.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Revision {
+    /// The current revision round, starting at 1
+    pub round: u32,
+    pub spans: Vec<RevisionSpan>,
+}
+
+/// Part of the difference between two texts, lengths are in bytes
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Diff {
+    Equal(usize),
+    Delete(usize),
+    Insert(String),
+}
+
+/// Splits text into words, runs of whitespace and single other characters
+fn tokens(text: &str) -> Vec<&str> {
+    let class = |c: char| {
+        if c.is_alphanumeric() {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+    for (index, c) in text.char_indices() {
+        let current = class(c);
+        if index > start && (previous != Some(current) || current == 2) {
+            tokens.push(&text[start..index]);
+            start = index;
+        }
+        previous = Some(current);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+
+    tokens
+}
+
+/// Beyond this many edited tokens the texts are treated as entirely different
+const MAX_EDITS: isize = 1000;
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Step {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Shortest edit script between `a` and `b` after Myers, one step per token
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::many_single_char_names
+)]
+fn shortest_edit(a: &[&str], b: &[&str]) -> Vec<Step> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let at = |k: isize| (k + max) as usize;
+
+    let mut v = vec![0_isize; 2 * max as usize + 2];
+    // The furthest reaching paths after each number of edits
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut edits = 0;
+    'search: for d in 0..=max {
+        if d > MAX_EDITS {
+            let mut steps = vec![Step::Delete; a.len()];
+            steps.extend(vec![Step::Insert; b.len()]);
+            return steps;
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+
+            if x >= n && y >= m {
+                edits = d;
+                break 'search;
+            }
+        }
+        trace.push(v[at(-d)..=at(d)].to_vec());
+    }
+
+    let mut steps = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=edits).rev() {
+        let previous = &trace[(d - 1) as usize];
+        let get = |k: isize| previous[(k + d - 1) as usize];
+
+        let k = x - y;
+        let previous_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let previous_x = get(previous_k);
+        let previous_y = previous_x - previous_k;
+
+        while x > previous_x && y > previous_y {
+            steps.push(Step::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        if x == previous_x {
+            steps.push(Step::Insert);
+            y -= 1;
+        } else {
+            steps.push(Step::Delete);
+            x -= 1;
+        }
+    }
+    steps.extend((0..x.min(y)).map(|_| Step::Equal));
+
+    steps.reverse();
+    steps
+}
+
+/// The word wise difference between `old` and `new`
+pub fn diff(old: &str, new: &str) -> Vec<Diff> {
+    let (a, b) = (tokens(old), tokens(new));
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut steps = vec![Step::Equal; prefix];
+    steps.extend(shortest_edit(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+    ));
+    steps.extend(vec![Step::Equal; suffix]);
+
+    let (mut a, mut b) = (a.into_iter(), b.into_iter());
+    let mut diffs: Vec<Diff> = Vec::new();
+    for step in steps {
+        let token = match step {
+            Step::Equal => {
+                b.next();
+                Diff::Equal(a.next().map_or(0, str::len))
+            }
+            Step::Delete => Diff::Delete(a.next().map_or(0, str::len)),
+            Step::Insert => Diff::Insert(b.next().unwrap_or_default().to_string()),
+        };
+
+        match (diffs.last_mut(), token) {
+            (Some(Diff::Equal(last)), Diff::Equal(length))
+            | (Some(Diff::Delete(last)), Diff::Delete(length)) => *last += length,
+            (Some(Diff::Insert(last)), Diff::Insert(text)) => last.push_str(&text),
+            (_, token) => diffs.push(token),
+        }
+    }
+
+    diffs
+}
+
+/// Moves `length` bytes of visible text from the front of `queue` to `output`,
+/// passing each piece through `map`. Deleted spans on the way are kept.
+fn take_visible(
+    queue: &mut VecDeque<RevisionSpan>,
+    mut length: usize,
+    output: &mut Vec<RevisionSpan>,
+    mut map: impl FnMut(RevisionSpan) -> Option<RevisionSpan>,
+) {
+    while length > 0 {
+        let Some(mut span) = queue.pop_front() else {
+            return;
+        };
+        if span.deleted.is_some() {
+            output.push(span);
+            continue;
+        }
+
+        if span.text.len() > length {
+            let rest = span.text.split_off(length);
+            queue.push_front(RevisionSpan {
+                text: rest,
+                ..span.clone()
+            });
+        }
+        length -= span.text.len();
+        output.extend(map(span));
+    }
+}
+
+impl Revision {
+    /// Starts the first round with `baseline` as the unchanged text
+    pub fn new(baseline: &str) -> Self {
+        let mut revision = Self {
+            round: 1,
+            spans: vec![RevisionSpan {
+                text: baseline.to_string(),
+                inserted: None,
+                deleted: None,
+            }],
+        };
+        revision.normalize();
+        revision
+    }
+
+    /// The text with all changes applied
+    pub fn text(&self) -> String {
+        self.spans
+            .iter()
+            .filter(|span| span.deleted.is_none())
+            .map(|span| span.text.as_str())
+            .collect()
+    }
+
+    /// Records the difference between the tracked text and `current` as changes of this round
+    pub fn update(&mut self, current: &str) {
+        let old = self.text();
+        if old == current {
+            return;
+        }
+
+        let round = self.round;
+        let mut queue: VecDeque<RevisionSpan> = std::mem::take(&mut self.spans).into();
+        let mut spans = Vec::with_capacity(queue.len() + 2);
+        for part in diff(&old, current) {
+            match part {
+                Diff::Equal(length) => take_visible(&mut queue, length, &mut spans, Some),
+                // Text inserted in this round just goes away again
+                Diff::Delete(length) => take_visible(&mut queue, length, &mut spans, |mut span| {
+                    if span.inserted == Some(round) {
+                        None
+                    } else {
+                        span.deleted = Some(round);
+                        Some(span)
+                    }
+                }),
+                Diff::Insert(text) => spans.push(RevisionSpan {
+                    text,
+                    inserted: Some(round),
+                    deleted: None,
+                }),
+            }
+        }
+        spans.extend(queue);
+
+        self.spans = spans;
+        self.normalize();
+    }
+
+    pub fn next_round(&mut self) {
+        self.round += 1;
+    }
+
+    /// Indices of the spans that are changes
+    pub fn changes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.spans
+            .iter()
+            .enumerate()
+            .filter(|(_, span)| span.is_change())
+            .map(|(index, _)| index)
+    }
+
+    /// Makes the change at span `index` part of the baseline
+    pub fn accept(&mut self, index: usize) {
+        let Some(span) = self.spans.get_mut(index) else {
+            return;
+        };
+        if span.deleted.is_some() {
+            self.spans.remove(index);
+        } else {
+            span.inserted = None;
+        }
+        self.normalize();
+    }
+
+    /// Undoes the change at span `index`
+    pub fn reject(&mut self, index: usize) {
+        let Some(span) = self.spans.get_mut(index) else {
+            return;
+        };
+        if span.deleted.is_some() {
+            span.deleted = None;
+        } else if span.inserted.is_some() {
+            self.spans.remove(index);
+        }
+        self.normalize();
+    }
+
+    pub fn accept_all(&mut self) {
+        self.spans.retain(|span| span.deleted.is_none());
+        for span in &mut self.spans {
+            span.inserted = None;
+        }
+        self.normalize();
+    }
+
+    /// Goes back to the baseline
+    pub fn reject_all(&mut self) {
+        self.spans.retain(|span| span.inserted.is_none());
+        for span in &mut self.spans {
+            span.deleted = None;
+        }
+        self.normalize();
+    }
+
+    /// Merges neighbouring spans of the same kind and drops empty ones
+    fn normalize(&mut self) {
+        let mut spans: Vec<RevisionSpan> = Vec::with_capacity(self.spans.len());
+        for span in std::mem::take(&mut self.spans) {
+            if span.text.is_empty() {
+                continue;
+            }
+            match spans.last_mut() {
+                Some(last) if last.inserted == span.inserted && last.deleted == span.deleted => {
+                    last.text.push_str(&span.text);
+                }
+                _ => spans.push(span),
+            }
+        }
+        self.spans = spans;
+    }
+}
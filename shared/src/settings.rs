@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{command_label, normalize_shortcut, LogLevel, SYSTEM_THEME};
+
+/// Version of the settings format, older `settings.json` files are migrated when loaded
+pub const SETTINGS_VERSION: u32 = 2;
+
+/// Format the export menu starts with
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Html,
+    Docx,
+}
+
+impl ExportFormat {
+    pub const ALL: [Self; 3] = [Self::Markdown, Self::Html, Self::Docx];
+
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+            Self::Docx => "docx",
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Markdown => "Markdown",
+            Self::Html => "HTML",
+            Self::Docx => "DOCX (with tracked changes)",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+}
+
+/// Missing fields take their default, so partial and older files still load
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub version: u32,
+    pub theme: String,
+    /// Theme used while the system is in light mode and `theme` is "System"
+    pub light_theme: String,
+    /// Theme used while the system is in dark mode and `theme` is "System"
+    pub dark_theme: String,
+    /// Minutes between automatic saves, 0 turns autosaving off
+    pub autosave_interval: u32,
+    /// Days deleted chapters are kept in the trash, 0 keeps them forever
+    pub trash_retention: u32,
+    /// Whether the undo history of a chapter is saved next to it
+    pub persist_history: bool,
+    /// Whether the last project opens on launch instead of the start screen
+    pub reopen_last_project: bool,
+    /// CSS font family of the editor
+    pub editor_font: String,
+    /// Line height of the editor in percent of the font size
+    pub line_height: u32,
+    /// Maximum width of the editor text in characters, 0 uses the whole pane
+    pub page_width: u32,
+    /// Language tag like `en-US` the spellchecker uses, empty turns it off
+    pub spellcheck_language: String,
+    pub export_format: ExportFormat,
+    /// Number of backups kept per project, one is made whenever it is opened.
+    /// 0 keeps all of them.
+    pub backup_retention: u32,
+    /// Minutes without input after which an encrypted project locks, 0 never locks it
+    pub lock_after: u32,
+    /// Least important messages that are written to the log
+    pub log_level: LogLevel,
+    /// Shortcuts that replace those of the default keymap, by command.
+    /// No shortcuts unbind the command.
+    pub keybindings: BTreeMap<String, Vec<String>>,
+}
+
+/// Result of reading `settings.json`, `errors` lists everything that had to be reset
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+pub struct LoadedSettings {
+    pub settings: Settings,
+    pub errors: Vec<String>,
+}
+
+pub const LINE_HEIGHTS: std::ops::RangeInclusive<u32> = 100..=300;
+pub const PAGE_WIDTHS: std::ops::RangeInclusive<u32> = 30..=200;
+
+impl Settings {
+    /// The theme to show, following the system when `theme` is "System"
+    pub fn active_theme(&self, dark_mode: bool) -> &str {
+        if self.theme != SYSTEM_THEME {
+            &self.theme
+        } else if dark_mode {
+            &self.dark_theme
+        } else {
+            &self.light_theme
+        }
+    }
+
+    /// Resets every field with an invalid value to its default and
+    /// returns what was wrong
+    pub fn validate(&mut self) -> Vec<String> {
+        let default = Self::default();
+        let mut errors = Vec::new();
+
+        if self.theme.trim().is_empty() {
+            errors.push("theme: must not be empty".to_string());
+            self.theme = default.theme;
+        }
+        if self.light_theme.trim().is_empty() {
+            errors.push("light_theme: must not be empty".to_string());
+            self.light_theme = default.light_theme;
+        }
+        if self.dark_theme.trim().is_empty() {
+            errors.push("dark_theme: must not be empty".to_string());
+            self.dark_theme = default.dark_theme;
+        }
+        if self.autosave_interval > 60 {
+            errors.push(format!(
+                "autosave_interval: {} minutes is more than an hour",
+                self.autosave_interval
+            ));
+            self.autosave_interval = default.autosave_interval;
+        }
+        if self.lock_after > 240 {
+            errors.push(format!(
+                "lock_after: {} minutes is more than four hours",
+                self.lock_after
+            ));
+            self.lock_after = default.lock_after;
+        }
+        if self.editor_font.trim().is_empty() {
+            errors.push("editor_font: must not be empty".to_string());
+            self.editor_font = default.editor_font;
+        }
+        if !LINE_HEIGHTS.contains(&self.line_height) {
+            errors.push(format!(
+                "line_height: {}% is not between {}% and {}%",
+                self.line_height,
+                LINE_HEIGHTS.start(),
+                LINE_HEIGHTS.end()
+            ));
+            self.line_height = default.line_height;
+        }
+        if self.page_width != 0 && !PAGE_WIDTHS.contains(&self.page_width) {
+            errors.push(format!(
+                "page_width: {} characters is not between {} and {}",
+                self.page_width,
+                PAGE_WIDTHS.start(),
+                PAGE_WIDTHS.end()
+            ));
+            self.page_width = default.page_width;
+        }
+        let language_tag = self
+            .spellcheck_language
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()));
+        if !self.spellcheck_language.is_empty() && !language_tag {
+            errors.push(format!(
+                "spellcheck_language: \"{}\" is not a language tag like \"en-US\"",
+                self.spellcheck_language
+            ));
+            self.spellcheck_language = default.spellcheck_language;
+        }
+        self.keybindings.retain(|command, shortcuts| {
+            if command_label(command).is_none() {
+                errors.push(format!("keybindings: \"{command}\" is not a command"));
+                return false;
+            }
+            let invalid = shortcuts
+                .iter()
+                .find(|shortcut| normalize_shortcut(shortcut).is_none());
+            if let Some(shortcut) = invalid {
+                errors.push(format!(
+                    "keybindings: \"{shortcut}\" of \"{command}\" is not a shortcut"
+                ));
+            }
+            invalid.is_none()
+        });
+
+        errors
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            theme: String::from("Light"),
+            light_theme: String::from("Light"),
+            dark_theme: String::from("Dark"),
+            autosave_interval: 5,
+            trash_retention: 30,
+            persist_history: true,
+            reopen_last_project: false,
+            editor_font: String::from("Arial, sans-serif"),
+            line_height: 150,
+            page_width: 0,
+            spellcheck_language: String::from("en-US"),
+            export_format: ExportFormat::default(),
+            backup_retention: 10,
+            lock_after: 10,
+            log_level: LogLevel::default(),
+            keybindings: BTreeMap::new(),
+        }
+    }
+}
+
+impl fmt::Display for Settings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Settings:")?;
+        writeln!(f, "Version: {:?}", self.version)?;
+        writeln!(f, "Theme: {:?}", self.theme)?;
+        writeln!(f, "Light Theme: {:?}", self.light_theme)?;
+        writeln!(f, "Dark Theme: {:?}", self.dark_theme)?;
+        writeln!(f, "Autosave Interval: {:?}", self.autosave_interval)?;
+        writeln!(f, "Trash Retention: {:?}", self.trash_retention)?;
+        writeln!(f, "Persist History: {:?}", self.persist_history)?;
+        writeln!(f, "Reopen Last Project: {:?}", self.reopen_last_project)?;
+        writeln!(f, "Editor Font: {:?}", self.editor_font)?;
+        writeln!(f, "Line Height: {:?}", self.line_height)?;
+        writeln!(f, "Page Width: {:?}", self.page_width)?;
+        writeln!(f, "Spellcheck Language: {:?}", self.spellcheck_language)?;
+        writeln!(f, "Export Format: {:?}", self.export_format)?;
+        writeln!(f, "Backup Retention: {:?}", self.backup_retention)?;
+        writeln!(f, "Lock After: {:?}", self.lock_after)?;
+        writeln!(f, "Log Level: {:?}", self.log_level)?;
+        writeln!(f, "Keybindings: {:?}", self.keybindings)?;
+
+        Ok(())
+    }
+}
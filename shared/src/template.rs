@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{ChapterMeta, CodexEntry, ExportFormat, Settings};
+
+/// A chapter a template starts the project with
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TemplateChapter {
+    pub name: String,
+    pub content: String,
+    pub note: String,
+    pub meta: ChapterMeta,
+}
+
+/// Settings a template brings along, they replace the current settings when a
+/// project is created from it. Settings that are `None` stay as they are.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TemplateSettings {
+    pub editor_font: Option<String>,
+    pub line_height: Option<u32>,
+    pub page_width: Option<u32>,
+    pub export_format: Option<ExportFormat>,
+}
+
+impl TemplateSettings {
+    /// Takes the editor and export settings of `settings`, for saving a project
+    /// as a template
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            editor_font: Some(settings.editor_font.clone()),
+            line_height: Some(settings.line_height),
+            page_width: Some(settings.page_width),
+            export_format: Some(settings.export_format),
+        }
+    }
+
+    pub fn apply(&self, settings: &mut Settings) {
+        if let Some(editor_font) = &self.editor_font {
+            settings.editor_font.clone_from(editor_font);
+        }
+        if let Some(line_height) = self.line_height {
+            settings.line_height = line_height;
+        }
+        if let Some(page_width) = self.page_width {
+            settings.page_width = page_width;
+        }
+        if let Some(export_format) = self.export_format {
+            settings.export_format = export_format;
+        }
+    }
+
+    /// Describes every setting the template changes, for its preview
+    pub fn describe(&self) -> Vec<String> {
+        let mut changes = Vec::new();
+        if let Some(editor_font) = &self.editor_font {
+            // The first family of the CSS font list is the one that shows
+            let family = editor_font.split(',').next().unwrap_or_default();
+            changes.push(format!("Editor font: {}", family.trim().trim_matches('"')));
+        }
+        if let Some(line_height) = self.line_height {
+            changes.push(format!("Line height: {line_height}%"));
+        }
+        match self.page_width {
+            Some(0) => changes.push("Page width: full".to_string()),
+            Some(page_width) => changes.push(format!("Page width: {page_width} characters")),
+            None => {}
+        }
+        if let Some(export_format) = self.export_format {
+            changes.push(format!("Export format: {}", export_format.label()));
+        }
+        changes
+    }
+}
+
+/// Everything a new project can start with besides the empty folders
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default)]
+#[serde(default)]
+pub struct ProjectTemplate {
+    pub name: String,
+    pub description: String,
+    /// Content of the `Note.md` of the project
+    pub note: String,
+    pub chapters: Vec<TemplateChapter>,
+    pub codex: Vec<CodexEntry>,
+    pub settings: TemplateSettings,
+    /// Built-in templates come with PaperSmith and can't be deleted
+    pub builtin: bool,
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+
+/// Themes that ship with `PaperSmith`, custom themes can't use their names
+pub const BUILTIN_THEMES: [&str; 5] = ["Light", "Light Dark", "Medium", "Dark", "Very Dark"];
+
+/// Theme setting that switches between a light and a dark theme with the system
+pub const SYSTEM_THEME: &str = "System";
+
+/// The palette of a theme, every color is a hex color like `#1e1e2e`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ThemeColors {
+    pub primary: String,
+    pub secondary: String,
+    pub accent: String,
+    pub text: String,
+    pub subtext: String,
+    pub base: String,
+    pub mantle: String,
+    pub crust: String,
+}
+
+impl ThemeColors {
+    /// The colors together with their names
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("primary", self.primary.as_str()),
+            ("secondary", self.secondary.as_str()),
+            ("accent", self.accent.as_str()),
+            ("text", self.text.as_str()),
+            ("subtext", self.subtext.as_str()),
+            ("base", self.base.as_str()),
+            ("mantle", self.mantle.as_str()),
+            ("crust", self.crust.as_str()),
+        ]
+        .into_iter()
+    }
+}
+
+/// A user theme from a TOML or JSON file in `<data_dir>/PaperSmith/themes/`
+///
+/// ```toml
+/// name = "Solarized"
+///
+/// [colors]
+/// primary = "#268bd2"
+/// ...
+/// ```
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub colors: ThemeColors,
+}
+
+/// A theme file as found in the themes folder, `theme` is only set when it is valid
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct ThemeFile {
+    pub file: String,
+    pub theme: Option<Theme>,
+    pub errors: Vec<String>,
+}
+
+/// Parses `#rgb` and `#rrggbb` colors
+pub fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hex.len() {
+        3 => {
+            let mut rgb = [0; 3];
+            for (channel, c) in rgb.iter_mut().zip(hex.chars()) {
+                let value = u8::from_str_radix(&c.to_string(), 16).ok()?;
+                *channel = value * 17;
+            }
+            Some(rgb)
+        }
+        6 => {
+            let mut rgb = [0; 3];
+            for (index, channel) in rgb.iter_mut().enumerate() {
+                *channel = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok()?;
+            }
+            Some(rgb)
+        }
+        _ => None,
+    }
+}
+
+impl Theme {
+    /// Everything wrong with the theme, empty if it can be used
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        let name = self.name.trim();
+        if name.is_empty() {
+            errors.push("The theme has no name".to_string());
+        } else if BUILTIN_THEMES
+            .iter()
+            .chain([&SYSTEM_THEME])
+            .any(|builtin| builtin.eq_ignore_ascii_case(name))
+        {
+            errors.push(format!("\"{name}\" is the name of a built-in theme"));
+        }
+
+        for (key, color) in self.colors.iter() {
+            if parse_color(color).is_none() {
+                errors.push(format!("{key}: \"{color}\" is not a hex color"));
+            }
+        }
+
+        errors
+    }
+
+    /// The palette as the CSS variables tailwindcss-themer reads the colors from
+    pub fn css_variables(&self) -> String {
+        self.colors
+            .iter()
+            .filter_map(|(key, color)| {
+                let [r, g, b] = parse_color(color)?;
+                Some(format!("--colors-{key}: {r} {g} {b};"))
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
@@ -0,0 +1,26 @@
+use shared::{find_comments, ChapterComments, Project};
+
+use crate::encryption::read_text;
+
+/// The comments of every chapter of the project that has any, in chapter order
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn list_comments(project: Project) -> Vec<ChapterComments> {
+    project
+        .chapters
+        .iter()
+        .filter_map(|chapter| {
+            let mut content_path = project.path.clone();
+            content_path.push("Chapters");
+            content_path.push(chapter);
+            content_path.push("Content.md");
+
+            let content = read_text(&content_path).ok()?;
+            let comments = find_comments(&content);
+            (!comments.is_empty()).then(|| ChapterComments {
+                chapter: chapter.clone(),
+                comments,
+            })
+        })
+        .collect()
+}
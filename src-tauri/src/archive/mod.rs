@@ -0,0 +1,368 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use chrono::Utc;
+use log::{info, warn};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use shared::{bytes_checksum, Project};
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::recent::open_project_at;
+
+pub const ARCHIVE_EXTENSION: &str = "papersmith";
+const MANIFEST: &str = ".papersmith-manifest.json";
+/// The statistics are kept in the data folder, in the archive they are in this folder
+const STATISTICS: &str = ".statistics";
+/// Archives of newer versions may hold files this version doesn't know how to open
+const ARCHIVE_VERSION: u32 = 1;
+
+/// Folder of a project its backups are kept in
+const BACKUPS: &str = "Backups";
+
+/// Lists the files of an archive, to find out if it is complete and unchanged
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// Name of the project folder
+    name: String,
+    /// Unix timestamp of when the archive was packed
+    created: i64,
+    /// Checksums keyed by the path of the file in the archive
+    files: BTreeMap<String, u64>,
+}
+
+fn statistics_path() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_default()
+        .join("PaperSmith")
+        .join("Statistics")
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    path.is_file() && path.extension().is_some_and(|x| x == ARCHIVE_EXTENSION)
+}
+
+/// Every file below `dir`, with its path in the archive below `prefix`
+fn collect_files(dir: &Path, prefix: &str, skip: &[&str], files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if skip.contains(&name.as_str()) {
+            continue;
+        }
+        let archive_path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, &archive_path, &[], files);
+        } else {
+            files.push((archive_path, path));
+        }
+    }
+}
+
+fn write_archive(target: &Path, name: &str, files: &[(String, PathBuf)]) -> ZipResult<()> {
+    let mut zip = ZipWriter::new(File::create(target)?);
+    let options = SimpleFileOptions::default();
+    let mut manifest = Manifest {
+        version: ARCHIVE_VERSION,
+        name: name.to_string(),
+        created: Utc::now().timestamp(),
+        files: BTreeMap::new(),
+    };
+
+    for (archive_path, path) in files {
+        let bytes = fs::read(path)?;
+        manifest
+            .files
+            .insert(archive_path.clone(), bytes_checksum(&bytes));
+        zip.start_file(archive_path.as_str(), options)?;
+        zip.write_all(&bytes)?;
+    }
+
+    zip.start_file(MANIFEST, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest).unwrap().as_bytes())?;
+    zip.finish()?;
+    Ok(())
+}
+
+/// Packs the project into a single `.papersmith` file at a place the user picks.
+/// Exports are left out, backups and the writing statistics only go in when asked for.
+/// Returns the path of the archive.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn pack_project(project: Project, backups: bool, statistics: bool) -> Option<String> {
+    let name = project.path.file_name()?.to_string_lossy().to_string();
+    let target = FileDialog::new()
+        .set_title("Pack Project")
+        .set_file_name(format!("{name}.{ARCHIVE_EXTENSION}"))
+        .add_filter("PaperSmith Archive", &[ARCHIVE_EXTENSION])
+        .save_file()?;
+
+    let mut skip = vec!["Exports", MANIFEST];
+    if !backups {
+        skip.push(BACKUPS);
+    }
+    let mut files = Vec::new();
+    collect_files(&project.path, "", &skip, &mut files);
+    if statistics {
+        collect_files(&statistics_path(), STATISTICS, &[], &mut files);
+    }
+
+    match write_archive(&target, &name, &files) {
+        Ok(()) => {
+            info!("Packed {} files of {name} into {target:?}", files.len());
+            Some(target.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            warn!("Error while packing project: {e}");
+            let _ = fs::remove_file(&target);
+            None
+        }
+    }
+}
+
+/// The backups in `backups_path`, from the oldest to the newest
+fn list_backups(backups_path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = backups_path.read_dir() else {
+        return Vec::new();
+    };
+    let mut backups: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| is_archive(path))
+        .collect();
+    backups.sort();
+    backups
+}
+
+/// Whether `backup` holds exactly `files`, compared by their checksums
+fn is_backed_up(backup: &Path, files: &[(String, PathBuf)]) -> bool {
+    let Ok(manifest) = File::open(backup)
+        .map_err(|e| e.to_string())
+        .and_then(|file| ZipArchive::new(file).map_err(|e| e.to_string()))
+        .and_then(|mut zip| read_manifest(&mut zip))
+    else {
+        return false;
+    };
+    manifest.files.len() == files.len()
+        && files.iter().all(|(archive_path, path)| {
+            fs::read(path).is_ok_and(|bytes| {
+                manifest.files.get(archive_path) == Some(&bytes_checksum(&bytes))
+            })
+        })
+}
+
+/// Projects that were backed up since PaperSmith started
+static BACKED_UP: Mutex<BTreeSet<PathBuf>> = Mutex::new(BTreeSet::new());
+
+/// Packs the project at `path` into its `Backups/` folder, once per session and
+/// only if it changed since its newest backup. The backups beyond the newest
+/// `keep` ones are removed, 0 keeps all of them.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn backup_project(path: String, keep: u32) {
+    let project_path = PathBuf::from(path);
+    let Some(name) = project_path
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+    else {
+        return;
+    };
+    if !BACKED_UP.lock().unwrap().insert(project_path.clone()) {
+        return;
+    }
+    let backups_path = project_path.join(BACKUPS);
+
+    let mut files = Vec::new();
+    collect_files(
+        &project_path,
+        "",
+        &["Exports", BACKUPS, MANIFEST],
+        &mut files,
+    );
+    let mut backups = list_backups(&backups_path);
+    if backups
+        .last()
+        .is_some_and(|backup| is_backed_up(backup, &files))
+    {
+        info!("{name} did not change since its last backup");
+    } else {
+        // The time comes first, so the names sort from the oldest to the newest backup
+        let target = backups_path.join(format!(
+            "{} {name}.{ARCHIVE_EXTENSION}",
+            Utc::now().format("%Y-%m-%d %H-%M-%S")
+        ));
+        let result = fs::create_dir_all(&backups_path)
+            .map_err(Into::into)
+            .and_then(|()| write_archive(&target, &name, &files));
+        match result {
+            Ok(()) => {
+                info!("Backed up {name} into {target:?}");
+                backups.push(target);
+            }
+            Err(e) => {
+                warn!("Error while backing up project: {e}");
+                let _ = fs::remove_file(&target);
+            }
+        }
+    }
+
+    if keep == 0 {
+        return;
+    }
+    let excess = backups.len().saturating_sub(keep as usize);
+    for backup in &backups[..excess] {
+        match fs::remove_file(backup) {
+            Ok(()) => info!("Removed old backup {backup:?}"),
+            Err(e) => warn!("Error while removing old backup {backup:?}: {e}"),
+        }
+    }
+}
+
+fn read_manifest(zip: &mut ZipArchive<File>) -> Result<Manifest, String> {
+    let mut content = String::new();
+    zip.by_name(MANIFEST)
+        .map_err(|e| format!("the archive has no manifest: {e}"))?
+        .read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    let manifest: Manifest =
+        serde_json::from_str(&content).map_err(|e| format!("the manifest is not readable: {e}"))?;
+    if manifest.version > ARCHIVE_VERSION {
+        return Err("the archive was made by a newer version of PaperSmith".to_string());
+    }
+    Ok(manifest)
+}
+
+/// The files of an archive that go into the statistics, by their path below the statistics folder
+type Statistics = Vec<(PathBuf, Vec<u8>)>;
+
+/// Writes the project files of `zip` into `target`, checking each against the
+/// manifest. The statistics are only returned, nothing is written outside of
+/// `target` before the whole archive turned out to be fine.
+fn extract(
+    zip: &mut ZipArchive<File>,
+    manifest: &Manifest,
+    target: &Path,
+) -> Result<Statistics, String> {
+    let mut extracted = 0;
+    let mut statistics = Vec::new();
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index).map_err(|e| e.to_string())?;
+        let name = file.name().to_string();
+        if file.is_dir() || name == MANIFEST {
+            continue;
+        }
+        // Paths leaving the project folder are never written
+        let Some(relative) = file.enclosed_name() else {
+            return Err(format!("{name} is outside of the project"));
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+        if manifest.files.get(&name) != Some(&bytes_checksum(&bytes)) {
+            return Err(format!("{name} is damaged or was changed"));
+        }
+        extracted += 1;
+
+        if let Ok(statistic) = relative.strip_prefix(STATISTICS) {
+            statistics.push((statistic.to_path_buf(), bytes));
+            continue;
+        }
+        let path = target.join(&relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    }
+
+    if extracted == manifest.files.len() {
+        Ok(statistics)
+    } else {
+        Err("files listed in the manifest are missing".to_string())
+    }
+}
+
+/// A path in `parent` named `name` that doesn't exist yet, later ones get a number
+fn unused_path(parent: &Path, name: &str) -> PathBuf {
+    let mut path = parent.join(name);
+    let mut index = 2;
+    while path.exists() {
+        path = parent.join(format!("{name} {index}"));
+        index += 1;
+    }
+    path
+}
+
+/// Unpacks the archive at `path` into a new folder next to it and returns the folder.
+/// It is unpacked into a hidden folder first, a broken archive leaves nothing behind.
+pub fn unpack_archive(path: &Path) -> Result<PathBuf, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut zip = ZipArchive::new(file).map_err(|e| format!("not a PaperSmith archive: {e}"))?;
+    let manifest = read_manifest(&mut zip)?;
+
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    // Only the last part of the name, it must not point anywhere else
+    let name = Path::new(manifest.name.trim())
+        .file_name()
+        .or_else(|| path.file_stem())
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let unpacking = unused_path(parent, &format!(".{name}.unpacking"));
+    fs::create_dir_all(&unpacking).map_err(|e| e.to_string())?;
+
+    let statistics = match extract(&mut zip, &manifest, &unpacking) {
+        Ok(statistics) => statistics,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&unpacking);
+            return Err(e);
+        }
+    };
+    let target = unused_path(parent, &name);
+    if let Err(e) = fs::rename(&unpacking, &target) {
+        let _ = fs::remove_dir_all(&unpacking);
+        return Err(e.to_string());
+    }
+    // Empty folders aren't in the archive
+    for folder in ["Chapters", "Extras", BACKUPS, "Exports"] {
+        let _ = fs::create_dir_all(target.join(folder));
+    }
+
+    // Statistics of the same session are the same file, they aren't overwritten
+    for (statistic, bytes) in statistics {
+        let statistic_path = statistics_path().join(statistic);
+        if statistic_path.exists() {
+            continue;
+        }
+        let written = statistic_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&statistic_path, bytes));
+        if let Err(e) = written {
+            warn!("Error while adding statistics {statistic_path:?}: {e}");
+        }
+    }
+    info!("Unpacked {path:?} into {target:?}");
+    Ok(target)
+}
+
+/// Lets the user pick a `.papersmith` archive and opens the project in it
+#[tauri::command]
+pub fn open_archive() -> Option<Project> {
+    FileDialog::new()
+        .set_title("Open Project Archive")
+        .add_filter("PaperSmith Archive", &[ARCHIVE_EXTENSION])
+        .pick_file()
+        .and_then(open_project_at)
+}
@@ -1,4 +1,5 @@
 use rfd::FileDialog;
+use shared::is_reserved_name;
 use std::fs;
 use std::io::ErrorKind;
 use std::path::Path;
@@ -30,16 +31,14 @@ pub fn can_create_path(path: &str) -> String {
         return "Path does not have a parent directory.".into();
     }
 
-    //#[cfg(target_os = "windows")]
-    //{
-    //    let reserved_names = [
-    //        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7",
-    //        "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
-    //    ];
-    //    if reserved_names.contains(&path.file_name().unwrap_or_default().to_str().unwrap_or("")) {
-    //        return "The path uses a reserved name.".into();
-    //    }
-    //}
+    // Checked everywhere, projects are moved between systems
+    let file_name = parsed_path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    if is_reserved_name(&file_name) {
+        return "The path uses a reserved name.".into();
+    }
 
     let temp_file_path = parsed_path
         .parent()
@@ -0,0 +1,41 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::{info, warn};
+use tauri::{GlobalWindowEvent, Window, WindowEvent};
+
+/// Sent to the window when it is closed with unsaved changes, the frontend asks what to do with them
+const CLOSE_REQUESTED: &str = "close-requested";
+
+/// Whether the open chapter has unsaved changes, as told by the frontend
+static UNSAVED: AtomicBool = AtomicBool::new(false);
+
+/// Tells whether closing the window has to wait for the user to decide about unsaved changes
+#[tauri::command]
+pub fn set_unsaved(changes: bool) {
+    UNSAVED.store(changes, Ordering::SeqCst);
+}
+
+/// Keeps the window open while there are unsaved changes and lets the frontend ask first
+pub fn on_window_event(event: &GlobalWindowEvent) {
+    let WindowEvent::CloseRequested { api, .. } = event.event() else {
+        return;
+    };
+    if !UNSAVED.load(Ordering::SeqCst) {
+        return;
+    }
+    api.prevent_close();
+    info!("Closing waits for the unsaved changes");
+    if let Err(e) = event.window().emit(CLOSE_REQUESTED, ()) {
+        warn!("Error while emitting close request: {e}");
+    }
+}
+
+/// Closes the window once the unsaved changes are saved or discarded
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn close_window(window: Window) {
+    UNSAVED.store(false, Ordering::SeqCst);
+    if let Err(e) = window.close() {
+        warn!("Error while closing the window: {e}");
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use rfd::FileDialog;
+use shared::{codex_name_error, find_mentions, CodexEntry, CodexKind, CodexMention, Project};
+use tauri::{AppHandle, Manager};
+
+use crate::encryption::{read_text, write_text};
+
+fn read_entry(entry_path: &Path) -> Option<CodexEntry> {
+    let content = fs::read_to_string(entry_path.join("Entry.json")).ok()?;
+    let mut entry: CodexEntry = match serde_json::from_str(&content) {
+        Ok(entry) => entry,
+        Err(e) => {
+            warn!("Codex entry {entry_path:?} is not readable: {e}");
+            return None;
+        }
+    };
+    // The name leads to the folder of the entry, it must not lead out of `Extras/`
+    if let Some(error) = codex_name_error(&entry.name) {
+        warn!("Codex entry {entry_path:?} has an invalid name: {error}");
+        return None;
+    }
+    entry.notes = read_text(&entry_path.join("Notes.md")).unwrap_or_default();
+
+    Some(entry)
+}
+
+/// The codex entries of the project at `project_path`
+pub fn codex_entries(project_path: &Path) -> Vec<CodexEntry> {
+    let extras_path = project_path.join("Extras");
+
+    let mut entries = Vec::new();
+    for kind in CodexKind::ALL {
+        let Ok(dir) = extras_path.join(kind.folder()).read_dir() else {
+            continue;
+        };
+        for entry_path in dir.filter_map(Result::ok).map(|x| x.path()) {
+            if let Some(entry) = read_entry(&entry_path) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.name.cmp(&b.name)));
+    entries
+}
+
+/// The codex entries of the project at `path`. Their images can be shown from
+/// now on, the asset protocol only serves the `Extras/` folders of opened projects.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn list_codex_entries(app: AppHandle, path: String) -> Vec<CodexEntry> {
+    let project_path = PathBuf::from(path);
+    let extras_path = project_path.join("Extras");
+    if let Err(e) = app
+        .asset_protocol_scope()
+        .allow_directory(&extras_path, true)
+    {
+        warn!("Error while allowing codex images of {extras_path:?}: {e}");
+    }
+
+    codex_entries(&project_path)
+}
+
+/// Writes `entry` into its folder, moving the folder of `previous` there.
+/// Returns what went wrong, empty if it worked.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn write_codex_entry(path: String, previous: Option<CodexEntry>, entry: CodexEntry) -> String {
+    if let Some(error) = codex_name_error(&entry.name) {
+        warn!("Codex entry {:?} has an invalid name: {error}", entry.name);
+        return error.to_string();
+    }
+    let project_path = PathBuf::from(path);
+    let entry_path = entry.dir(&project_path);
+
+    // Renamed or retyped entries take their images with them
+    let previous = previous.filter(|previous| codex_name_error(&previous.name).is_none());
+    if let Some(previous) = previous {
+        let previous_path = previous.dir(&project_path);
+        if previous_path != entry_path && previous_path.exists() {
+            if let Some(parent) = entry_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Err(e) = fs::rename(&previous_path, &entry_path) {
+                warn!("Error while moving codex entry: {e}");
+            }
+        }
+    }
+
+    if let Err(e) = fs::create_dir_all(&entry_path) {
+        warn!("Error while creating codex entry: {e}");
+        return e.to_string();
+    }
+
+    let notes = entry.notes.clone();
+    let stored = CodexEntry {
+        notes: String::new(),
+        ..entry
+    };
+    let string = serde_json::to_string_pretty(&stored).unwrap();
+
+    if let Err(e) = fs::write(entry_path.join("Entry.json"), string) {
+        warn!("Error while writing codex entry: {e}");
+        return e.to_string();
+    }
+    if let Err(e) = write_text(&entry_path.join("Notes.md"), &notes) {
+        warn!("Error while writing codex notes: {e}");
+        return e.to_string();
+    }
+    info!("Wrote codex entry: {entry_path:?}");
+    String::new()
+}
+
+/// Lets the user pick an image and copies it into the entry folder at `path`.
+/// Returns the file name of the copy.
+#[tauri::command]
+pub fn add_codex_image(path: String) -> Option<String> {
+    let image = FileDialog::new()
+        .set_title("Choose image")
+        .add_filter("Images", &["png", "jpg", "jpeg", "gif", "webp", "svg"])
+        .pick_file()?;
+    let file_name = image.file_name()?.to_string_lossy().to_string();
+
+    let entry_path = PathBuf::from(path);
+    let _ = fs::create_dir_all(&entry_path);
+    match fs::copy(&image, entry_path.join(&file_name)) {
+        Ok(_) => Some(file_name),
+        Err(e) => {
+            warn!("Error while copying codex image: {e}");
+            None
+        }
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn find_codex_mentions(project: Project, entry: CodexEntry) -> Vec<CodexMention> {
+    let entries = [entry];
+
+    project
+        .chapters
+        .iter()
+        .filter_map(|chapter| {
+            let mut content_path = project.path.clone();
+            content_path.push("Chapters");
+            content_path.push(chapter);
+            content_path.push("Content.md");
+
+            let content = read_text(&content_path).ok()?;
+            let count = find_mentions(&content, &entries).len();
+            (count > 0).then(|| CodexMention {
+                chapter: chapter.clone(),
+                count,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+use std::fs;
+use std::path::Path;
+
+use log::{info, warn};
+use shared::{diagnose, repair, ChapterFolder, DoctorReport, Project, ProjectProblem};
+
+use crate::loader::write_project_config;
+
+fn chapter_folders(project_path: &Path) -> Vec<ChapterFolder> {
+    let Ok(dir) = project_path.join("Chapters").read_dir() else {
+        return Vec::new();
+    };
+    let mut folders: Vec<ChapterFolder> = dir
+        .filter_map(Result::ok)
+        .filter(|x| x.file_type().is_ok_and(|x| x.is_dir()))
+        .map(|x| {
+            let path = x.path();
+            ChapterFolder {
+                name: x.file_name().to_string_lossy().into_owned(),
+                content: path.join("Content.md").is_file(),
+                note: path.join("Note.md").is_file(),
+            }
+        })
+        .collect();
+    folders.sort_by(|a, b| a.name.cmp(&b.name));
+    folders
+}
+
+/// Lists where the project file and the project folder don't match
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn check_project(project: Project) -> Vec<ProjectProblem> {
+    diagnose(&project, &chapter_folders(&project.path))
+}
+
+/// Repairs every problem `check_project` finds and reports what changed
+#[tauri::command]
+pub fn repair_project(mut project: Project) -> DoctorReport {
+    let problems = diagnose(&project, &chapter_folders(&project.path));
+    let mut changes = Vec::new();
+
+    for problem in &problems {
+        let file = match problem {
+            ProjectProblem::MissingContent(chapter) => Some((chapter, "Content.md")),
+            ProjectProblem::MissingNote(chapter) => Some((chapter, "Note.md")),
+            _ => None,
+        };
+        let result = file.map_or(Ok(()), |(chapter, file)| {
+            fs::write(project.path.join("Chapters").join(chapter).join(file), "")
+        });
+        match result {
+            Ok(()) => changes.push(problem.repaired()),
+            Err(e) => {
+                warn!("Error while repairing project: {problem}: {e}");
+                changes.push(format!("Could not repair: {problem} ({e})"));
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        repair(&mut project, &problems);
+        write_project_config(project.clone());
+        info!("Repaired {} problems of {:?}", problems.len(), project.path);
+    }
+
+    DoctorReport { project, changes }
+}
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use shared::{is_encrypted_file, ProjectLock};
+
+use crate::watcher::note_own_write;
+
+/// Kept in the project folder of encrypted projects, it holds what is needed
+/// to check a passphrase but never the key itself
+const KEY_FILE: &str = ".papersmith-key.json";
+/// Encrypted files start with this, files without it are read as they are
+const MAGIC: &[u8] = b"PSENC1\n";
+/// Encrypted into the key file, a passphrase is right if it decrypts again
+const VERIFIER: &[u8] = b"PaperSmith";
+const KEY_VERSION: u32 = 1;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    /// Salt the key is derived from the passphrase with
+    salt: Vec<u8>,
+    /// `VERIFIER` encrypted with the key
+    verifier: Vec<u8>,
+}
+
+/// Keys of the unlocked projects, by project folder. They are only kept in memory.
+static KEYS: Mutex<BTreeMap<PathBuf, [u8; 32]>> = Mutex::new(BTreeMap::new());
+
+fn locked_error() -> io::Error {
+    io::Error::new(ErrorKind::PermissionDenied, "the project is locked")
+}
+
+/// The project folder `path` is in, if that project is encrypted
+fn encrypted_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|x| x.join(KEY_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+fn key_of(root: &Path) -> Option<[u8; 32]> {
+    KEYS.lock().ok()?.get(root).copied()
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+fn seal(key: &[u8; 32], plain: &[u8]) -> Vec<u8> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let encrypted = cipher
+        .encrypt(&nonce, plain)
+        .expect("encrypting in memory doesn't fail");
+    [MAGIC, nonce.as_slice(), &encrypted].concat()
+}
+
+/// Decrypts `bytes`, `None` if they were changed or encrypted with another key
+fn open(key: &[u8; 32], bytes: &[u8]) -> Option<Vec<u8>> {
+    let bytes = bytes.strip_prefix(MAGIC)?;
+    if bytes.len() < NONCE_LENGTH {
+        return None;
+    }
+    let (nonce, encrypted) = bytes.split_at(NONCE_LENGTH);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), encrypted)
+        .ok()
+}
+
+fn read_key_file(root: &Path) -> Option<KeyFile> {
+    let content = fs::read_to_string(root.join(KEY_FILE)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(key_file) => Some(key_file),
+        Err(e) => {
+            warn!("Key file of {root:?} is not readable: {e}");
+            None
+        }
+    }
+}
+
+/// Encrypts `content` with the key of the project at `root` if that project is
+/// encrypted, for copies of its chapters kept outside of it. Fails while it is locked.
+pub fn seal_for(root: &Path, content: &str) -> io::Result<Vec<u8>> {
+    if !is_encrypted(root) {
+        return Ok(content.as_bytes().to_vec());
+    }
+    Ok(seal(
+        &key_of(root).ok_or_else(locked_error)?,
+        content.as_bytes(),
+    ))
+}
+
+/// Reads `bytes` of the project at `root`, decrypting them if they are encrypted
+pub fn open_for(root: &Path, bytes: Vec<u8>) -> io::Result<String> {
+    if !bytes.starts_with(MAGIC) {
+        return String::from_utf8(bytes).map_err(|e| io::Error::new(ErrorKind::InvalidData, e));
+    }
+    let key = key_of(root).ok_or_else(locked_error)?;
+    let plain = open(&key, &bytes).ok_or_else(|| {
+        io::Error::new(ErrorKind::InvalidData, "the file is damaged or was changed")
+    })?;
+    String::from_utf8(plain).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Reads the file at `path`, decrypting it if it is encrypted.
+/// Fails for encrypted files while their project is locked.
+pub fn read_text(path: &Path) -> io::Result<String> {
+    let bytes = fs::read(path)?;
+    // Encrypted files outside of an encrypted project have no key and can't be read
+    let root = encrypted_root(path).unwrap_or_default();
+    open_for(&root, bytes)
+}
+
+/// Writes `content` to the file at `path`, encrypted if it is a chapter or note
+/// of an encrypted project. Nothing is written while that project is locked.
+pub fn write_text(path: &Path, content: &str) -> io::Result<()> {
+    let encrypted = path
+        .file_name()
+        .is_some_and(|name| is_encrypted_file(&name.to_string_lossy()));
+    let bytes = match encrypted_root(path) {
+        Some(root) if encrypted => {
+            seal(&key_of(&root).ok_or_else(locked_error)?, content.as_bytes())
+        }
+        _ => content.as_bytes().to_vec(),
+    };
+    note_own_write(path, &bytes);
+    fs::write(path, bytes)
+}
+
+/// Every file below `dir` that is encrypted in an encrypted project, also those in the trash
+fn encrypted_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = dir.read_dir() else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            encrypted_files(&path, files);
+        } else if is_encrypted_file(&entry.file_name().to_string_lossy()) {
+            files.push(path);
+        }
+    }
+}
+
+pub fn is_encrypted(root: &Path) -> bool {
+    root.join(KEY_FILE).is_file()
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn encryption_status(path: String) -> ProjectLock {
+    let root = PathBuf::from(path);
+    if !is_encrypted(&root) {
+        ProjectLock::Unencrypted
+    } else if key_of(&root).is_some() {
+        ProjectLock::Unlocked
+    } else {
+        ProjectLock::Locked
+    }
+}
+
+/// Encrypts the chapters and notes of the project at `path` with a key derived
+/// from `passphrase`. The project stays unlocked. Returns an error message, empty if it worked.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn encrypt_project(path: String, passphrase: String) -> String {
+    let root = PathBuf::from(path);
+    if is_encrypted(&root) {
+        return "The project is already encrypted.".to_string();
+    }
+    if passphrase.is_empty() {
+        return "Please enter a passphrase.".to_string();
+    }
+
+    let mut salt = vec![0; SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let key = match derive_key(&passphrase, &salt) {
+        Ok(key) => key,
+        Err(e) => return e,
+    };
+    let key_file = KeyFile {
+        version: KEY_VERSION,
+        salt,
+        verifier: seal(&key, VERIFIER),
+    };
+    let string = serde_json::to_string_pretty(&key_file).unwrap();
+    if let Err(e) = fs::write(root.join(KEY_FILE), string) {
+        warn!("Error while writing key file: {e}");
+        return e.to_string();
+    }
+    if let Ok(mut keys) = KEYS.lock() {
+        keys.insert(root.clone(), key);
+    }
+
+    let mut files = Vec::new();
+    encrypted_files(&root, &mut files);
+    let mut errors = Vec::new();
+    for file in files {
+        if let Err(e) = read_text(&file).and_then(|content| write_text(&file, &content)) {
+            warn!("Error while encrypting {file:?}: {e}");
+            errors.push(format!("{}: {e}", file.display()));
+        }
+    }
+    info!("Encrypted project: {root:?}");
+    errors.join("\n")
+}
+
+/// Decrypts every file of the unlocked project at `path` and removes the key file.
+/// Returns an error message, empty if it worked.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn decrypt_project(path: String) -> String {
+    let root = PathBuf::from(path);
+    if key_of(&root).is_none() {
+        return "The project is locked or not encrypted.".to_string();
+    }
+
+    let mut files = Vec::new();
+    encrypted_files(&root, &mut files);
+    let mut errors = Vec::new();
+    for file in files {
+        let result = read_text(&file).and_then(|content| {
+            note_own_write(&file, content.as_bytes());
+            fs::write(&file, content)
+        });
+        if let Err(e) = result {
+            warn!("Error while decrypting {file:?}: {e}");
+            errors.push(format!("{}: {e}", file.display()));
+        }
+    }
+    // With files left encrypted the key is still needed for them
+    if !errors.is_empty() {
+        return errors.join("\n");
+    }
+    if let Err(e) = fs::remove_file(root.join(KEY_FILE)) {
+        warn!("Error while removing key file: {e}");
+        return e.to_string();
+    }
+    if let Ok(mut keys) = KEYS.lock() {
+        keys.remove(&root);
+    }
+    info!("Decrypted project: {root:?}");
+    String::new()
+}
+
+/// Unlocks the project at `path`, false if the passphrase is wrong
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn unlock_project(path: String, passphrase: String) -> bool {
+    let root = PathBuf::from(path);
+    let Some(key_file) = read_key_file(&root) else {
+        return false;
+    };
+    if key_file.version > KEY_VERSION {
+        warn!("Key file of {root:?} was made by a newer version of PaperSmith");
+        return false;
+    }
+    let Ok(key) = derive_key(&passphrase, &key_file.salt) else {
+        return false;
+    };
+    if open(&key, &key_file.verifier).as_deref() != Some(VERIFIER) {
+        info!("Wrong passphrase for {root:?}");
+        return false;
+    }
+    if let Ok(mut keys) = KEYS.lock() {
+        keys.insert(root.clone(), key);
+    }
+    info!("Unlocked project: {root:?}");
+    true
+}
+
+/// Forgets the key of the project at `path`, its files can't be read until it is unlocked again
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn lock_project(path: String) {
+    let root = PathBuf::from(path);
+    if let Ok(mut keys) = KEYS.lock() {
+        if keys.remove(&root).is_some() {
+            info!("Locked project: {root:?}");
+        }
+    }
+}
@@ -0,0 +1,327 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::Utc;
+use shared::{code_span_end, RevisionSpan};
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/><Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/></Types>"#;
+
+const PACKAGE_RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+const DOCUMENT_RELATIONSHIPS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#;
+
+const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:pPr><w:spacing w:after="120"/></w:pPr></w:style><w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="240"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:sz w:val="32"/></w:rPr></w:style><w:style w:type="paragraph" w:styleId="Heading2"><w:name w:val="heading 2"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="1"/></w:pPr><w:rPr><w:b/><w:sz w:val="28"/></w:rPr></w:style></w:styles>"#;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// How a byte of a paragraph shows up in the document
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+struct Format {
+    /// Markdown markup, it is left out
+    hidden: bool,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+    strike: bool,
+    highlight: bool,
+}
+
+impl Format {
+    /// The format switched on and off by the emphasis `marker`
+    fn emphasis(&mut self, marker: &str) -> &mut bool {
+        match marker {
+            "**" => &mut self.bold,
+            "__" => &mut self.underline,
+            "~~" => &mut self.strike,
+            "::" => &mut self.highlight,
+            _ => &mut self.italic,
+        }
+    }
+
+    fn properties(self) -> String {
+        let properties: String = [
+            (self.bold, "<w:b/>"),
+            (self.italic, "<w:i/>"),
+            (self.strike, "<w:strike/>"),
+            (self.highlight, r#"<w:highlight w:val="yellow"/>"#),
+            (self.underline, r#"<w:u w:val="single"/>"#),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|(_, property)| *property)
+        .collect();
+
+        if properties.is_empty() {
+            properties
+        } else {
+            format!("<w:rPr>{properties}</w:rPr>")
+        }
+    }
+}
+
+/// Emphasis markers, the longer ones first
+const EMPHASIS: [&str; 6] = ["**", "__", "~~", "::", "*", "_"];
+
+/// The format of every byte of the markdown paragraph `line`, markup is hidden.
+/// Also returns whether the paragraph is a heading.
+fn format_markdown(line: &str) -> (bool, Vec<Format>) {
+    let mut formats = vec![Format::default(); line.len()];
+    let hide = |formats: &mut [Format], range: std::ops::Range<usize>| {
+        for format in &mut formats[range] {
+            format.hidden = true;
+        }
+    };
+
+    let hashes = line.bytes().take_while(|x| *x == b'#').count();
+    let heading = (1..=6).contains(&hashes) && line[hashes..].starts_with(' ');
+    let mut position = if heading {
+        hashes + 1
+    } else if let Some(quote) = line.strip_prefix('>') {
+        line.len() - quote.trim_start_matches(' ').len()
+    } else {
+        0
+    };
+    hide(&mut formats, 0..position);
+
+    let mut current = Format::default();
+    while position < line.len() {
+        let rest = &line[position..];
+        let previous = line[..position].chars().next_back();
+
+        if rest.starts_with('\\') && rest[1..].starts_with(|c: char| c.is_ascii_punctuation()) {
+            hide(&mut formats, position..position + 1);
+            formats[position + 1] = current;
+            position += 2;
+            continue;
+        }
+        if rest.starts_with('`') {
+            if let Some(end) = code_span_end(line, position) {
+                let ticks = rest.bytes().take_while(|x| *x == b'`').count();
+                formats[position..end].fill(current);
+                hide(&mut formats, position..position + ticks);
+                hide(&mut formats, end - ticks..end);
+                position = end;
+                continue;
+            }
+        }
+        // Links and images keep their text, the target is left out
+        let link_start = usize::from(rest.starts_with("!["));
+        if rest[link_start..].starts_with('[') && !rest[link_start..].starts_with("[^") {
+            let label_end = rest.find("](");
+            let target_end = label_end.and_then(|end| rest[end..].find(')').map(|x| end + x));
+            if let (Some(label_end), Some(target_end)) = (label_end, target_end) {
+                hide(&mut formats, position..position + link_start + 1);
+                formats[position + link_start + 1..position + label_end].fill(current);
+                hide(
+                    &mut formats,
+                    position + label_end..position + target_end + 1,
+                );
+                position += target_end + 1;
+                continue;
+            }
+        }
+
+        let marker = EMPHASIS.iter().find(|marker| rest.starts_with(**marker));
+        if let Some(marker) = marker {
+            let after = &rest[marker.len()..];
+            let is_marker = if *current.emphasis(marker) {
+                previous.is_some_and(|c| !c.is_whitespace())
+            } else {
+                // Underscores inside of words are part of them
+                after.starts_with(|c: char| !c.is_whitespace())
+                    && after.contains(marker)
+                    && !(marker.starts_with('_') && previous.is_some_and(char::is_alphanumeric))
+            };
+            if is_marker {
+                let on = current.emphasis(marker);
+                *on = !*on;
+                hide(&mut formats, position..position + marker.len());
+                position += marker.len();
+                continue;
+            }
+        }
+
+        let length = rest.chars().next().map_or(1, char::len_utf8);
+        formats[position..position + length].fill(current);
+        position += length;
+    }
+
+    (heading, formats)
+}
+
+/// A DOCX document of plain paragraphs. Insertions and deletions of a
+/// revision become tracked changes, with one author per revision round.
+pub struct Document {
+    body: String,
+    paragraph: String,
+    next_id: usize,
+    date: String,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self {
+            body: String::new(),
+            paragraph: String::new(),
+            next_id: 0,
+            date: Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        }
+    }
+
+    /// Opening tag of a tracked change, `kind` is `ins` or `del`
+    fn change(&mut self, kind: &str, round: u32, empty: bool) -> String {
+        self.next_id += 1;
+        format!(
+            r#"<w:{kind} w:id="{}" w:author="Revision {round}" w:date="{}"{}>"#,
+            self.next_id,
+            self.date,
+            if empty { "/" } else { "" }
+        )
+    }
+
+    pub fn heading(&mut self, text: &str, page_break: bool) {
+        if page_break {
+            self.body
+                .push_str(r#"<w:p><w:r><w:br w:type="page"/></w:r></w:p>"#);
+        }
+        self.body.push_str(&format!(
+            r#"<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t xml:space="preserve">{}</w:t></w:r></w:p>"#,
+            escape(text)
+        ));
+    }
+
+    fn run(&mut self, text: &str, span: &RevisionSpan, format: Format) {
+        if text.is_empty() {
+            return;
+        }
+        let text = escape(text);
+        let properties = format.properties();
+
+        let mut run = if span.deleted.is_some() {
+            format!(r#"<w:r>{properties}<w:delText xml:space="preserve">{text}</w:delText></w:r>"#)
+        } else {
+            format!(r#"<w:r>{properties}<w:t xml:space="preserve">{text}</w:t></w:r>"#)
+        };
+        if let Some(round) = span.deleted {
+            run = format!("{}{run}</w:del>", self.change("del", round, false));
+        }
+        if let Some(round) = span.inserted {
+            run = format!("{}{run}</w:ins>", self.change("ins", round, false));
+        }
+        self.paragraph.push_str(&run);
+    }
+
+    /// Ends the paragraph at a line break of `span`
+    fn end_paragraph(&mut self, span: &RevisionSpan, heading: bool) {
+        let mut mark = String::new();
+        if let Some(round) = span.inserted {
+            mark.push_str(&self.change("ins", round, true));
+        }
+        if let Some(round) = span.deleted {
+            mark.push_str(&self.change("del", round, true));
+        }
+
+        let mut properties = String::new();
+        if heading {
+            properties.push_str(r#"<w:pStyle w:val="Heading2"/>"#);
+        }
+        if !mark.is_empty() {
+            properties.push_str(&format!("<w:rPr>{mark}</w:rPr>"));
+        }
+        if !properties.is_empty() {
+            properties = format!("<w:pPr>{properties}</w:pPr>");
+        }
+        let runs = std::mem::take(&mut self.paragraph);
+        self.body
+            .push_str(&format!("<w:p>{properties}{runs}</w:p>"));
+    }
+
+    /// Adds a paragraph of markdown made of `pieces` of spans, the markup
+    /// becomes formatting. `end` is the span of the line break after it.
+    fn paragraph(&mut self, pieces: &[(&str, &RevisionSpan)], end: &RevisionSpan) {
+        let line: String = pieces.iter().map(|(text, _)| *text).collect();
+        let (heading, formats) = format_markdown(&line);
+
+        let mut offset = 0;
+        for (text, span) in pieces {
+            let formats = &formats[offset..offset + text.len()];
+            let mut start = 0;
+            while start < text.len() {
+                let format = formats[start];
+                let length = formats[start..]
+                    .iter()
+                    .take_while(|x| **x == format)
+                    .count();
+                if !format.hidden {
+                    self.run(&text[start..start + length], span, format);
+                }
+                start += length;
+            }
+            offset += text.len();
+        }
+        self.end_paragraph(end, heading);
+    }
+
+    /// Adds markdown text made of spans, every line becomes a paragraph
+    pub fn text(&mut self, spans: &[RevisionSpan]) {
+        let mut pieces = Vec::new();
+        for span in spans {
+            let mut lines = span.text.split('\n').peekable();
+            while let Some(line) = lines.next() {
+                pieces.push((line, span));
+                if lines.peek().is_some() {
+                    self.paragraph(&pieces, span);
+                    pieces.clear();
+                }
+            }
+        }
+        self.paragraph(
+            &pieces,
+            &RevisionSpan {
+                text: String::new(),
+                inserted: None,
+                deleted: None,
+            },
+        );
+    }
+
+    pub fn write(self, path: &Path) -> ZipResult<()> {
+        let document = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}<w:sectPr/></w:body></w:document>"#,
+            self.body
+        );
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default();
+        for (name, content) in [
+            ("[Content_Types].xml", CONTENT_TYPES),
+            ("_rels/.rels", PACKAGE_RELATIONSHIPS),
+            ("word/_rels/document.xml.rels", DOCUMENT_RELATIONSHIPS),
+            ("word/styles.xml", STYLES),
+            ("word/document.xml", document.as_str()),
+        ] {
+            zip.start_file(name, options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+
+        Ok(())
+    }
+}
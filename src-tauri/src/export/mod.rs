@@ -0,0 +1,118 @@
+use log::{info, warn};
+use shared::{find_footnotes, replace_comments, strip_comments, Project, RevisionSpan};
+
+use crate::encryption::read_text;
+use crate::revision::read_revision;
+
+mod docx;
+use docx::Document;
+
+/// Gives the footnotes of a chapter labels that can't clash with those of other chapters
+fn prefix_footnotes(content: &str, prefix: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut last_end = 0;
+
+    for footnote in find_footnotes(content) {
+        output.push_str(&content[last_end..footnote.label.start]);
+        output.push_str(prefix);
+        last_end = footnote.label.start;
+    }
+    output.push_str(&content[last_end..]);
+
+    output
+}
+
+/// Joins all chapters of the project into one markdown document, each under
+/// a heading with its name. Comments are left out unless `comments` is set.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn compile_manuscript(project: Project, comments: bool) -> String {
+    let mut manuscript = String::new();
+
+    for (index, chapter) in project.chapters.iter().enumerate() {
+        let mut content_path = project.path.clone();
+        content_path.push("Chapters");
+        content_path.push(chapter);
+        content_path.push("Content.md");
+
+        let content = match read_text(&content_path) {
+            Ok(content) => content.replace("\r\n", "\n"),
+            Err(e) => {
+                warn!("Error reading chapter {content_path:?} for export: {e}");
+                continue;
+            }
+        };
+        let content = if comments {
+            content
+        } else {
+            strip_comments(&content)
+        };
+
+        manuscript.push_str(&format!("# {}\n\n", project.title(chapter)));
+        manuscript.push_str(prefix_footnotes(&content, &format!("{}-", index + 1)).trim_end());
+        manuscript.push_str("\n\n");
+    }
+
+    manuscript
+}
+
+/// Writes all chapters into `Exports/<project>.docx`. Chapters in revision mode
+/// keep their changes as tracked changes. Returns the path of the document.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn export_docx(project: Project, comments: bool) -> Option<String> {
+    let mut document = Document::new();
+
+    for (index, chapter) in project.chapters.iter().enumerate() {
+        let chapter_path = project.path.join("Chapters").join(chapter);
+        let content = match read_text(&chapter_path.join("Content.md")) {
+            Ok(content) => content.replace("\r\n", "\n"),
+            Err(e) => {
+                warn!("Error reading chapter {chapter_path:?} for export: {e}");
+                continue;
+            }
+        };
+
+        let mut spans = if let Some(mut revision) = read_revision(&chapter_path) {
+            revision.update(&content);
+            revision.spans
+        } else {
+            vec![RevisionSpan {
+                text: content,
+                inserted: None,
+                deleted: None,
+            }]
+        };
+        // Comments stay as text in brackets after what they are about
+        for span in &mut spans {
+            span.text = if comments {
+                replace_comments(&span.text, |_, comment| {
+                    let annotated = comment
+                        .annotated
+                        .clone()
+                        .map(|range| span.text[range].to_string())
+                        .unwrap_or_default();
+                    format!("{annotated} [{}]", comment.text)
+                })
+            } else {
+                strip_comments(&span.text)
+            };
+        }
+
+        document.heading(&project.title(chapter), index > 0);
+        document.text(&spans);
+    }
+
+    let name = project.path.file_name()?.to_string_lossy().to_string();
+    let path = project.path.join("Exports").join(format!("{name}.docx"));
+    match document.write(&path) {
+        Ok(()) => {
+            info!("Exported project to {path:?}");
+            Some(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            warn!("Error while exporting to DOCX: {e}");
+            None
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use log::{info, warn};
+use shared::EditHistory;
+
+use crate::encryption::{read_text, write_text};
+
+/// Hidden file inside a chapter folder the undo history is kept in
+const HISTORY_FILE: &str = ".history.json";
+
+/// Reads the undo history saved in the chapter folder at `path`
+#[tauri::command]
+pub fn get_history(path: String) -> Option<EditHistory> {
+    let history_path = PathBuf::from(path).join(HISTORY_FILE);
+    let content = read_text(&history_path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(history) => Some(history),
+        Err(e) => {
+            warn!("History {history_path:?} is not readable: {e}");
+            None
+        }
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn write_history(path: String, history: EditHistory) {
+    let history_path = PathBuf::from(path).join(HISTORY_FILE);
+    let string = serde_json::to_string(&history).unwrap();
+
+    match write_text(&history_path, &string) {
+        Ok(()) => info!("Wrote history: {history_path:?}"),
+        Err(e) => warn!("Error while writing history: {e}"),
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::PathBuf;
@@ -6,6 +7,8 @@ use log::{info, warn};
 use serde_json::Error;
 use shared::Project;
 
+use crate::encryption::read_text;
+
 pub fn parse_project(path: PathBuf) -> Option<Project> {
     let mut file_path = path.clone();
     file_path.push(".papersmith.json");
@@ -22,8 +25,9 @@ pub fn parse_project(path: PathBuf) -> Option<Project> {
 
     let settings: Result<Project, Error> = serde_json::from_str(&settings_string);
     match settings {
+        // The project may have been moved or unpacked from an archive since
         Ok(x) => {
-            return Some(x);
+            return Some(Project { path, ..x });
         }
         Err(x) => {
             warn!("Config file didnt load, recalculating: {x}");
@@ -57,6 +61,7 @@ pub fn parse_project(path: PathBuf) -> Option<Project> {
         path,
         chapters,
         active_chapter: None,
+        metadata: BTreeMap::new(),
     })
 }
 
@@ -65,6 +70,7 @@ pub fn write_project_config(project: Project) {
     let string = serde_json::to_string_pretty(&project).unwrap();
     let mut config_path = project.path;
     config_path.push(".papersmith.json");
+    crate::watcher::note_own_write(&config_path, string.as_bytes());
     match File::create(&config_path) {
         Ok(mut file) => match file.write_all(string.as_bytes()) {
             Ok(()) => info!("Wrote config: {config_path:?}"),
@@ -73,3 +79,51 @@ pub fn write_project_config(project: Project) {
         Err(x) => warn!("Error while creating/opening config file: {x}"),
     };
 }
+
+/// Reads the project at `path` again after it changed outside of PaperSmith.
+/// Chapters whose folders are gone are dropped and new folders are added at the end.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn reload_project(path: String) -> Option<Project> {
+    let mut project = parse_project(PathBuf::from(&path))?;
+    let Ok(dir) = project.path.join("Chapters").read_dir() else {
+        return Some(project);
+    };
+    let mut folders: Vec<String> = dir
+        .filter_map(Result::ok)
+        .filter(|x| x.file_type().is_ok_and(|x| x.is_dir()))
+        .map(|x| x.file_name().to_string_lossy().into_owned())
+        .collect();
+    folders.sort();
+
+    let active = project
+        .active_chapter
+        .and_then(|index| project.chapters.get(index).cloned());
+    project.chapters.retain(|chapter| folders.contains(chapter));
+    for folder in folders {
+        if !project.chapters.contains(&folder) {
+            project.chapters.push(folder);
+        }
+    }
+    project.active_chapter = active
+        .and_then(|active| project.chapters.iter().position(|x| *x == active))
+        .or_else(|| (!project.chapters.is_empty()).then_some(0));
+    Some(project)
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_word_counts(project: Project) -> Vec<usize> {
+    project
+        .chapters
+        .iter()
+        .map(|chapter| {
+            let mut content_path = project.path.clone();
+            content_path.push("Chapters");
+            content_path.push(chapter);
+            content_path.push("Content.md");
+
+            read_text(&content_path).map_or(0, |content| content.split_whitespace().count())
+        })
+        .collect()
+}
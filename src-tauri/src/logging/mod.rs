@@ -0,0 +1,222 @@
+use std::cell::Cell;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+use serde_json::Value;
+use shared::{parse_log, LogEntry, LogLevel, BACKEND, FRONTEND};
+
+const LOG_FILE: &str = "papersmith.log";
+/// The log file is moved to `papersmith.1.log` once it is this big
+const MAX_SIZE: u64 = 1024 * 1024;
+/// Number of moved log files that are kept, older ones are deleted
+const KEEP: usize = 3;
+/// Entries the log viewer shows at most
+const VIEWER_ENTRIES: usize = 2000;
+
+/// Writes the messages of PaperSmith and of the frontend into the log file in the data folder
+struct FileLogger {
+    file: Mutex<Option<File>>,
+}
+
+static LOGGER: FileLogger = FileLogger {
+    file: Mutex::new(None),
+};
+
+thread_local! {
+    /// Call id the frontend sent with the command that is running
+    static CALL: Cell<Option<u32>> = Cell::default();
+}
+
+fn log_folder() -> PathBuf {
+    dirs_next::data_dir().unwrap_or_default().join("PaperSmith")
+}
+
+/// The log file, `papersmith.<index>.log` for the moved ones
+fn log_path(index: usize) -> PathBuf {
+    if index == 0 {
+        log_folder().join(LOG_FILE)
+    } else {
+        log_folder().join(format!("papersmith.{index}.log"))
+    }
+}
+
+fn open_log() -> Option<File> {
+    let _ = fs::create_dir_all(log_folder());
+    match OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(log_path(0))
+    {
+        Ok(file) => Some(file),
+        Err(e) => {
+            eprintln!("Failed to open the log file: {e:?}");
+            None
+        }
+    }
+}
+
+/// Moves every log file one index up, the oldest one is deleted
+fn rotate() {
+    let _ = fs::remove_file(log_path(KEEP));
+    for index in (0..KEEP).rev() {
+        let _ = fs::rename(log_path(index), log_path(index + 1));
+    }
+}
+
+const fn level_of(level: log::Level) -> LogLevel {
+    match level {
+        log::Level::Error => LogLevel::Error,
+        log::Level::Warn => LogLevel::Warn,
+        log::Level::Info => LogLevel::Info,
+        log::Level::Debug => LogLevel::Debug,
+        log::Level::Trace => LogLevel::Trace,
+    }
+}
+
+const fn filter_of(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Error => LevelFilter::Error,
+        LogLevel::Warn => LevelFilter::Warn,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Trace => LevelFilter::Trace,
+    }
+}
+
+impl FileLogger {
+    fn write(&self, entry: &LogEntry) {
+        let line = entry.to_line();
+        if cfg!(debug_assertions) {
+            eprint!("{line}");
+        }
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+        if file
+            .as_ref()
+            .and_then(|x| x.metadata().ok())
+            .is_some_and(|x| x.len() >= MAX_SIZE)
+        {
+            *file = None;
+            rotate();
+        }
+        if file.is_none() {
+            *file = open_log();
+        }
+        if let Some(file) = file.as_mut() {
+            if let Err(e) = file.write_all(line.as_bytes()) {
+                eprintln!("Failed to write to the log file: {e:?}");
+            }
+        }
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // Libraries only get into the log with warnings and errors
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with("papersmith") || metadata.level() <= log::Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.write(&LogEntry {
+            time: now(),
+            level: level_of(record.level()),
+            source: BACKEND.to_string(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            call: CALL.with(Cell::get),
+        });
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+fn now() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S%.3f").to_string()
+}
+
+/// Sends the messages of `log::info!` and the like into the log file
+pub fn init() {
+    match log::set_logger(&LOGGER) {
+        Ok(()) => log::set_max_level(filter_of(LogLevel::default())),
+        Err(e) => eprintln!("Failed to install the logger: {e}"),
+    }
+    log::info!(
+        "PaperSmith {} started on {}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS
+    );
+}
+
+/// The call id the frontend sent along with a command
+pub fn call_of(payload: &Value) -> Option<u32> {
+    payload
+        .get("call")
+        .and_then(Value::as_u64)
+        .and_then(|call| u32::try_from(call).ok())
+}
+
+/// Runs a command, the messages it logs carry `call`
+pub fn in_call<T>(call: Option<u32>, command: impl FnOnce() -> T) -> T {
+    CALL.with(|x| x.set(call));
+    let result = command();
+    CALL.with(|x| x.set(None));
+    result
+}
+
+/// Only messages at least as important as `level` are written from now on
+pub fn set_level(level: LogLevel) {
+    log::set_max_level(filter_of(level));
+}
+
+/// Writes a message of the frontend into the log, next to those of the backend.
+/// `call` is the id of the command the frontend invoked last.
+#[tauri::command]
+pub fn log(level: Option<LogLevel>, target: Option<String>, msg: String, call: Option<u32>) {
+    let level = level.unwrap_or_default();
+    if filter_of(level) > log::max_level() {
+        return;
+    }
+    LOGGER.write(&LogEntry {
+        time: now(),
+        level,
+        source: FRONTEND.to_string(),
+        target: target.unwrap_or_else(|| FRONTEND.to_string()),
+        message: msg,
+        call,
+    });
+}
+
+/// The latest entries of the log, the oldest first
+#[tauri::command]
+pub fn read_log() -> Vec<LogEntry> {
+    LOGGER.flush();
+    let mut entries: Vec<LogEntry> = (0..=KEEP)
+        .rev()
+        .filter_map(|index| fs::read_to_string(log_path(index)).ok())
+        .flat_map(|content| parse_log(&content))
+        .collect();
+    let skip = entries.len().saturating_sub(VIEWER_ENTRIES);
+    entries.drain(..skip);
+    entries
+}
+
+/// The folder the log files are in, to attach them to bug reports
+#[tauri::command]
+pub fn get_log_folder() -> String {
+    log_folder().to_string_lossy().to_string()
+}
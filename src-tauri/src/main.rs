@@ -1,8 +1,9 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use dark_light::Mode;
 use glob::glob;
+use loader::get_word_counts;
+use loader::reload_project;
 use loader::write_project_config;
 use log::info;
 use log::warn;
@@ -11,21 +12,113 @@ use saving::create_directory;
 use saving::create_empty_file;
 use std::fs;
 use std::fs::File;
-use std::fs::OpenOptions;
-use std::io::Read;
 use std::io::Write;
 use std::path::Path;
 use std::process::Command;
-use std::time::SystemTime;
-use dark_light;
+use tauri::Manager;
 
 mod loader;
-use loader::parse_project;
 
 mod checking;
 use checking::can_create_path;
 use checking::choose_folder;
 
+mod closing;
+use closing::close_window;
+use closing::set_unsaved;
+
+mod codex;
+use codex::add_codex_image;
+use codex::find_codex_mentions;
+use codex::list_codex_entries;
+use codex::write_codex_entry;
+
+mod annotations;
+use annotations::list_comments;
+
+mod archive;
+use archive::backup_project;
+use archive::open_archive;
+use archive::pack_project;
+
+mod doctor;
+use doctor::check_project;
+use doctor::repair_project;
+
+mod logging;
+use logging::get_log_folder;
+use logging::log;
+use logging::read_log;
+
+mod encryption;
+use encryption::decrypt_project;
+use encryption::encrypt_project;
+use encryption::encryption_status;
+use encryption::lock_project;
+use encryption::read_text;
+use encryption::unlock_project;
+use encryption::write_text;
+
+mod export;
+use export::compile_manuscript;
+use export::export_docx;
+
+mod history;
+use history::get_history;
+use history::write_history;
+
+mod recovery;
+use recovery::clear_journal;
+use recovery::discard_journal;
+use recovery::discard_recovered;
+use recovery::end_session;
+use recovery::list_recovered;
+use recovery::read_recovered;
+use recovery::restore_recovered;
+use recovery::write_journal;
+
+mod revision;
+use revision::get_revision;
+use revision::write_revision;
+
+mod notes;
+use notes::search_notes;
+
+mod recent;
+use recent::get_last_project;
+use recent::list_recent_projects;
+use recent::open_project;
+use recent::open_project_at;
+use recent::pin_recent_project;
+use recent::remove_recent_project;
+
+mod settings;
+use settings::get_settings;
+use settings::write_settings;
+
+mod templates;
+use templates::delete_template;
+use templates::list_templates;
+use templates::save_project_as_template;
+
+mod themes;
+use themes::export_theme;
+use themes::get_dark_mode;
+use themes::import_theme;
+use themes::list_themes;
+use themes::watch_system_theme;
+use themes::watch_themes;
+
+mod watcher;
+use watcher::watch_project;
+use watcher::ProjectWatcher;
+
+mod trash;
+use trash::empty_trash;
+use trash::list_trash;
+use trash::restore_chapter;
+use trash::trash_chapter;
+
 mod saving;
 use saving::add_chapter;
 use saving::create_project;
@@ -33,103 +126,114 @@ use saving::delete_path;
 use saving::rename_path;
 
 use shared::Project;
-use shared::Settings;
 
 fn main() {
+    logging::init();
+
+    let commands = tauri::generate_handler![
+        get_project,
+        open_project,
+        get_last_project,
+        list_recent_projects,
+        pin_recent_project,
+        remove_recent_project,
+        write_to_file,
+        write_to_json,
+        choose_folder,
+        can_create_path,
+        create_project,
+        get_data_dir,
+        get_documents_folder,
+        rename_path,
+        add_chapter,
+        delete_path,
+        open_explorer,
+        create_empty_file,
+        get_file_content,
+        get_settings,
+        write_settings,
+        list_statistic_files,
+        unformat_file_name,
+        read_json_file,
+        write_project_config,
+        reload_project,
+        check_project,
+        pack_project,
+        backup_project,
+        open_archive,
+        repair_project,
+        encryption_status,
+        encrypt_project,
+        decrypt_project,
+        unlock_project,
+        lock_project,
+        watch_project,
+        get_word_counts,
+        list_codex_entries,
+        write_codex_entry,
+        add_codex_image,
+        find_codex_mentions,
+        trash_chapter,
+        list_trash,
+        restore_chapter,
+        empty_trash,
+        get_history,
+        write_history,
+        list_comments,
+        compile_manuscript,
+        export_docx,
+        get_revision,
+        write_revision,
+        list_themes,
+        import_theme,
+        export_theme,
+        get_dark_mode,
+        list_templates,
+        save_project_as_template,
+        delete_template,
+        create_directory,
+        log,
+        read_log,
+        get_log_folder,
+        write_journal,
+        list_recovered,
+        read_recovered,
+        restore_recovered,
+        discard_recovered,
+        discard_journal,
+        set_unsaved,
+        close_window,
+        search_notes,
+    ];
+
     // here `"quit".to_string()` defines the menu item id, and the second parameter is the menu item label.
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![
-            get_project,
-            write_to_file,
-            write_to_json,
-            choose_folder,
-            can_create_path,
-            create_project,
-            get_data_dir,
-            get_documents_folder,
-            rename_path,
-            add_chapter,
-            delete_path,
-            open_explorer,
-            create_empty_file,
-            get_file_content,
-            get_settings,
-            list_statistic_files,
-            unformat_file_name,
-            read_json_file,
-            write_project_config,
-            create_directory,
-            log,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
-
-fn get_systemtheme() -> String {
-    let system_theme = dark_light::detect();
-
-    match system_theme {
-        Ok(theme) => {
-            match theme {
-                Mode::Dark => String::from("Dark"),
-                Mode::Light => String::from("Light"),
-                Mode::Unspecified => String::new(),
-            }
-        },
-        Err(e) => {
-            log(format!("Error getting System Theme: {e:?}"));
-            String::new()
-        }
-    }
-}
-
-#[tauri::command]
-fn log(msg: String) {
-    let mut path = get_data_dir();
-    path.push_str("/PaperSmith/papersmith.log");
-
-    if !Path::new(path.as_str()).exists() {
-        match File::create(path) {
-            Ok(_) => return,
-            Err(e) => {
-                eprintln!("Error creating log file: {e:?}");
-                return;
+        .setup(|app| {
+            watch_themes(app);
+            watch_system_theme(app);
+            app.manage(ProjectWatcher::default());
+            Ok(())
+        })
+        .on_window_event(|event| closing::on_window_event(&event))
+        // The messages of a command carry the call id the frontend sent with it
+        .invoke_handler(move |invoke| {
+            let call = logging::call_of(invoke.message.payload());
+            logging::in_call(call, || commands(invoke));
+        })
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_, event| {
+            // The journal is only kept when PaperSmith doesn't close properly
+            if let tauri::RunEvent::Exit = event {
+                end_session();
             }
-        }
-    }
-
-    let mut file = match OpenOptions::new()
-        .append(true)
-        .write(true)
-        .create(true)
-        .open(path)
-    {
-        Ok(f) => {
-            println!("created log file");   
-            f
-        },
-        Err(e) => {
-            eprintln!("Failed to open or create the file: {e:?}");
-            return;
-        }
-    };
-
-    let now = SystemTime::now();
-    let log_msg = String::from(format!("[{now:?}]\t{msg:?}\n"));
-
-    match file.write(log_msg.as_bytes()) {
-        Ok(_) => return,
-        Err(e) => {
-            eprintln!("Failed to write to log file: {e:?}");
-            return;
-        }
-    }
+        });
 }
 
 #[tauri::command]
 fn get_file_content(path: String) -> String {
     info!("Reading file: {path}");
-    match fs::read_to_string(path) {
+    match read_text(Path::new(&path)) {
         Ok(string) => string,
         Err(e) => {
             warn!("Error reading file: {e}");
@@ -227,9 +331,13 @@ fn read_json_file(path: String) -> Option<String> {
     })
 }
 
+/// Opens the project folder the user picks, archives are opened with `open_archive`
 #[tauri::command]
 fn get_project() -> Option<Project> {
-    FileDialog::new().pick_folder().and_then(parse_project)
+    FileDialog::new()
+        .set_title("Open Project")
+        .pick_folder()
+        .and_then(open_project_at)
 }
 
 #[tauri::command]
@@ -263,45 +371,6 @@ fn write_to_json(path: String, name: String, content: String) {
     let _result = write!(file, "{content}");
 }
 
-#[tauri::command]
-#[allow(clippy::needless_pass_by_value)]
-fn get_settings(path: String) -> Settings {
-    let file_path = format!("{path}/settings.json");
-
-    let file = File::open(&file_path);
-
-    match file {
-        Ok(mut x) => {
-            let mut content = String::new();
-            let _ = x.read_to_string(&mut content);
-
-            let json_content: Result<Settings, serde_json::Error> = serde_json::from_str(&content);
-
-            match json_content {
-                Ok(settings) => {
-                    return settings;
-                }
-                Err(e) => {
-                    eprint!("Settings were not readable! Error: {e:?}");
-                }
-            }
-
-            Settings::default()
-        }
-        Err(e) => {
-            eprint!("{e}");
-            let mut settings = Settings::default();
-            let system_theme = get_systemtheme();
-
-            if system_theme != String::new() {
-                settings.theme = system_theme;
-            }
-
-            settings
-        }
-    }
-}
-
 #[tauri::command]
 fn get_data_dir() -> String {
     if let Some(config_dir) = dirs_next::data_dir() {
@@ -313,8 +382,7 @@ fn get_data_dir() -> String {
 #[tauri::command]
 #[allow(clippy::needless_pass_by_value)]
 fn write_to_file(path: String, content: String) {
-    use std::fs::{self, OpenOptions};
-    use std::io::Write;
+    use std::fs;
 
     // Ensure the directory exists
     let path = std::path::Path::new(&path);
@@ -327,24 +395,16 @@ fn write_to_file(path: String, content: String) {
         }
     }
 
-    let mut file = match OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .create(true)
-        .open(path)
-    {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("Failed to open or create the file: {e:?}");
-            return;
+    // Chapters and notes of encrypted projects are encrypted here
+    match write_text(path, &content) {
+        Ok(()) => {
+            println!("Content appended to file: {path:?}");
+            if path.file_name().is_some_and(|x| x == "Content.md") {
+                if let Some(chapter_path) = path.parent() {
+                    clear_journal(chapter_path);
+                }
+            }
         }
-    };
-
-    //println!("{:?}", content);
-
-    // Write the content to the file
-    match write!(file, "{content}") {
-        Ok(()) => println!("Content appended to file: {path:?}"),
         Err(e) => eprintln!("Failed to write to file: {e:?}"),
     }
 }
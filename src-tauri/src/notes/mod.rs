@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+use log::info;
+use shared::{search_lines, NoteMatch};
+
+use crate::encryption::read_text;
+use crate::loader::parse_project;
+
+/// The lines of the project note and the chapter notes containing `query`,
+/// in the order of the chapters. Notes of a locked project aren't searched.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn search_notes(path: String, query: String) -> Vec<NoteMatch> {
+    let project_path = PathBuf::from(path);
+    let Some(project) = parse_project(project_path.clone()) else {
+        return Vec::new();
+    };
+
+    let notes = std::iter::once((None, project_path.join("Note.md"))).chain(
+        project.chapters.iter().map(|chapter| {
+            (
+                Some(chapter.clone()),
+                project_path.join("Chapters").join(chapter).join("Note.md"),
+            )
+        }),
+    );
+    let matches: Vec<NoteMatch> = notes
+        .filter_map(|(chapter, note_path)| Some((chapter, read_text(&note_path).ok()?)))
+        .flat_map(|(chapter, content)| {
+            search_lines(&content, &query)
+                .into_iter()
+                .map(move |(line, text)| NoteMatch {
+                    chapter: chapter.clone(),
+                    line,
+                    text,
+                })
+        })
+        .collect();
+    info!(
+        "Found {} lines in the notes of {project_path:?}",
+        matches.len()
+    );
+    matches
+}
@@ -0,0 +1,181 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use shared::{Project, RecentProject};
+
+use crate::archive::{is_archive, unpack_archive};
+use crate::loader::{get_word_counts, parse_project};
+
+const RECENT_FILE: &str = "recent.json";
+/// Older projects are forgotten, pinned ones are always kept
+const MAX_RECENT: usize = 20;
+
+/// What `recent.json` keeps of a project, the rest is read from the project itself
+#[derive(Serialize, Deserialize)]
+struct RecentEntry {
+    path: PathBuf,
+    opened: i64,
+    #[serde(default)]
+    pinned: bool,
+}
+
+fn recent_path() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_default()
+        .join("PaperSmith")
+        .join(RECENT_FILE)
+}
+
+fn read_recent() -> Vec<RecentEntry> {
+    fs::read_to_string(recent_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Sorts the entries, pinned and recently opened ones first, and writes them
+fn write_recent(mut entries: Vec<RecentEntry>) {
+    entries.sort_by_key(|entry| (!entry.pinned, std::cmp::Reverse(entry.opened)));
+    let mut unpinned = 0;
+    entries.retain(|entry| {
+        unpinned += usize::from(!entry.pinned);
+        entry.pinned || unpinned <= MAX_RECENT
+    });
+
+    let path = recent_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let string = serde_json::to_string_pretty(&entries).unwrap();
+    if let Err(e) = fs::write(&path, string) {
+        warn!("Error while writing recent projects: {e}");
+    }
+}
+
+fn is_project(path: &Path) -> bool {
+    path.join(".papersmith.json").is_file()
+}
+
+fn unix_time(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let seconds = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    i64::try_from(seconds).ok()
+}
+
+/// The last change to the project file or to the content of a chapter
+fn last_edited(project: &Project) -> Option<i64> {
+    let chapters = project.chapters.iter().map(|chapter| {
+        project
+            .path
+            .join("Chapters")
+            .join(chapter)
+            .join("Content.md")
+    });
+    std::iter::once(project.path.join(".papersmith.json"))
+        .chain(chapters)
+        .filter_map(|path| unix_time(&path))
+        .max()
+}
+
+/// Puts the project at `path` on top of the recent projects
+pub fn remember_project(path: &Path) {
+    let mut entries = read_recent();
+    let pinned = entries
+        .iter()
+        .any(|entry| entry.path == path && entry.pinned);
+    entries.retain(|entry| entry.path != path);
+    entries.push(RecentEntry {
+        path: path.to_path_buf(),
+        opened: Utc::now().timestamp(),
+        pinned,
+    });
+    write_recent(entries);
+}
+
+/// Opens the project at `path` and remembers it. Archives are unpacked next
+/// to themselves first.
+pub fn open_project_at(path: PathBuf) -> Option<Project> {
+    let path = if is_archive(&path) {
+        match unpack_archive(&path) {
+            Ok(folder) => folder,
+            Err(e) => {
+                warn!("Error while unpacking {path:?}: {e}");
+                return None;
+            }
+        }
+    } else {
+        path
+    };
+    let project = parse_project(path)?;
+    remember_project(&project.path);
+    Some(project)
+}
+
+/// Lists the recent projects with their word count. Projects that were moved
+/// or deleted are removed from the list.
+#[tauri::command]
+pub fn list_recent_projects() -> Vec<RecentProject> {
+    let mut entries = read_recent();
+    let count = entries.len();
+    entries.retain(|entry| is_project(&entry.path));
+    if entries.len() != count {
+        info!("Removed {} missing recent projects", count - entries.len());
+        write_recent(entries);
+        entries = read_recent();
+    }
+
+    entries
+        .into_iter()
+        .filter_map(|entry| {
+            let project = parse_project(entry.path.clone())?;
+            Some(RecentProject {
+                name: entry.path.file_name()?.to_string_lossy().into_owned(),
+                pinned: entry.pinned,
+                opened: entry.opened,
+                edited: last_edited(&project),
+                words: get_word_counts(project).iter().sum(),
+                path: entry.path,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn open_project(path: String) -> Option<Project> {
+    open_project_at(PathBuf::from(path))
+}
+
+/// The project that was opened last, with the chapter it was on
+#[tauri::command]
+pub fn get_last_project() -> Option<Project> {
+    let last = read_recent()
+        .into_iter()
+        .filter(|entry| is_project(&entry.path))
+        .max_by_key(|entry| entry.opened)?;
+    open_project_at(last.path)
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn pin_recent_project(path: String, pinned: bool) {
+    let mut entries = read_recent();
+    for entry in &mut entries {
+        if entry.path == Path::new(&path) {
+            entry.pinned = pinned;
+        }
+    }
+    write_recent(entries);
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn remove_recent_project(path: String) {
+    let mut entries = read_recent();
+    entries.retain(|entry| entry.path != Path::new(&path));
+    write_recent(entries);
+}
@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use chrono::Utc;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use shared::{text_checksum, RecoveredChapter, RecoveredText};
+
+use crate::encryption::{open_for, read_text, seal_for, write_text};
+use crate::loader::parse_project;
+
+/// Folder in the data folder the journal is kept in
+const RECOVERY_FOLDER: &str = "Recovery";
+
+/// What the journal knows about the text it keeps for a chapter
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    /// Entries of other sessions are left over from one that didn't close properly
+    session: String,
+    project: PathBuf,
+    chapter: String,
+    saved: i64,
+}
+
+fn recovery_folder() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_default()
+        .join("PaperSmith")
+        .join(RECOVERY_FOLDER)
+}
+
+/// Tells the journals of this run apart from those of earlier ones
+fn session() -> &'static str {
+    static SESSION: OnceLock<String> = OnceLock::new();
+    SESSION.get_or_init(|| format!("{}-{}", Utc::now().timestamp_millis(), std::process::id()))
+}
+
+/// The journal of a chapter is named after the path of its folder
+fn id_of(chapter_path: &Path) -> String {
+    format!(
+        "{:016x}",
+        text_checksum([chapter_path.to_string_lossy().as_ref()])
+    )
+}
+
+fn entry_path(id: &str) -> PathBuf {
+    recovery_folder().join(format!("{id}.json"))
+}
+
+fn text_path(id: &str) -> PathBuf {
+    recovery_folder().join(format!("{id}.md"))
+}
+
+fn read_entry(id: &str) -> Option<JournalEntry> {
+    let content = fs::read_to_string(entry_path(id)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(entry) => Some(entry),
+        Err(e) => {
+            warn!("Journal {id} is not readable: {e}");
+            None
+        }
+    }
+}
+
+/// Writes to a file next to `path` first, a crash while writing never leaves half a file
+fn write_whole(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, bytes)?;
+    fs::rename(temporary, path)
+}
+
+fn remove(id: &str) {
+    let _ = fs::remove_file(text_path(id));
+    let _ = fs::remove_file(entry_path(id));
+}
+
+/// Keeps the unsaved text of the chapter folder at `path`, until the chapter is
+/// saved or PaperSmith closes. Encrypted chapters stay encrypted in the journal.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn write_journal(path: String, content: String) {
+    let chapter_path = PathBuf::from(path);
+    let (Some(chapter), Some(project)) = (
+        chapter_path.file_name(),
+        chapter_path.parent().and_then(Path::parent),
+    ) else {
+        return;
+    };
+    let id = id_of(&chapter_path);
+    let entry = JournalEntry {
+        session: session().to_string(),
+        project: project.to_path_buf(),
+        chapter: chapter.to_string_lossy().to_string(),
+        saved: Utc::now().timestamp(),
+    };
+
+    let result = fs::create_dir_all(recovery_folder())
+        .and_then(|()| seal_for(project, &content))
+        .and_then(|bytes| write_whole(&text_path(&id), &bytes))
+        .and_then(|()| {
+            let string = serde_json::to_string_pretty(&entry).unwrap();
+            write_whole(&entry_path(&id), string.as_bytes())
+        });
+    if let Err(e) = result {
+        warn!("Error while writing journal of {chapter_path:?}: {e}");
+    }
+}
+
+/// Forgets the journal of the chapter folder at `chapter_path`, its text was saved
+pub fn clear_journal(chapter_path: &Path) {
+    let id = id_of(chapter_path);
+    if entry_path(&id).exists() {
+        remove(&id);
+    }
+}
+
+/// Forgets the journal of the chapter folder at `path`, its changes were discarded
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn discard_journal(path: String) {
+    clear_journal(Path::new(&path));
+}
+
+/// Removes the journals of this run, called when PaperSmith closes properly
+pub fn end_session() {
+    let Ok(entries) = recovery_folder().read_dir() else {
+        return;
+    };
+    for file in entries.filter_map(Result::ok) {
+        let name = file.file_name().to_string_lossy().to_string();
+        let Some(id) = name.strip_suffix(".json") else {
+            continue;
+        };
+        if read_entry(id).is_some_and(|entry| entry.session == session()) {
+            remove(id);
+        }
+    }
+    info!("Closed the session cleanly");
+}
+
+/// The chapters whose unsaved text is left over from a run that didn't close properly
+#[tauri::command]
+pub fn list_recovered() -> Vec<RecoveredChapter> {
+    let Ok(entries) = recovery_folder().read_dir() else {
+        return Vec::new();
+    };
+    let mut recovered: Vec<RecoveredChapter> = entries
+        .filter_map(Result::ok)
+        .filter_map(|file| {
+            let name = file.file_name().to_string_lossy().to_string();
+            let id = name.strip_suffix(".json")?.to_string();
+            let entry = read_entry(&id)?;
+            if entry.session == session() || !text_path(&id).is_file() {
+                return None;
+            }
+            let title = parse_project(entry.project.clone())
+                .map_or_else(|| entry.chapter.clone(), |x| x.title(&entry.chapter));
+            Some(RecoveredChapter {
+                id,
+                project: entry.project,
+                chapter: entry.chapter,
+                title,
+                saved: entry.saved,
+            })
+        })
+        .collect();
+    recovered.sort_by_key(|x| x.saved);
+    if !recovered.is_empty() {
+        warn!(
+            "The last session didn't close properly, recovered {} chapters",
+            recovered.len()
+        );
+    }
+    recovered
+}
+
+/// The recovered text and the saved one of the chapter, `None` while an encrypted project is locked
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn read_recovered(id: String) -> Option<RecoveredText> {
+    let entry = read_entry(&id)?;
+    let bytes = fs::read(text_path(&id)).ok()?;
+    let recovered = match open_for(&entry.project, bytes) {
+        Ok(recovered) => recovered,
+        Err(e) => {
+            warn!("Error while reading journal {id}: {e}");
+            return None;
+        }
+    };
+    let content_path = entry
+        .project
+        .join("Chapters")
+        .join(&entry.chapter)
+        .join("Content.md");
+    Some(RecoveredText {
+        recovered,
+        saved: read_text(&content_path).unwrap_or_default(),
+    })
+}
+
+/// Saves the recovered text into the chapter. Returns what went wrong, empty if it worked.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn restore_recovered(id: String) -> String {
+    let Some(entry) = read_entry(&id) else {
+        return "The recovered text is gone.".to_string();
+    };
+    let chapter_path = entry.project.join("Chapters").join(&entry.chapter);
+    if !chapter_path.is_dir() {
+        return "The chapter doesn't exist anymore.".to_string();
+    }
+    let Some(text) = read_recovered(id.clone()) else {
+        return "The recovered text can't be read, unlock the project first.".to_string();
+    };
+    match write_text(&chapter_path.join("Content.md"), &text.recovered) {
+        Ok(()) => {
+            info!("Restored unsaved text of {chapter_path:?}");
+            remove(&id);
+            String::new()
+        }
+        Err(e) => {
+            warn!("Error while restoring {chapter_path:?}: {e}");
+            e.to_string()
+        }
+    }
+}
+
+/// Throws the recovered text of the chapter away
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn discard_recovered(id: String) {
+    remove(&id);
+}
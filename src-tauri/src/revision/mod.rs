@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use shared::Revision;
+
+use crate::encryption::{read_text, write_text};
+
+/// Hidden file inside a chapter folder the tracked changes are kept in
+const REVISION_FILE: &str = ".revision.json";
+
+/// Reads the tracked changes of the chapter folder at `chapter_path`, if it is in revision mode
+pub fn read_revision(chapter_path: &Path) -> Option<Revision> {
+    let revision_path = chapter_path.join(REVISION_FILE);
+    let content = read_text(&revision_path).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(revision) => Some(revision),
+        Err(e) => {
+            warn!("Revision {revision_path:?} is not readable: {e}");
+            None
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_revision(path: String) -> Option<Revision> {
+    read_revision(Path::new(&path))
+}
+
+/// Writes the tracked changes of the chapter folder at `path`, `None` ends revision mode
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn write_revision(path: String, revision: Option<Revision>) {
+    let revision_path = PathBuf::from(path).join(REVISION_FILE);
+
+    let Some(revision) = revision else {
+        if revision_path.exists() {
+            match fs::remove_file(&revision_path) {
+                Ok(()) => info!("Ended revision: {revision_path:?}"),
+                Err(e) => warn!("Error while removing revision: {e}"),
+            }
+        }
+        return;
+    };
+
+    let string = serde_json::to_string(&revision).unwrap();
+    match write_text(&revision_path, &string) {
+        Ok(()) => info!("Wrote revision: {revision_path:?}"),
+        Err(e) => warn!("Error while writing revision: {e}"),
+    }
+}
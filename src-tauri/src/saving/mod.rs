@@ -1,12 +1,17 @@
 use std::fs::{self, File};
 use std::path::PathBuf;
 
-use shared::Project;
+use shared::{chapter_folder_name, Project, ProjectTemplate};
 
-use crate::loader::parse_project;
+use crate::loader::write_project_config;
+use crate::recent::open_project_at;
+use crate::templates::apply_template;
 
+/// Creates the folders of a new project at `path`, with the chapters, notes
+/// and codex entries of `template`
 #[tauri::command]
-pub fn create_project(path: String) -> Option<Project> {
+#[allow(clippy::needless_pass_by_value)]
+pub fn create_project(path: String, template: Option<ProjectTemplate>) -> Option<Project> {
     let mut path = PathBuf::from(path);
 
     let _ = fs::create_dir(&path);
@@ -37,7 +42,11 @@ pub fn create_project(path: String) -> Option<Project> {
     let _ = File::create(&path);
     path.pop();
 
-    parse_project(path)
+    if let Some(template) = template {
+        write_project_config(apply_template(&path, &template));
+    }
+
+    open_project_at(path)
 }
 
 use std::io;
@@ -117,10 +126,22 @@ pub fn rename_path(path: &str, old: &str, new: &str) {
     }
 }
 
+/// Creates the folder of a new chapter called `title` in the project at `path`.
+/// Returns the name of the folder, the title is kept in the project metadata.
 #[tauri::command]
-pub fn add_chapter(path: String) {
-    let mut path = PathBuf::from(path);
-    let _ = fs::create_dir(&path);
+#[allow(clippy::needless_pass_by_value)]
+pub fn add_chapter(path: String, title: String) -> String {
+    let chapters_path = PathBuf::from(path).join("Chapters");
+    let base = chapter_folder_name(&title);
+    let mut name = base.clone();
+    let mut index = 2;
+    while chapters_path.join(&name).exists() {
+        name = format!("{base}-{index}");
+        index += 1;
+    }
+
+    let mut path = chapters_path.join(&name);
+    let _ = fs::create_dir_all(&path);
 
     path.push("Note");
     path.set_extension("md");
@@ -131,4 +152,6 @@ pub fn add_chapter(path: String) {
     path.set_extension("md");
     let _ = File::create(&path);
     path.pop();
+
+    name
 }
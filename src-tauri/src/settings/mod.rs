@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::Path;
+
+use log::{info, warn};
+use serde_json::{Map, Value};
+use shared::{LoadedSettings, Settings, SETTINGS_VERSION, SYSTEM_THEME};
+
+use crate::logging::set_level;
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Unreadable settings are kept here instead of being overwritten
+const BROKEN_SETTINGS_FILE: &str = "settings.invalid.json";
+
+/// Brings settings written by an older version up to `SETTINGS_VERSION`
+fn migrate(settings: &mut Map<String, Value>, version: u32) {
+    if version < 2 {
+        // Version 1 had no version field and kept the autosave interval in milliseconds
+        if let Some(interval) = settings.remove("interval").and_then(|x| x.as_u64()) {
+            settings.insert("autosave_interval".into(), (interval / 60_000).into());
+        }
+    }
+    settings.insert("version".into(), SETTINGS_VERSION.into());
+}
+
+/// Reads the settings one field at a time, so a broken value only resets itself
+fn parse_settings(file: Map<String, Value>, errors: &mut Vec<String>) -> Settings {
+    let mut merged = match serde_json::to_value(Settings::default()) {
+        Ok(Value::Object(defaults)) => defaults,
+        _ => Map::new(),
+    };
+
+    for (key, value) in file {
+        if !merged.contains_key(&key) {
+            info!("Ignoring unknown setting: {key}");
+            continue;
+        }
+        let previous = merged.insert(key.clone(), value);
+        if let Err(e) = serde_json::from_value::<Settings>(Value::Object(merged.clone())) {
+            errors.push(format!("{key}: {e}"));
+            if let Some(previous) = previous {
+                merged.insert(key, previous);
+            }
+        }
+    }
+
+    serde_json::from_value(Value::Object(merged)).unwrap_or_default()
+}
+
+fn save(file_path: &Path, settings: &Settings) {
+    if let Some(parent) = file_path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let string = serde_json::to_string_pretty(settings).unwrap();
+    match fs::write(file_path, string) {
+        Ok(()) => info!("Wrote settings: {file_path:?}"),
+        Err(e) => warn!("Error while writing settings: {e}"),
+    }
+}
+
+/// Reads `settings.json` from the folder `path`, migrating and validating it.
+/// Everything that couldn't be read is reset to its default and reported.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn get_settings(path: String) -> LoadedSettings {
+    let file_path = Path::new(&path).join(SETTINGS_FILE);
+    let Ok(content) = fs::read_to_string(&file_path) else {
+        // Without settings the theme follows the system
+        return LoadedSettings {
+            settings: Settings {
+                theme: SYSTEM_THEME.to_string(),
+                ..Settings::default()
+            },
+            errors: Vec::new(),
+        };
+    };
+
+    let mut errors = Vec::new();
+    let mut file = match serde_json::from_str::<Value>(&content) {
+        Ok(Value::Object(file)) => file,
+        Ok(_) => {
+            errors.push(format!("{SETTINGS_FILE} does not contain settings"));
+            Map::new()
+        }
+        Err(e) => {
+            errors.push(format!("{SETTINGS_FILE} is not valid JSON: {e}"));
+            Map::new()
+        }
+    };
+    if !errors.is_empty() {
+        let broken_path = Path::new(&path).join(BROKEN_SETTINGS_FILE);
+        if fs::copy(&file_path, &broken_path).is_ok() {
+            errors.push(format!("The old file was kept as {BROKEN_SETTINGS_FILE}"));
+        }
+    }
+
+    let version = file
+        .get("version")
+        .and_then(Value::as_u64)
+        .map_or(1, |version| u32::try_from(version).unwrap_or(u32::MAX));
+    if version > SETTINGS_VERSION {
+        errors.push(format!(
+            "{SETTINGS_FILE} is from a newer version of PaperSmith, unknown settings are ignored"
+        ));
+    } else if version < SETTINGS_VERSION {
+        migrate(&mut file, version);
+    }
+
+    let mut settings = parse_settings(file, &mut errors);
+    errors.extend(settings.validate());
+    set_level(settings.log_level);
+    for error in &errors {
+        warn!("Settings: {error}");
+    }
+
+    if version < SETTINGS_VERSION {
+        info!("Migrated settings from version {version} to {SETTINGS_VERSION}");
+        save(&file_path, &settings);
+    }
+
+    LoadedSettings { settings, errors }
+}
+
+/// Writes `settings.json` into the folder `path`
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn write_settings(path: String, settings: Settings) {
+    set_level(settings.log_level);
+    save(&Path::new(&path).join(SETTINGS_FILE), &settings);
+}
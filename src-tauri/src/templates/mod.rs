@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use shared::{
+    chapter_folder_name, ChapterMeta, CodexEntry, Project, ProjectTemplate, TemplateChapter,
+    TemplateSettings,
+};
+
+use crate::codex::{codex_entries, write_codex_entry};
+use crate::encryption::is_encrypted;
+
+/// Templates that come with PaperSmith, they are compiled into the app
+const BUILTIN_TEMPLATES: [&str; 4] = [
+    include_str!("builtin/novel.json"),
+    include_str!("builtin/short_stories.json"),
+    include_str!("builtin/screenplay.json"),
+    include_str!("builtin/non_fiction.json"),
+];
+
+fn templates_path() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_default()
+        .join("PaperSmith")
+        .join("templates")
+}
+
+/// Name of the file a template is saved in, without characters file systems don't allow
+fn template_file(name: &str) -> PathBuf {
+    let file_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    templates_path().join(format!("{}.json", file_name.trim()))
+}
+
+fn builtin_templates() -> Vec<ProjectTemplate> {
+    BUILTIN_TEMPLATES
+        .iter()
+        .filter_map(|template| match serde_json::from_str(template) {
+            Ok(template) => Some(ProjectTemplate {
+                builtin: true,
+                ..template
+            }),
+            Err(e) => {
+                warn!("Built-in template is not readable: {e}");
+                None
+            }
+        })
+        .collect()
+}
+
+fn user_templates() -> Vec<ProjectTemplate> {
+    let Ok(dir) = templates_path().read_dir() else {
+        return Vec::new();
+    };
+    let mut templates: Vec<ProjectTemplate> = dir
+        .filter_map(Result::ok)
+        .map(|file| file.path())
+        .filter(|path| path.extension().is_some_and(|x| x == "json"))
+        .filter_map(|path| {
+            let content = fs::read_to_string(&path).ok()?;
+            match serde_json::from_str::<ProjectTemplate>(&content) {
+                Ok(template) => Some(ProjectTemplate {
+                    builtin: false,
+                    ..template
+                }),
+                Err(e) => {
+                    warn!("Template {path:?} is not readable: {e}");
+                    None
+                }
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+/// The built-in templates followed by the ones the user saved
+#[tauri::command]
+pub fn list_templates() -> Vec<ProjectTemplate> {
+    let mut templates = builtin_templates();
+    templates.extend(user_templates());
+    templates
+}
+
+/// Writes the chapters, notes and codex entries of `template` into the new
+/// project at `path`
+pub fn apply_template(path: &Path, template: &ProjectTemplate) -> Project {
+    let mut project = Project {
+        path: path.to_path_buf(),
+        chapters: Vec::new(),
+        active_chapter: None,
+        metadata: BTreeMap::new(),
+    };
+
+    if let Err(e) = fs::write(path.join("Note.md"), &template.note) {
+        warn!("Error while writing the project note: {e}");
+    }
+
+    for chapter in &template.chapters {
+        let title = chapter.name.trim();
+        if title.is_empty() {
+            continue;
+        }
+        let base = chapter_folder_name(title);
+        let mut name = base.clone();
+        let mut index = 2;
+        while project.chapters.contains(&name) {
+            name = format!("{base}-{index}");
+            index += 1;
+        }
+        let chapter_path = path.join("Chapters").join(&name);
+        if let Err(e) = fs::create_dir_all(&chapter_path) {
+            warn!("Error while creating chapter {title}: {e}");
+            continue;
+        }
+        let _ = fs::write(chapter_path.join("Content.md"), &chapter.content);
+        let _ = fs::write(chapter_path.join("Note.md"), &chapter.note);
+
+        project.metadata.insert(
+            name.clone(),
+            ChapterMeta {
+                title: title.to_string(),
+                ..chapter.meta.clone()
+            },
+        );
+        project.chapters.push(name);
+    }
+    if !project.chapters.is_empty() {
+        project.active_chapter = Some(0);
+    }
+
+    // Entries with names that can't be folder names are left out
+    for entry in &template.codex {
+        write_codex_entry(
+            path.to_string_lossy().to_string(),
+            None,
+            CodexEntry {
+                images: Vec::new(),
+                ..entry.clone()
+            },
+        );
+    }
+
+    info!("Created project {path:?} from template {}", template.name);
+    project
+}
+
+/// Turns `project` into a template called `name`, with its chapters, notes
+/// and codex entries. Images of codex entries are left out.
+/// Returns what went wrong, or an empty string when the template was saved.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn save_project_as_template(
+    project: Project,
+    name: String,
+    description: String,
+    settings: TemplateSettings,
+) -> String {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return "Please enter a name".to_string();
+    }
+    // Templates aren't encrypted, the chapters would be readable in them
+    if is_encrypted(&project.path) {
+        return "Encrypted projects can't be saved as templates".to_string();
+    }
+    if builtin_templates()
+        .iter()
+        .any(|template| template.name == name)
+    {
+        return format!("\"{name}\" is the name of a built-in template");
+    }
+
+    let chapters = project
+        .chapters
+        .iter()
+        .map(|chapter| {
+            let chapter_path = project.path.join("Chapters").join(chapter);
+            TemplateChapter {
+                name: project.title(chapter),
+                content: fs::read_to_string(chapter_path.join("Content.md")).unwrap_or_default(),
+                note: fs::read_to_string(chapter_path.join("Note.md")).unwrap_or_default(),
+                meta: ChapterMeta {
+                    title: String::new(),
+                    ..project.meta(chapter)
+                },
+            }
+        })
+        .collect();
+    let codex = codex_entries(&project.path)
+        .into_iter()
+        .map(|entry| CodexEntry {
+            images: Vec::new(),
+            ..entry
+        })
+        .collect();
+
+    let template = ProjectTemplate {
+        name: name.clone(),
+        description,
+        note: fs::read_to_string(project.path.join("Note.md")).unwrap_or_default(),
+        chapters,
+        codex,
+        settings,
+        builtin: false,
+    };
+
+    let file = template_file(&name);
+    let _ = fs::create_dir_all(templates_path());
+    let string = serde_json::to_string_pretty(&template).unwrap();
+    match fs::write(&file, string) {
+        Ok(()) => {
+            info!("Saved template: {file:?}");
+            String::new()
+        }
+        Err(e) => format!("The template could not be saved: {e}"),
+    }
+}
+
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn delete_template(name: String) {
+    let file = template_file(&name);
+    if let Err(e) = fs::remove_file(&file) {
+        warn!("Error while deleting template {file:?}: {e}");
+    }
+}
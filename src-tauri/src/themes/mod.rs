@@ -0,0 +1,266 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use dark_light::Mode;
+use log::{info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rfd::FileDialog;
+use shared::{Theme, ThemeFile};
+use tauri::{App, Manager};
+
+/// Emitted whenever a file in the themes folder changes
+const THEMES_CHANGED: &str = "themes-changed";
+
+/// Emitted with whether the system is in dark mode whenever that changes
+const SYSTEM_THEME_CHANGED: &str = "system-theme-changed";
+
+/// How often the system theme is checked, `dark_light` can't notify about changes
+const SYSTEM_THEME_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Keeps the watcher of the themes folder alive as long as the app runs
+struct ThemeWatcher(#[allow(dead_code)] Mutex<RecommendedWatcher>);
+
+fn themes_path() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_default()
+        .join("PaperSmith")
+        .join("themes")
+}
+
+fn is_theme_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|x| x.to_str()),
+        Some("toml" | "json")
+    )
+}
+
+/// Reads and validates a TOML or JSON theme
+fn parse_theme(path: &Path) -> Result<Theme, Vec<String>> {
+    let content = fs::read_to_string(path).map_err(|e| vec![e.to_string()])?;
+    let theme: Theme = if path.extension().is_some_and(|x| x == "json") {
+        serde_json::from_str(&content).map_err(|e| vec![e.to_string()])?
+    } else {
+        toml::from_str(&content).map_err(|e| vec![e.message().to_string()])?
+    };
+
+    let errors = theme.validate();
+    if errors.is_empty() {
+        Ok(theme)
+    } else {
+        Err(errors)
+    }
+}
+
+fn read_theme_file(path: &Path) -> ThemeFile {
+    let file = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    match parse_theme(path) {
+        Ok(theme) => ThemeFile {
+            file,
+            theme: Some(theme),
+            errors: Vec::new(),
+        },
+        Err(errors) => {
+            warn!("Theme {path:?} is not valid: {errors:?}");
+            ThemeFile {
+                file,
+                theme: None,
+                errors,
+            }
+        }
+    }
+}
+
+/// All theme files in the themes folder, sorted by file name.
+/// When two themes share a name only the first one is used.
+#[tauri::command]
+pub fn list_themes() -> Vec<ThemeFile> {
+    let path = themes_path();
+    if let Err(e) = fs::create_dir_all(&path) {
+        warn!("Error while creating themes folder: {e}");
+        return Vec::new();
+    }
+    let Ok(dir) = path.read_dir() else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = dir
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| is_theme_file(path))
+        .collect();
+    paths.sort();
+
+    let mut names = HashSet::new();
+    paths
+        .iter()
+        .map(|path| {
+            let mut file = read_theme_file(path);
+            if let Some(theme) = &file.theme {
+                if !names.insert(theme.name.to_lowercase()) {
+                    file.errors.push(format!(
+                        "Another theme is already called \"{}\"",
+                        theme.name
+                    ));
+                    file.theme = None;
+                }
+            }
+            file
+        })
+        .collect()
+}
+
+/// Name for `file` in the themes folder that no other theme file has,
+/// `name (2).toml` and so on if `file` is taken
+fn unused_file_name(file: &str) -> String {
+    let path = Path::new(file);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    std::iter::once(file.to_string())
+        .chain((2..).map(|number| format!("{stem} ({number}).{extension}")))
+        .find(|name| !themes_path().join(name).exists())
+        .unwrap_or_default()
+}
+
+/// Lets the user pick a theme file and copies it into the themes folder if it is valid.
+/// An existing theme file of the same name is kept. Returns `None` if nothing was picked.
+#[tauri::command]
+pub fn import_theme() -> Option<ThemeFile> {
+    let source = FileDialog::new()
+        .add_filter("Theme", &["toml", "json"])
+        .pick_file()?;
+
+    let mut file = read_theme_file(&source);
+    if file.theme.is_none() {
+        return Some(file);
+    }
+
+    file.file = unused_file_name(&file.file);
+    let target = themes_path().join(&file.file);
+    let result = fs::create_dir_all(themes_path()).and_then(|()| fs::copy(&source, &target));
+    match result {
+        Ok(_) => {
+            info!("Imported theme: {target:?}");
+            Some(file)
+        }
+        Err(e) => {
+            warn!("Error while importing theme: {e}");
+            Some(ThemeFile {
+                theme: None,
+                errors: vec![e.to_string()],
+                ..file
+            })
+        }
+    }
+}
+
+/// Lets the user choose where to save a copy of the theme file `file`.
+/// Returns whether it was saved.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn export_theme(file: String) -> bool {
+    let source = themes_path().join(&file);
+    let Some(target) = FileDialog::new().set_file_name(&file).save_file() else {
+        return false;
+    };
+
+    match fs::copy(&source, &target) {
+        Ok(_) => {
+            info!("Exported theme to {target:?}");
+            true
+        }
+        Err(e) => {
+            warn!("Error while exporting theme: {e}");
+            false
+        }
+    }
+}
+
+/// Tells the frontend whenever a theme file is added, changed or removed
+pub fn watch_themes(app: &App) {
+    let path = themes_path();
+    if let Err(e) = fs::create_dir_all(&path) {
+        warn!("Error while creating themes folder: {e}");
+        return;
+    }
+
+    let handle = app.handle();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_))
+            || !event.paths.iter().any(|path| is_theme_file(path))
+        {
+            return;
+        }
+        if let Err(e) = handle.emit_all(THEMES_CHANGED, ()) {
+            warn!("Error while emitting theme change: {e}");
+        }
+    });
+
+    let watcher = watcher.and_then(|mut watcher| {
+        watcher.watch(&path, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => {
+            app.manage(ThemeWatcher(Mutex::new(watcher)));
+        }
+        Err(e) => warn!("Error while watching themes folder: {e}"),
+    }
+}
+
+/// Whether getting the system theme failed the last time. The system theme is
+/// polled, so only the first failure in a row is logged.
+static DETECTION_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the system is in dark mode, `None` if it doesn't say
+fn system_dark_mode() -> Option<bool> {
+    let mode = dark_light::detect();
+    let failed = DETECTION_FAILED.swap(mode.is_err(), Ordering::Relaxed);
+    match mode {
+        Ok(Mode::Dark) => Some(true),
+        Ok(Mode::Light) => Some(false),
+        Ok(Mode::Unspecified) => None,
+        Err(e) => {
+            if !failed {
+                warn!("Error getting system theme: {e:?}");
+            }
+            None
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_dark_mode() -> bool {
+    system_dark_mode().unwrap_or_default()
+}
+
+/// Tells the frontend whenever the system switches between light and dark mode
+pub fn watch_system_theme(app: &App) {
+    let handle = app.handle();
+    let mut dark_mode = system_dark_mode();
+
+    thread::spawn(move || loop {
+        thread::sleep(SYSTEM_THEME_INTERVAL);
+
+        let current = system_dark_mode();
+        if current.is_none() || current == dark_mode {
+            continue;
+        }
+        dark_mode = current;
+        info!("System theme changed, dark mode: {dark_mode:?}");
+        if let Err(e) = handle.emit_all(SYSTEM_THEME_CHANGED, current) {
+            warn!("Error while emitting system theme change: {e}");
+        }
+    });
+}
@@ -0,0 +1,124 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use log::{info, warn};
+use shared::{Project, TrashItem};
+
+const TRASH_FOLDER: &str = ".trash";
+const TRASH_INFO: &str = "trash.json";
+
+fn trash_path(project_path: &Path) -> PathBuf {
+    project_path.join(TRASH_FOLDER)
+}
+
+fn read_item(item_path: &Path) -> Option<TrashItem> {
+    let content = fs::read_to_string(item_path.join(TRASH_INFO)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(item) => Some(item),
+        Err(e) => {
+            warn!("Trash info {item_path:?} is not readable: {e}");
+            None
+        }
+    }
+}
+
+/// Moves a chapter into the trash of the project, remembering where it was.
+/// Returns whether the chapter was moved.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn trash_chapter(project: Project, chapter: String) -> bool {
+    let Some(position) = project.chapters.iter().position(|x| *x == chapter) else {
+        return false;
+    };
+
+    let deleted = Utc::now();
+    let item = TrashItem {
+        id: format!("{}-{chapter}", deleted.timestamp_millis()),
+        name: chapter.clone(),
+        position,
+        deleted: deleted.timestamp(),
+        meta: project.meta(&chapter),
+    };
+
+    let item_path = trash_path(&project.path).join(&item.id);
+    if let Err(e) = fs::create_dir_all(&item_path) {
+        warn!("Error while creating trash folder: {e}");
+        return false;
+    }
+
+    let chapter_path = project.path.join("Chapters").join(&chapter);
+    if let Err(e) = fs::rename(&chapter_path, item_path.join(&chapter)) {
+        warn!("Error while moving chapter to trash: {e}");
+        let _ = fs::remove_dir(&item_path);
+        return false;
+    }
+
+    let string = serde_json::to_string_pretty(&item).unwrap();
+    match fs::write(item_path.join(TRASH_INFO), string) {
+        Ok(()) => info!("Moved chapter to trash: {item_path:?}"),
+        Err(e) => warn!("Error while writing trash info: {e}"),
+    }
+
+    true
+}
+
+#[tauri::command]
+pub fn list_trash(path: String) -> Vec<TrashItem> {
+    let Ok(dir) = trash_path(Path::new(&path)).read_dir() else {
+        return Vec::new();
+    };
+
+    let mut items: Vec<TrashItem> = dir
+        .filter_map(Result::ok)
+        .filter_map(|entry| read_item(&entry.path()))
+        .collect();
+    items.sort_by_key(|item| std::cmp::Reverse(item.deleted));
+    items
+}
+
+/// Moves a chapter out of the trash back into `Chapters/`.
+/// If the name was taken in the meantime a number gets appended.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn restore_chapter(path: String, id: String) -> Option<TrashItem> {
+    let project_path = PathBuf::from(path);
+    let item_path = trash_path(&project_path).join(&id);
+    let mut item = read_item(&item_path)?;
+
+    let chapters_path = project_path.join("Chapters");
+    let mut name = item.name.clone();
+    let mut index = 1;
+    while chapters_path.join(&name).exists() {
+        name = format!("{} ({index})", item.name);
+        index += 1;
+    }
+
+    if let Err(e) = fs::rename(item_path.join(&item.name), chapters_path.join(&name)) {
+        warn!("Error while restoring chapter: {e}");
+        return None;
+    }
+    let _ = fs::remove_dir_all(&item_path);
+
+    info!("Restored chapter {name} from trash");
+    item.name = name;
+    Some(item)
+}
+
+/// Permanently deletes trashed chapters older than `days`, or all of them
+#[tauri::command]
+pub fn empty_trash(path: String, days: Option<u32>) {
+    let project_path = PathBuf::from(&path);
+    let cutoff = days.map(|days| Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60);
+
+    for item in list_trash(path) {
+        if cutoff.is_some_and(|cutoff| item.deleted > cutoff) {
+            continue;
+        }
+        let item_path = trash_path(&project_path).join(&item.id);
+        match fs::remove_dir_all(&item_path) {
+            Ok(()) => info!("Deleted from trash: {item_path:?}"),
+            Err(e) => warn!("Error while emptying trash: {e}"),
+        }
+    }
+}
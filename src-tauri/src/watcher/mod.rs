@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::{info, warn};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use shared::bytes_checksum;
+use tauri::{AppHandle, Manager};
+
+/// Changes that come in together, like a sync tool writing several files, are sent at once
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+const CHAPTER_CHANGED: &str = "chapter-changed";
+const NOTE_CHANGED: &str = "note-changed";
+const PROJECT_CHANGED: &str = "project-changed";
+
+/// Checksums of the files PaperSmith wrote itself. Changes to them aren't
+/// reported as long as the files still have that content.
+static OWN_WRITES: Mutex<BTreeMap<PathBuf, u64>> = Mutex::new(BTreeMap::new());
+
+/// Watches the open project, dropping it stops the watcher
+#[derive(Default)]
+pub struct ProjectWatcher(Mutex<Option<RecommendedWatcher>>);
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Change {
+    /// The project file or the chapter folders
+    Project,
+    /// The note of the project or of a chapter
+    Note(Option<String>),
+    /// The content of a chapter
+    Chapter(String),
+}
+
+/// The path as the watcher reports it, files that don't exist yet are looked
+/// up through their folder
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path)
+        .or_else(|_| match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => fs::canonicalize(parent).map(|x| x.join(name)),
+            _ => Ok(path.to_path_buf()),
+        })
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Remembers that PaperSmith is about to write `bytes` to `path`
+pub fn note_own_write(path: &Path, bytes: &[u8]) {
+    if let Ok(mut writes) = OWN_WRITES.lock() {
+        writes.insert(canonical(path), bytes_checksum(bytes));
+    }
+}
+
+fn is_own_write(path: &Path) -> bool {
+    let Ok(writes) = OWN_WRITES.lock() else {
+        return false;
+    };
+    let Some(checksum) = writes.get(path) else {
+        return false;
+    };
+    fs::read(path).is_ok_and(|bytes| bytes_checksum(&bytes) == *checksum)
+}
+
+fn classify(root: &Path, path: &Path) -> Option<Change> {
+    let parts: Vec<&str> = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|part| part.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+
+    match parts.as_slice() {
+        [".papersmith.json"] | ["Chapters", _] => Some(Change::Project),
+        ["Note.md"] => Some(Change::Note(None)),
+        ["Chapters", chapter, "Note.md"] => Some(Change::Note(Some((*chapter).to_string()))),
+        ["Chapters", chapter, "Content.md"] => Some(Change::Chapter((*chapter).to_string())),
+        _ => None,
+    }
+}
+
+fn emit(app: &AppHandle, change: Change) {
+    let result = match change {
+        Change::Project => app.emit_all(PROJECT_CHANGED, ()),
+        Change::Note(chapter) => app.emit_all(NOTE_CHANGED, chapter),
+        Change::Chapter(chapter) => app.emit_all(CHAPTER_CHANGED, chapter),
+    };
+    if let Err(e) = result {
+        warn!("Error while emitting file change: {e}");
+    }
+}
+
+/// Tells the frontend when the chapters, notes or the project file at `path`
+/// change outside of PaperSmith. Watching stops when `path` is `None`.
+#[tauri::command]
+#[allow(clippy::needless_pass_by_value)]
+pub fn watch_project(app: AppHandle, watcher: tauri::State<ProjectWatcher>, path: Option<String>) {
+    let Ok(mut current) = watcher.0.lock() else {
+        return;
+    };
+    // Dropping the watcher also ends the thread of the previous project
+    *current = None;
+    let Some(path) = path else {
+        return;
+    };
+    let root = canonical(Path::new(&path));
+
+    let (sender, receiver) = mpsc::channel::<PathBuf>();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            let _ = sender.send(path);
+        }
+    });
+    let watcher = watcher.and_then(|mut watcher| {
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => {
+            info!("Watching project: {root:?}");
+            *current = Some(watcher);
+        }
+        Err(e) => {
+            warn!("Error while watching project: {e}");
+            return;
+        }
+    }
+
+    thread::spawn(move || {
+        while let Ok(first) = receiver.recv() {
+            let mut paths = vec![first];
+            while let Ok(path) = receiver.recv_timeout(DEBOUNCE) {
+                paths.push(path);
+            }
+
+            let changes: BTreeSet<Change> = paths
+                .iter()
+                .filter(|path| !is_own_write(path))
+                .filter_map(|path| classify(&root, path))
+                .collect();
+            for change in changes {
+                emit(&app, change);
+            }
+        }
+    });
+}
@@ -1,17 +1,19 @@
+use std::path::PathBuf;
 use std::rc::Rc;
 
-use gloo::utils::document;
 use gloo_timers::callback::Timeout;
 use serde::{Deserialize, Serialize};
 use serde_wasm_bindgen::to_value;
+use shared::CodexEntry;
+use shared::EditHistory;
 use shared::Project;
-use sidebar::buttons::Button;
+use shared::ProjectLock;
+use shared::Revision;
+use shared::ThemeFile;
 use statistic::StatisticWindow;
+use wasm_bindgen::closure::Closure;
 use wasm_bindgen::prelude::wasm_bindgen;
-use wasm_bindgen::JsCast;
 use wasm_bindgen::JsValue;
-use web_sys::HtmlDocument;
-use web_sys::HtmlElement;
 use yew::events::MouseEvent;
 use yew::platform::spawn_local;
 use yew::prelude::*;
@@ -19,6 +21,7 @@ use yew_icons::IconId;
 use yewdux::dispatch;
 use yewdux::prelude::*;
 
+use shared::LoadedSettings;
 use shared::Settings;
 
 #[path = "notepad/notepad.rs"]
@@ -35,25 +38,100 @@ use statistic::Statistics;
 
 #[path = "sidebar/sidebar.rs"]
 mod sidebar;
+use sidebar::trash::EmptyTrashArgs;
 use sidebar::SideBarWrapper;
 
 #[path = "project-wizard/wizard.rs"]
 mod wizard;
-use wizard::ProjectWizard;
+use wizard::{ProjectWizard, SaveTemplateMenu};
 
 #[path = "modal-system/modal.rs"]
 mod modal;
 use modal::Modal;
 use modal::VerticalModal;
 
+#[path = "commands/commands.rs"]
+mod commands;
+use commands::{use_commands, use_shortcuts, CommandButton, CommandPalette};
+
 #[path = "settings-menu/settings.rs"]
 mod settings;
-use settings::SettingsMenu;
+use settings::themes::{load_themes, set_dark_mode};
+use settings::{SettingsErrors, SettingsMenu};
+
+#[path = "corkboard/corkboard.rs"]
+mod corkboard;
+use corkboard::{Corkboard, Outliner};
+
+#[path = "codex/codex.rs"]
+mod codex;
+use codex::load_codex;
+
+#[path = "export/export.rs"]
+mod export;
+use export::{ExportMenu, PackMenu};
+
+#[path = "start-screen/start_screen.rs"]
+mod start_screen;
+use start_screen::StartScreen;
+
+#[path = "project-doctor/doctor.rs"]
+mod doctor;
+use doctor::{check_project, ProjectDoctor};
+
+#[path = "file-watcher/file_watcher.rs"]
+mod file_watcher;
+use file_watcher::use_file_watcher;
+
+#[path = "encryption/encryption.rs"]
+mod encryption;
+use encryption::{
+    is_readable, lock_project, project_lock, use_project_lock, EncryptionMenu, LockScreen,
+};
+
+#[path = "log-viewer/log_viewer.rs"]
+mod log_viewer;
+pub use log_viewer::init_logging;
+use log_viewer::LogViewer;
+
+#[path = "recovery/recovery.rs"]
+mod recovery;
+use recovery::{use_recovery_journal, RecoveryPrompt};
+
+#[path = "unsaved-changes/unsaved_changes.rs"]
+mod unsaved_changes;
+use unsaved_changes::{use_close_guard, Leave, UnsavedPrompt};
+
+#[path = "notes/notes.rs"]
+mod notes;
+use notes::{NoteEditor, OpenNote};
+
+#[path = "editor/editor.rs"]
+mod editor;
+use editor::EditorHandle;
 
 #[wasm_bindgen]
 extern "C" {
-    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"])]
-    async fn invoke(cmd: &str, args: JsValue) -> JsValue;
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"], js_name = invoke)]
+    async fn invoke_command(cmd: &str, args: JsValue) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "event"])]
+    async fn listen(event: &str, handler: &Closure<dyn FnMut(JsValue)>) -> JsValue;
+}
+
+/// Invokes the command `cmd` of the backend with a new call id. The messages the
+/// command logs carry it, and so do those of the frontend until the next `await`.
+pub async fn invoke(cmd: &str, args: JsValue) -> JsValue {
+    let call = log_viewer::begin_call();
+    let args = if args.is_object() {
+        args
+    } else {
+        js_sys::Object::new().into()
+    };
+    let _ = js_sys::Reflect::set(&args, &"call".into(), &call.into());
+    let result = invoke_command(cmd, args).await;
+    log_viewer::continue_call(call);
+    result
 }
 
 #[derive(Properties, PartialEq)]
@@ -72,12 +150,54 @@ pub struct FileWriteData {
     pub content: String,
 }
 
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum View {
+    #[default]
+    Editor,
+    Corkboard,
+    Outliner,
+    Notes,
+}
+
 #[derive(Default, Clone, PartialEq, Eq, Store, Debug)]
 pub struct State {
     project: Option<Project>,
     settings: Option<Settings>,
     changes: bool,
     dragger: Option<usize>,
+    view: View,
+    codex: Vec<CodexEntry>,
+    themes: Vec<ThemeFile>,
+    /// Whether the system is in dark mode, for the "System" theme
+    dark_mode: bool,
+    /// Counts the notes changed outside of PaperSmith, the open note reloads when it changes
+    note_reloads: u32,
+    /// Counts the saves of chapters, what is read from the chapter files reloads when it changes
+    saves: u32,
+    /// Encryption state of the project at the path, the open project may not be checked yet
+    lock: Option<(PathBuf, ProjectLock)>,
+    /// Chapter switch or close that waits until the unsaved changes are saved or discarded
+    leaving: Option<Leave>,
+    /// The note shown in the notes view
+    note: OpenNote,
+}
+
+#[derive(Serialize)]
+struct HistoryArgs {
+    path: String,
+    history: EditHistory,
+}
+
+#[derive(Serialize)]
+struct RevisionArgs {
+    path: String,
+    revision: Option<Revision>,
+}
+
+#[derive(Serialize)]
+struct BackupArgs {
+    path: String,
+    keep: u32,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -90,63 +210,26 @@ pub fn app() -> Html {
     let (state, dispatch) = use_store::<State>();
     let modal = use_state(|| html!());
 
-    let project_path = state.project.as_ref().map(|proj| proj.path.clone());
-    let text_input_ref = use_node_ref();
     let pages_ref = use_node_ref();
+    let editor = (*use_state(EditorHandle::default)).clone();
 
     let save_fn = {
-        let text_input_ref = text_input_ref.clone();
-        let state = state.clone();
+        let dispatch = dispatch.clone();
+        let editor = editor.clone();
 
         Callback::from(move |()| {
-            let text_input_ref = text_input_ref.clone();
-            let project_path = project_path.clone();
-            let state = state.clone();
-
-            spawn_local(async move {
-                if state.project.is_none() {
-                    return;
-                }
-                if state.project.as_ref().unwrap().active_chapter.is_none() {
-                    return;
-                }
-                if let Some(input_element) = text_input_ref.cast::<HtmlElement>() {
-                    let text = input_element.inner_text();
-
-                    if let Some(mut path) = project_path {
-                        path.push("Chapters");
-                        path.push(
-                            state
-                                .project
-                                .as_ref()
-                                .unwrap()
-                                .chapters
-                                .get(state.project.as_ref().unwrap().active_chapter.unwrap())
-                                .unwrap(),
-                        );
-                        path.push("Content.md");
-
-                        let write_data = FileWriteData {
-                            path: path.to_string_lossy().to_string(),
-                            content: text,
-                        };
-
-                        invoke(
-                            "write_to_file",
-                            serde_wasm_bindgen::to_value(&write_data).unwrap(),
-                        )
-                        .await;
-
-                        // modal.set(html! {});
-                    }
-                }
-            });
+            spawn_local(save_chapter(dispatch.clone(), editor.clone()));
         })
     };
 
     let save = {
         let dispatch = dispatch.clone();
-        Callback::from(move |_: MouseEvent| {
+        let readable = is_readable(&state);
+        Callback::from(move |()| {
+            // Nothing can be written while the project is locked, the changes stay
+            if !readable {
+                return;
+            }
             save_fn.emit(());
             dispatch.reduce_mut(|x| x.changes = false);
         })
@@ -169,7 +252,7 @@ pub fn app() -> Html {
 
     let open_modal = {
         let modal = modal.clone();
-        Callback::from(move |_| {
+        Callback::from(move |()| {
             modal.set(html! {
                 <Modal
                     content={html! {
@@ -205,9 +288,97 @@ pub fn app() -> Html {
         })
     };
 
+    let open_save_template = {
+        let modal = modal.clone();
+        let has_project = state.project.is_some();
+        Callback::from(move |()| {
+            if !has_project {
+                return;
+            }
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <SaveTemplateMenu
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |_| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        })
+    };
+
+    let open_doctor = {
+        let modal = modal.clone();
+        let has_project = state.project.is_some();
+        Callback::from(move |()| {
+            if !has_project {
+                return;
+            }
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <ProjectDoctor
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |_| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        })
+    };
+
+    let open_encryption = {
+        let modal = modal.clone();
+        let has_project = state.project.is_some();
+        Callback::from(move |()| {
+            if !has_project {
+                return;
+            }
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <EncryptionMenu
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |_| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        })
+    };
+
+    // Unsaved changes are written while the key is still there
+    let lock = {
+        let dispatch = dispatch.clone();
+        let editor = editor.clone();
+        let modal = modal.clone();
+        Callback::from(move |()| {
+            if project_lock(&dispatch.get()) != Some(ProjectLock::Unlocked) {
+                return;
+            }
+            modal.set(html!());
+            let dispatch = dispatch.clone();
+            let editor = editor.clone();
+            spawn_local(async move {
+                if dispatch.get().changes {
+                    save_chapter(dispatch.clone(), editor).await;
+                    dispatch.reduce_mut(|x| x.changes = false);
+                }
+                lock_project(&dispatch).await;
+            });
+        })
+    };
+
     let open_statistics = {
         let modal = modal.clone();
-        Callback::from(move |_| {
+        Callback::from(move |()| {
             modal.set(html! {
                 <VerticalModal
                     content={html! {
@@ -223,9 +394,27 @@ pub fn app() -> Html {
         })
     };
 
-    let open_settings: Callback<MouseEvent> = {
+    let open_log = {
         let modal = modal.clone();
-        Callback::from(move |_| {
+        Callback::from(move |()| {
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <LogViewer
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |_| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        })
+    };
+
+    let open_settings = {
+        let modal = modal.clone();
+        Callback::from(move |()| {
             modal.set(html! {
                 <Modal
                     content={html! {
@@ -241,50 +430,193 @@ pub fn app() -> Html {
         })
     };
 
-    let on_load = {
+    let open_export = {
+        let modal = modal.clone();
+        Callback::from(move |()| {
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                    <ExportMenu
+                        closing_callback={
+                            let modal = modal.clone();
+                            Callback::from(move |_| modal.set(html!()))
+                        }
+                    />
+                    }}
+                />
+            });
+        })
+    };
+
+    let open_pack = {
+        let modal = modal.clone();
+        let has_project = state.project.is_some();
+        Callback::from(move |()| {
+            if !has_project {
+                return;
+            }
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <PackMenu
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |_| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        })
+    };
+
+    // Opens the project the user picks with `command`, a folder or an archive
+    let open_with = |command: &'static str| {
         let modal = modal.clone();
         let dispatch = dispatch.clone();
 
-        Callback::from(move |_| {
+        Callback::from(move |()| {
             let modal = modal.clone();
             let dispatch = dispatch.clone();
             spawn_local(async move {
-                let project_jsvalue = invoke("get_project", JsValue::null()).await;
+                let project_jsvalue = invoke(command, JsValue::null()).await;
                 let project_or_none: Option<Project> =
                     serde_wasm_bindgen::from_value(project_jsvalue).unwrap();
-                if project_or_none.is_some() {
-                    dispatch.reduce_mut(|state| state.project = project_or_none);
+                if let Some(project) = project_or_none {
+                    dispatch.reduce_mut(|state| open_project(state, project));
                     modal.set(html!());
                 }
             });
         })
     };
+    let on_load = open_with("get_project");
+    let on_open_archive = open_with("open_archive");
 
-    let on_undo = Callback::from(move |_| {
-        let html_doc: HtmlDocument = document().dyn_into().unwrap();
-        html_doc.exec_command("undo").unwrap();
-    });
+    let toggle_view = |view: View| {
+        let dispatch = dispatch.clone();
+        Callback::from(move |()| {
+            dispatch.reduce_mut(|x| {
+                x.view = if x.view == view { View::Editor } else { view };
+            });
+        })
+    };
+    let on_corkboard = toggle_view(View::Corkboard);
+    let on_outliner = toggle_view(View::Outliner);
+    let on_notes = toggle_view(View::Notes);
 
-    let on_redo = Callback::from(move |_| {
-        let html_doc: HtmlDocument = document().dyn_into().unwrap();
-        html_doc.exec_command("redo").unwrap();
-    });
+    let switch_chapter = |step: fn(usize, usize) -> usize| {
+        let dispatch = dispatch.clone();
+        Callback::from(move |()| {
+            dispatch.reduce_mut(|x| {
+                let Some(project) = x.project.as_ref() else {
+                    return;
+                };
+                if project.chapters.is_empty() {
+                    return;
+                }
+                let index = project
+                    .active_chapter
+                    .map_or(0, |active| step(active, project.chapters.len()));
+                open_chapter(x, index);
+            });
+        })
+    };
+
+    let open_palette = {
+        let modal = modal.clone();
+        Callback::from(move |()| {
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <CommandPalette
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |_| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        })
+    };
+
+    use_commands(vec![
+        ("command_palette", open_palette),
+        ("save", save),
+        ("create_project", open_modal),
+        ("save_as_template", open_save_template),
+        ("load_project", on_load.clone()),
+        ("open_archive", on_open_archive.clone()),
+        ("pack_project", open_pack),
+        ("export", open_export),
+        ("settings", open_settings),
+        ("statistics", open_statistics),
+        ("show_log", open_log),
+        ("check_project", open_doctor.clone()),
+        ("encryption", open_encryption),
+        ("lock_project", lock.clone()),
+        ("corkboard", on_corkboard),
+        ("outliner", on_outliner),
+        ("notes", on_notes),
+        (
+            "next_chapter",
+            switch_chapter(|active, count| (active + 1).min(count - 1)),
+        ),
+        (
+            "previous_chapter",
+            switch_chapter(|active, _| active.saturating_sub(1)),
+        ),
+    ]);
+    use_shortcuts();
+    use_file_watcher(editor.clone(), modal.clone());
+    use_recovery_journal(editor.clone());
+    use_close_guard();
+    use_project_lock(
+        state
+            .settings
+            .as_ref()
+            .map_or_else(|| Settings::default().lock_after, |x| x.lock_after),
+        lock,
+    );
 
     {
         let state = state.clone();
         let dispatch = dispatch.clone();
 
+        let modal = modal.clone();
+
         use_effect_with((), move |()| {
-            apply_settings(state, dispatch);
+            apply_settings(state, dispatch.clone(), modal);
+
+            // Theme files can be edited and the system theme can change while the app runs
+            spawn_local(async move {
+                let on_themes_change = {
+                    let dispatch = dispatch.clone();
+                    Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                        let dispatch = dispatch.clone();
+                        spawn_local(async move { load_themes(&dispatch).await });
+                    })
+                };
+                listen("themes-changed", &on_themes_change).await;
+                on_themes_change.forget();
+
+                let on_system_change = Closure::<dyn FnMut(JsValue)>::new(move |event| {
+                    let payload = js_sys::Reflect::get(&event, &"payload".into());
+                    if let Some(dark_mode) = payload.ok().and_then(|x| x.as_bool()) {
+                        set_dark_mode(&dispatch, dark_mode);
+                    }
+                });
+                listen("system-theme-changed", &on_system_change).await;
+                on_system_change.forget();
+            });
         });
     }
     {
         let on_load = on_load.clone();
+        let on_open_archive = on_open_archive.clone();
         let modal = modal.clone();
-        let text_input_ref = text_input_ref.clone();
         use_effect_with(state.project.clone(), move |project| {
             if let Some(project) = project.clone() {
-                let project_clone = project.clone();
                 spawn_local(async move {
                     let _project_jsvalue = invoke(
                         "write_project_config",
@@ -295,111 +627,195 @@ pub fn app() -> Html {
                     )
                     .await;
                 });
-                if let Some(active_chapter) = project_clone.active_chapter {
-                    let project = project_clone.clone();
-                    spawn_local(async move {
-                        let mut content_path = project.path.clone();
-                        content_path.push("Chapters");
-                        content_path
-                            .push(project_clone.chapters.get(active_chapter).unwrap().clone());
-                        content_path.push("Content");
-                        content_path.set_extension("md");
-                        let content = invoke(
-                            "get_file_content",
-                            to_value(&PathArgs {
-                                path: content_path.to_str().unwrap().to_string(),
-                            })
-                            .unwrap(),
-                        )
-                        .await
-                        .as_string()
-                        .unwrap();
-
-                        if let Some(input_element) = text_input_ref.cast::<HtmlElement>() {
-                            input_element.set_inner_text(content.as_str());
-
-                            // gloo_console::log!("Setting Textarea content");
-                            let _result =
-                                input_element.dispatch_event(&InputEvent::new("input").unwrap());
-                            // match result {
-                            //     Ok(x) => gloo_console::log!(format!("{}", x)),
-                            //     Err(x) => gloo_console::log!(x),
-                            // }
-                        }
-                    });
-                }
             } else {
+                let on_close = {
+                    let modal = modal.clone();
+                    Callback::from(move |_| modal.set(html!()))
+                };
                 modal.set(html! {
                     <Modal
                         content={html! {
-                <div class="flex flex-col h-32 w-full justify-between">
-                    <div class="text-3xl text-center">
-                        {"Please select or create a project"}
-                    </div>
-                    <div class="flex justify-evenly w-full text-xl">
-                        <button
-                            class="bg-primary text-mantle p-2 rounded-lg cursor-pointer border-0 text-inherit text-[length:inherit] hover:ring-1 hover:ring-primary"
-                            onclick={let on_load = on_load.clone();
-                            Callback::from(move |e: MouseEvent| {
-                                on_load.emit(e);
-                            })}
-                        >
-                            { "Open Project" }
-                        </button>
-                        <button
-                            class="bg-secondary text-mantle p-2 rounded-lg cursor-pointer border-0 text-inherit text-[length:inherit] hover:ring-1 hover:ring-secondary"
-                            onclick={let open_modal2 = open_modal2.clone();
-                            Callback::from(move |e: MouseEvent| {
-                                open_modal2.emit(e);
-                            })}
-                        >
-                            { "Create Project" }
-                        </button>
-                    </div>
-                </div>
-                                }}
+                            <StartScreen
+                                open_folder={on_load.reform(|_| ())}
+                                open_archive={on_open_archive.reform(|_| ())}
+                                create_project={open_modal2.clone()}
+                                closing_callback={on_close}
+                            />
+                        }}
                     />
                 });
             }
         });
     }
 
+    {
+        let dispatch = dispatch.clone();
+        let project = state.project.clone();
+        let project_path = state.project.as_ref().map(|project| project.path.clone());
+        let retention = state.settings.as_ref().map_or_else(
+            || Settings::default().trash_retention,
+            |x| x.trash_retention,
+        );
+        let backup_retention = state.settings.as_ref().map_or_else(
+            || Settings::default().backup_retention,
+            |x| x.backup_retention,
+        );
+        use_effect_with(project_path, move |project_path| {
+            if let Some(project_path) = project_path.clone() {
+                // Backed up once per session if it changed, the oldest backups beyond the retention go
+                let args = BackupArgs {
+                    path: project_path.to_string_lossy().to_string(),
+                    keep: backup_retention,
+                };
+                spawn_local(async move {
+                    invoke("backup_project", to_value(&args).unwrap()).await;
+                });
+                if retention > 0 {
+                    let args = EmptyTrashArgs {
+                        path: project_path.to_string_lossy().to_string(),
+                        days: Some(retention),
+                    };
+                    spawn_local(async move {
+                        invoke("empty_trash", to_value(&args).unwrap()).await;
+                    });
+                }
+                load_codex(project_path, dispatch);
+
+                // Offer to repair projects that were changed outside of PaperSmith
+                if let Some(project) = project {
+                    spawn_local(async move {
+                        if !check_project(project).await.is_empty() {
+                            open_doctor.emit(());
+                        }
+                    });
+                }
+            }
+        });
+    }
+
+    // Only reload the editor when the chapter itself changes, not when the
+    // project is edited otherwise (reordering, metadata, ...)
+    {
+        let editor = editor.clone();
+        let persist_history = state.settings.as_ref().map_or_else(
+            || Settings::default().persist_history,
+            |x| x.persist_history,
+        );
+        // Locked chapters load once the project is unlocked
+        let readable = is_readable(&state);
+        let content_path = state.project.as_ref().and_then(|project| {
+            if !readable {
+                return None;
+            }
+            let chapter = project.chapters.get(project.active_chapter?)?;
+            let mut content_path = project.path.clone();
+            content_path.push("Chapters");
+            content_path.push(chapter);
+            content_path.push("Content");
+            content_path.set_extension("md");
+            Some(content_path)
+        });
+        use_effect_with(content_path, move |content_path| {
+            if let Some(content_path) = content_path.clone() {
+                spawn_local(async move {
+                    let content = invoke(
+                        "get_file_content",
+                        to_value(&PathArgs {
+                            path: content_path.to_str().unwrap().to_string(),
+                        })
+                        .unwrap(),
+                    )
+                    .await
+                    .as_string()
+                    .unwrap();
+
+                    // Histories of chapters opened before are kept across switches
+                    let chapter_path = content_path.parent().unwrap().to_path_buf();
+                    let saved_history =
+                        if persist_history && !editor.borrow().is_loaded(&chapter_path) {
+                            let history_jsvalue = invoke(
+                                "get_history",
+                                to_value(&PathArgs {
+                                    path: chapter_path.to_string_lossy().to_string(),
+                                })
+                                .unwrap(),
+                            )
+                            .await;
+                            serde_wasm_bindgen::from_value(history_jsvalue).unwrap_or_default()
+                        } else {
+                            None
+                        };
+                    let revision_jsvalue = invoke(
+                        "get_revision",
+                        to_value(&PathArgs {
+                            path: chapter_path.to_string_lossy().to_string(),
+                        })
+                        .unwrap(),
+                    )
+                    .await;
+                    let revision: Option<Revision> =
+                        serde_wasm_bindgen::from_value(revision_jsvalue).unwrap_or_default();
+
+                    editor.update(|editor| {
+                        Some(editor.open(chapter_path, &content, saved_history, revision))
+                    });
+                });
+            }
+        });
+    }
+
     html! {
         <div class="h-screen w-screen flex flex-col">
             <div class="light lightdark medium dark verydark" />
             <div class="modal-wrapper">{ (*modal).clone() }</div>
+            <RecoveryPrompt editor={editor.clone()} />
+            <UnsavedPrompt editor={editor.clone()} />
+            if project_lock(&state) == Some(ProjectLock::Locked) {
+                <LockScreen />
+            }
             <style id="dynamic-style" />
             <div class="h-8 flex justify-left items-center p-2 bg-crust">
-                <Button
-                    callback={open_modal}
-                    icon={IconId::LucideFilePlus}
-                    title="Create Project"
-                    size=1.5
-                />
-                <Button
-                    callback={on_load}
-                    icon={IconId::LucideFolderOpen}
-                    title="Load Project"
-                    size=1.5
-                />
-                <Button callback={save} icon={IconId::LucideSave} title="Save" size=1.5 />
-                <Button
-                    callback={open_settings}
-                    icon={IconId::LucideSettings}
-                    title="Open Settings"
-                    size=1.5
-                />
+                <CommandButton command="create_project" icon={IconId::LucideFilePlus} />
+                <CommandButton command="load_project" icon={IconId::LucideFolderOpen} />
+                <CommandButton command="save" icon={IconId::LucideSave} />
+                <CommandButton command="export" icon={IconId::LucideFileDown} />
+                <CommandButton command="settings" icon={IconId::LucideSettings} />
                 <div class="w-[1px] h-[20px] bg-subtext my-0 mx-1 " />
-                <Button callback={on_undo} icon={IconId::LucideUndo} title="Undo" size=1.5 />
-                <Button callback={on_redo} icon={IconId::LucideRedo} title="Redo" size=1.5 />
+                <CommandButton command="corkboard" icon={IconId::LucideLayoutGrid} />
+                <CommandButton command="outliner" icon={IconId::LucideTable} />
+                <CommandButton command="notes" icon={IconId::LucideFileText} />
+                <div class="w-[1px] h-[20px] bg-subtext my-0 mx-1 " />
+                <CommandButton command="undo" icon={IconId::LucideUndo} />
+                <CommandButton command="redo" icon={IconId::LucideRedo} />
                 <div class="w-[1px] h-[20px] bg-subtext my-0 mx-1 " />
                 <TextStylingControls />
+                <div class="ml-auto" />
+                if project_lock(&state) == Some(ProjectLock::Unlocked) {
+                    <CommandButton command="lock_project" icon={IconId::LucideLock} />
+                }
+                <CommandButton command="command_palette" icon={IconId::LucideCommand} />
             </div>
             <div id="main_content" class="flex flex-1 grow min-h-0 m-3">
                 <div class="h-full bg-crust">
                     { html!{<SideBarWrapper modal={modal.clone()}/>} }
                 </div>
-                <Notepads pages_ref={pages_ref.clone()} text_input_ref={text_input_ref} />
+                if state.view == View::Corkboard {
+                    <Corkboard />
+                } else if state.view == View::Outliner {
+                    <Outliner />
+                } else if state.view == View::Notes {
+                    <NoteEditor modal={modal.clone()} />
+                }
+                // The editor stays mounted while planning so its content survives
+                <div
+                    class={classes!("flex", "flex-1", "min-w-0", (state.view != View::Editor).then_some("hidden"))}
+                >
+                    <Notepads
+                        pages_ref={pages_ref.clone()}
+                        {editor}
+                        modal={modal.clone()}
+                    />
+                </div>
             </div>
             <div
                 class="h-3 justify-between items-center flex p-2 bg-crust border-solid border-t-[2px] border-x-0 border-b-0 border-text"
@@ -408,19 +824,98 @@ pub fn app() -> Html {
                     <Statistics pages_ref={pages_ref.clone()} />
                 </div>
                 <div class="bottombar-right">
-                    <Button
-                        callback={open_statistics}
-                        icon={IconId::LucideBarChart3}
-                        title="Statistics"
-                        size=1.5
-                    />
+                    <CommandButton command="statistics" icon={IconId::LucideBarChart3} />
                 </div>
             </div>
         </div>
     }
 }
 
-fn apply_settings(state: Rc<State>, dispatch: Dispatch<State>) {
+/// Opens the chapter at `index`. With unsaved changes the user is asked first
+/// whether to save or discard them.
+fn open_chapter(state: &mut State, index: usize) {
+    let Some(project) = state.project.as_mut() else {
+        return;
+    };
+    if index >= project.chapters.len() || project.active_chapter == Some(index) {
+        return;
+    }
+    if state.changes {
+        state.leaving = Some(Leave::Chapter(index));
+    } else {
+        project.active_chapter = Some(index);
+    }
+}
+
+/// Opens `project` in place of the open one. With unsaved changes the user is
+/// asked first whether to save or discard them.
+pub fn open_project(state: &mut State, project: Project) {
+    if state.changes {
+        state.leaving = Some(Leave::Project(project));
+    } else {
+        state.project = Some(project);
+    }
+}
+
+/// Writes the text of the editor to the active chapter, with its history and tracked changes
+async fn save_chapter(dispatch: Dispatch<State>, editor: EditorHandle) {
+    let state = dispatch.get();
+    if !is_readable(&state) {
+        return;
+    }
+    let Some(project) = state.project.as_ref() else {
+        return;
+    };
+    let Some(chapter) = project
+        .active_chapter
+        .and_then(|active| project.chapters.get(active))
+    else {
+        return;
+    };
+    let text = editor.borrow().text();
+
+    let mut path = project.path.clone();
+    path.push("Chapters");
+    path.push(chapter);
+    path.push("Content.md");
+
+    let saved_history = editor.borrow_mut().saved();
+    let revision = editor.borrow_mut().tracked_revision().cloned();
+    let write_data = FileWriteData {
+        path: path.to_string_lossy().to_string(),
+        content: text,
+    };
+
+    invoke(
+        "write_to_file",
+        serde_wasm_bindgen::to_value(&write_data).unwrap(),
+    )
+    .await;
+    dispatch.reduce_mut(|x| x.saves += 1);
+
+    let persist_history = state.settings.as_ref().map_or_else(
+        || Settings::default().persist_history,
+        |x| x.persist_history,
+    );
+    if let Some((chapter_path, history)) = saved_history {
+        let chapter_path = chapter_path.to_string_lossy().to_string();
+        let args = RevisionArgs {
+            path: chapter_path.clone(),
+            revision,
+        };
+        invoke("write_revision", to_value(&args).unwrap()).await;
+
+        if persist_history {
+            let args = HistoryArgs {
+                path: chapter_path,
+                history,
+            };
+            invoke("write_history", to_value(&args).unwrap()).await;
+        }
+    }
+}
+
+fn apply_settings(state: Rc<State>, dispatch: Dispatch<State>, modal: UseStateHandle<Html>) {
     spawn_local(async move {
         let path_jsvalue = invoke("get_data_dir", JsValue::NULL).await;
 
@@ -481,20 +976,39 @@ fn apply_settings(state: Rc<State>, dispatch: Dispatch<State>) {
         )
         .await;
 
-        let settings_result: Option<Settings> =
-            serde_wasm_bindgen::from_value(settings_jsvalue).unwrap();
+        let loaded: LoadedSettings =
+            serde_wasm_bindgen::from_value(settings_jsvalue).unwrap_or_default();
 
-        if let Some(settings) = settings_result.clone() {
-            dispatch.reduce_mut(|state| state.settings = settings_result);
+        let reopen_last_project = loaded.settings.reopen_last_project;
+        dispatch.reduce_mut(|state| state.settings = Some(loaded.settings));
 
-            switch_theme(settings.theme);
+        // Opened with the chapter it was on, the start screen shows otherwise
+        if reopen_last_project {
+            let project_jsvalue = invoke("get_last_project", JsValue::NULL).await;
+            let project: Option<Project> =
+                serde_wasm_bindgen::from_value(project_jsvalue).unwrap_or_default();
+            if project.is_some() {
+                dispatch.reduce_mut(|state| state.project = project);
+                modal.set(html!());
+            }
         }
-    });
-}
 
-fn switch_theme(theme: String) {
-    let html_doc: HtmlDocument = document().dyn_into().unwrap();
-    let body = html_doc.body().unwrap();
-    let theme2 = theme.to_lowercase().replace(' ', "");
-    body.set_class_name(format!("{theme2} bg-crust text-text").as_str());
+        if !loaded.errors.is_empty() {
+            let on_close = {
+                let modal = modal.clone();
+                Callback::from(move |_| modal.set(html!()))
+            };
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <SettingsErrors errors={loaded.errors} closing_callback={on_close} />
+                    }}
+                />
+            });
+        }
+
+        let dark_mode = invoke("get_dark_mode", JsValue::NULL).await;
+        dispatch.reduce_mut(|state| state.dark_mode = dark_mode.as_bool().unwrap_or_default());
+        load_themes(&dispatch).await;
+    });
 }
@@ -0,0 +1,530 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{codex_name_error, CodexEntry, CodexField, CodexKind, CodexMention, Project};
+use wasm_bindgen::prelude::wasm_bindgen;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yew::virtual_dom::VNode;
+use yew_icons::{Icon, IconId};
+use yewdux::prelude::*;
+
+use crate::app::modal::Modal;
+use crate::app::sidebar::buttons::Button;
+use crate::app::{invoke, PathArgs, State};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"], js_name = convertFileSrc)]
+    fn convert_file_src(path: &str) -> String;
+}
+
+#[derive(Serialize)]
+struct CodexWriteArgs {
+    path: String,
+    previous: Option<CodexEntry>,
+    entry: CodexEntry,
+}
+
+#[derive(Serialize)]
+struct MentionArgs {
+    project: Project,
+    entry: CodexEntry,
+}
+
+/// Reloads the codex of the project at `path` into the state
+pub fn load_codex(path: PathBuf, dispatch: Dispatch<State>) {
+    spawn_local(async move {
+        let entries_jsvalue = invoke(
+            "list_codex_entries",
+            to_value(&PathArgs {
+                path: path.to_string_lossy().to_string(),
+            })
+            .unwrap(),
+        )
+        .await;
+        let entries: Vec<CodexEntry> = from_value(entries_jsvalue).unwrap_or_default();
+        dispatch.reduce_mut(|x| x.codex = entries);
+    });
+}
+
+pub fn open_codex_editor(
+    modal: &UseStateHandle<VNode>,
+    entry: Option<CodexEntry>,
+    kind: CodexKind,
+) {
+    let on_close = {
+        let modal = modal.clone();
+        Callback::from(move |_| modal.set(html!()))
+    };
+    modal.set(html! {
+        <Modal
+            content={html! { <CodexEditor entry={entry} kind={kind} closing_callback={on_close} /> }}
+        />
+    });
+}
+
+fn draft_input_handler(
+    draft: UseStateHandle<CodexEntry>,
+    update: impl Fn(&mut CodexEntry, String) + 'static,
+) -> Callback<InputEvent> {
+    Callback::from(move |e: InputEvent| {
+        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+            let mut new_draft = (*draft).clone();
+            update(&mut new_draft, input.value());
+            draft.set(new_draft);
+        }
+    })
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CodexListProps {
+    pub modal: UseStateHandle<VNode>,
+}
+
+#[function_component(CodexList)]
+pub fn codex_list(CodexListProps { modal }: &CodexListProps) -> Html {
+    let (state, _) = use_store::<State>();
+
+    html! {
+        <div class="overflow-scroll grow shrink p-2">
+            { CodexKind::ALL
+                .iter()
+                .map(|kind| {
+                    let kind = *kind;
+                    let on_add = {
+                        let modal = modal.clone();
+                        Callback::from(move |_: MouseEvent| open_codex_editor(&modal, None, kind))
+                    };
+                    html! {
+                        <div class="mb-4">
+                            <div class="flex items-center justify-between text-subtext">
+                                <div class="font-bold">{ kind.folder() }</div>
+                                <Button
+                                    callback={on_add}
+                                    icon={IconId::LucidePlus}
+                                    title={format!("Add {kind}")}
+                                    size=1.3
+                                />
+                            </div>
+                            { state
+                                .codex
+                                .iter()
+                                .filter(|entry| entry.kind == kind)
+                                .map(|entry| {
+                                    let on_open = {
+                                        let modal = modal.clone();
+                                        let entry = entry.clone();
+                                        Callback::from(move |_: MouseEvent| {
+                                            open_codex_editor(&modal, Some(entry.clone()), kind);
+                                        })
+                                    };
+                                    html! {
+                                        <button
+                                            class="w-full hover:bg-mantle bg-crust rounded-lg flex items-center p-2 cursor-pointer border-0 text-inherit text-[length:inherit] text-start"
+                                            onclick={on_open}
+                                        >
+                                            { entry.name.clone() }
+                                        </button>
+                                    }
+                                })
+                                .collect::<Html>() }
+                        </div>
+                    }
+                })
+                .collect::<Html>() }
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CodexEditorProps {
+    /// The entry to edit, `None` creates a new entry of `kind`
+    #[prop_or_default]
+    pub entry: Option<CodexEntry>,
+    #[prop_or_default]
+    pub kind: CodexKind,
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+#[function_component(CodexEditor)]
+pub fn codex_editor(
+    CodexEditorProps {
+        entry,
+        kind,
+        closing_callback: on_close,
+    }: &CodexEditorProps,
+) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let draft = use_state(|| {
+        entry.clone().unwrap_or(CodexEntry {
+            kind: *kind,
+            ..CodexEntry::default()
+        })
+    });
+    let aliases = use_state(|| {
+        entry
+            .as_ref()
+            .map(|entry| entry.aliases.join(", "))
+            .unwrap_or_default()
+    });
+    let mentions = use_state(Vec::<CodexMention>::new);
+    let confirm_delete = use_state(|| false);
+
+    let project_path = state
+        .project
+        .as_ref()
+        .map(|project| project.path.clone())
+        .unwrap_or_default();
+
+    {
+        let mentions = mentions.clone();
+        let project = state.project.clone();
+        use_effect_with(entry.clone(), move |entry| {
+            if let (Some(entry), Some(project)) = (entry.clone(), project) {
+                spawn_local(async move {
+                    let mentions_jsvalue = invoke(
+                        "find_codex_mentions",
+                        to_value(&MentionArgs { project, entry }).unwrap(),
+                    )
+                    .await;
+                    mentions.set(from_value(mentions_jsvalue).unwrap_or_default());
+                });
+            }
+        });
+    }
+
+    let error_message = if let Some(error) = codex_name_error(draft.name.trim()) {
+        error
+    } else if state.codex.iter().any(|other| {
+        other.kind == draft.kind && other.name == draft.name && Some(other) != entry.as_ref()
+    }) {
+        "An entry with this name already exists."
+    } else {
+        ""
+    };
+
+    let on_name_input = draft_input_handler(draft.clone(), |draft, value| draft.name = value);
+
+    let on_aliases_input = {
+        let aliases = aliases.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                aliases.set(input.value());
+            }
+        })
+    };
+
+    let on_kind_change = {
+        let draft = draft.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                let index = select.value().parse::<usize>().unwrap_or_default();
+                let mut new_draft = (*draft).clone();
+                new_draft.kind = CodexKind::ALL[index.min(CodexKind::ALL.len() - 1)];
+                draft.set(new_draft);
+            }
+        })
+    };
+
+    let on_notes_input = {
+        let draft = draft.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlTextAreaElement>() {
+                let mut new_draft = (*draft).clone();
+                new_draft.notes = input.value();
+                draft.set(new_draft);
+            }
+        })
+    };
+
+    let on_add_field = {
+        let draft = draft.clone();
+        Callback::from(move |_: MouseEvent| {
+            let mut new_draft = (*draft).clone();
+            new_draft.fields.push(CodexField::default());
+            draft.set(new_draft);
+        })
+    };
+
+    let on_add_image = {
+        let draft = draft.clone();
+        let image_path = entry.as_ref().map(|entry| entry.dir(&project_path));
+        Callback::from(move |_: MouseEvent| {
+            let Some(image_path) = image_path.clone() else {
+                return;
+            };
+            let draft = draft.clone();
+            spawn_local(async move {
+                let image_jsvalue = invoke(
+                    "add_codex_image",
+                    to_value(&PathArgs {
+                        path: image_path.to_string_lossy().to_string(),
+                    })
+                    .unwrap(),
+                )
+                .await;
+                let image: Option<String> = from_value(image_jsvalue).unwrap_or_default();
+                if let Some(image) = image {
+                    let mut new_draft = (*draft).clone();
+                    new_draft.images.push(image);
+                    draft.set(new_draft);
+                }
+            });
+        })
+    };
+
+    let on_save = {
+        let draft = draft.clone();
+        let aliases = aliases.clone();
+        let previous = entry.clone();
+        let project_path = project_path.clone();
+        let dispatch = dispatch.clone();
+        let on_close = on_close.clone();
+        let is_valid = error_message.is_empty();
+        Callback::from(move |_: MouseEvent| {
+            if !is_valid {
+                return;
+            }
+            let mut new_entry = (*draft).clone();
+            new_entry.name = new_entry.name.trim().to_string();
+            new_entry.aliases = aliases
+                .split(',')
+                .map(str::trim)
+                .filter(|alias| !alias.is_empty())
+                .map(String::from)
+                .collect();
+            let previous = previous.clone();
+            let project_path = project_path.clone();
+            let dispatch = dispatch.clone();
+            spawn_local(async move {
+                invoke(
+                    "write_codex_entry",
+                    to_value(&CodexWriteArgs {
+                        path: project_path.to_string_lossy().to_string(),
+                        previous,
+                        entry: new_entry,
+                    })
+                    .unwrap(),
+                )
+                .await;
+                load_codex(project_path, dispatch);
+            });
+            on_close.emit(MouseEvent::new("Dummy").unwrap());
+        })
+    };
+
+    let on_delete = {
+        let entry = entry.clone();
+        let project_path = project_path.clone();
+        let confirm_delete = confirm_delete.clone();
+        let on_close = on_close.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(entry) = entry.clone() else {
+                return;
+            };
+            if !*confirm_delete {
+                confirm_delete.set(true);
+                return;
+            }
+            let project_path = project_path.clone();
+            let dispatch = dispatch.clone();
+            spawn_local(async move {
+                invoke(
+                    "delete_path",
+                    to_value(&PathArgs {
+                        path: entry.dir(&project_path).to_string_lossy().to_string(),
+                    })
+                    .unwrap(),
+                )
+                .await;
+                load_codex(project_path, dispatch);
+            });
+            on_close.emit(MouseEvent::new("Dummy").unwrap());
+        })
+    };
+
+    let fields = draft
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let on_field_name = draft_input_handler(draft.clone(), move |draft, value| {
+                draft.fields[index].name = value;
+            });
+            let on_field_value = draft_input_handler(draft.clone(), move |draft, value| {
+                draft.fields[index].value = value;
+            });
+            let on_remove = {
+                let draft = draft.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let mut new_draft = (*draft).clone();
+                    new_draft.fields.remove(index);
+                    draft.set(new_draft);
+                })
+            };
+            html! {
+                <div class="flex gap-2 my-1 items-center">
+                    <input
+                        class="w-1/3 bg-crust text-text p-2 rounded-lg border-0 font-standard text-base"
+                        placeholder="Field"
+                        value={field.name.clone()}
+                        oninput={on_field_name}
+                    />
+                    <input
+                        class="grow bg-crust text-text p-2 rounded-lg border-0 font-standard text-base"
+                        placeholder="Value"
+                        value={field.value.clone()}
+                        oninput={on_field_value}
+                    />
+                    <Button callback={on_remove} icon={IconId::LucideTrash2} title="Remove Field" size=1.3 />
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    let images = draft
+        .images
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            let src = entry
+                .as_ref()
+                .map(|entry| convert_file_src(&entry.dir(&project_path).join(image).to_string_lossy()))
+                .unwrap_or_default();
+            let on_remove = {
+                let draft = draft.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let mut new_draft = (*draft).clone();
+                    new_draft.images.remove(index);
+                    draft.set(new_draft);
+                })
+            };
+            html! {
+                <div class="relative group/image">
+                    <img src={src} alt={image.clone()} class="w-32 h-32 object-cover" />
+                    <div class="absolute top-1 right-1 hidden group-hover/image:flex">
+                        <Button callback={on_remove} icon={IconId::LucideTrash2} title="Remove Image" size=1.3 />
+                    </div>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    let chapter_title = |chapter: &str| {
+        state
+            .project
+            .as_ref()
+            .map_or_else(|| chapter.to_string(), |project| project.title(chapter))
+    };
+
+    html! {
+        <div class="flex flex-col max-h-[80vh] overflow-auto">
+            <div class="text-xl font-bold">
+                { if entry.is_some() { format!("Edit \"{}\"", draft.name) } else { format!("New {}", draft.kind) } }
+            </div>
+            <br />
+            <div class="flex gap-4">
+                <div class="grow">
+                    <div class="font-semibold">{ "Name:" }</div>
+                    <input
+                        class="w-full bg-crust text-text p-2 my-2 rounded-lg border-0 font-standard text-base"
+                        value={draft.name.clone()}
+                        oninput={on_name_input}
+                    />
+                </div>
+                <div>
+                    <div class="font-semibold">{ "Type:" }</div>
+                    <select
+                        onchange={on_kind_change}
+                        class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary my-2"
+                    >
+                        { CodexKind::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(index, option)| {
+                                html! { <option value={index.to_string()} selected={*option == draft.kind}>{ option.to_string() }</option> }
+                            })
+                            .collect::<Html>() }
+                    </select>
+                </div>
+            </div>
+            <div class="font-semibold">{ "Aliases (comma separated):" }</div>
+            <input
+                class="w-full bg-crust text-text p-2 my-2 rounded-lg border-0 font-standard text-base"
+                value={(*aliases).clone()}
+                oninput={on_aliases_input}
+            />
+            <div class="flex items-center justify-between">
+                <div class="font-semibold">{ "Fields:" }</div>
+                <Button callback={on_add_field} icon={IconId::LucidePlus} title="Add Field" size=1.3 />
+            </div>
+            { fields }
+            <div class="flex items-center justify-between">
+                <div class="font-semibold">{ "Images:" }</div>
+                if entry.is_some() {
+                    <Button callback={on_add_image} icon={IconId::LucidePlus} title="Add Image" size=1.3 />
+                }
+            </div>
+            <div class="flex flex-wrap gap-2">
+                { images }
+                if entry.is_none() {
+                    <div class="text-subtext">{ "Save the entry to add images." }</div>
+                }
+            </div>
+            <div class="font-semibold">{ "Notes:" }</div>
+            <textarea
+                class="h-32 my-2 bg-crust text-text rounded-lg border-0 focus:ring-0 p-2 font-standard resize-y"
+                value={draft.notes.clone()}
+                oninput={on_notes_input}
+            />
+            if entry.is_some() {
+                <div class="font-semibold">{ "Mentioned in:" }</div>
+                <div class="my-2">
+                    if mentions.is_empty() {
+                        <div class="text-subtext">{ "Not mentioned in any chapter." }</div>
+                    }
+                    { mentions
+                        .iter()
+                        .map(|mention| {
+                            html! {
+                                <div class="flex items-center gap-2">
+                                    <Icon icon_id={IconId::LucideFileText} width="1em" height="1em" />
+                                    { format!("{} ({}×)", chapter_title(&mention.chapter), mention.count) }
+                                </div>
+                            }
+                        })
+                        .collect::<Html>() }
+                </div>
+            }
+            <div id="footer" class="flex justify-end w-full pt-8">
+                <div class="text-text underline decoration-primary break-words mr-auto">
+                    { error_message }
+                </div>
+                if entry.is_some() {
+                    <button
+                        onclick={on_delete}
+                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-crust text-text hover:scale-105 border-0"
+                    >
+                        { if *confirm_delete { "Really delete?" } else { "Delete" } }
+                    </button>
+                }
+                <button
+                    onclick={on_save}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                    disabled={!error_message.is_empty()}
+                >
+                    { "Save" }
+                </button>
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,192 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo::events::{EventListener, EventListenerOptions};
+use shared::{command_label, shortcut_of, Keymap, DEFAULT_KEYMAP};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, KeyboardEvent};
+use yew::prelude::*;
+use yew_icons::IconId;
+use yewdux::prelude::*;
+
+use crate::app::sidebar::buttons::Button;
+use crate::app::State;
+
+#[path = "palette.rs"]
+mod palette;
+pub use palette::CommandPalette;
+
+/// Commands that act on the text of the editor. Form fields keep these keys
+/// for their own text.
+const TEXT_COMMANDS: [&str; 6] = ["undo", "redo", "bold", "italic", "underline", "highlight"];
+
+/// Attribute of elements that read shortcuts themselves, like the shortcut editor
+const RECORDING_ATTRIBUTE: &str = "data-recording";
+
+type Commands = HashMap<&'static str, Callback<()>>;
+
+thread_local! {
+    /// The commands of every mounted component, in the order they were mounted
+    static REGISTRY: RefCell<Vec<(usize, Commands)>> = RefCell::default();
+    static NEXT_REGISTRATION: Cell<usize> = Cell::default();
+}
+
+fn in_form_field(target: &Element) -> bool {
+    matches!(target.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+}
+
+/// The shortcut of a key press, Cmd counts as Ctrl
+pub fn shortcut_of_event(e: &KeyboardEvent) -> String {
+    shortcut_of(
+        &e.key(),
+        e.ctrl_key() || e.meta_key(),
+        e.alt_key(),
+        e.shift_key(),
+    )
+}
+
+/// Registers the commands a component can run for as long as it is mounted.
+/// When two components register the same command, the one mounted last runs it.
+#[hook]
+pub fn use_commands(commands: Vec<(&'static str, Callback<()>)>) {
+    let registration = *use_memo((), |()| {
+        NEXT_REGISTRATION.with(|next| {
+            let registration = next.get();
+            next.set(registration + 1);
+            registration
+        })
+    });
+
+    // The registry always holds the callbacks of the latest render
+    let commands: Commands = commands.into_iter().collect();
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        match registry.iter_mut().find(|(id, _)| *id == registration) {
+            Some((_, registered)) => *registered = commands,
+            None => registry.push((registration, commands)),
+        }
+    });
+
+    use_effect_with((), move |()| {
+        move || {
+            REGISTRY.with(|registry| {
+                registry.borrow_mut().retain(|(id, _)| *id != registration);
+            });
+        }
+    });
+}
+
+fn registered(command: &str) -> Option<Callback<()>> {
+    REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|(_, commands)| commands.get(command).cloned())
+    })
+}
+
+/// Runs `command`, returns false when no mounted component offers it
+pub fn run_command(command: &str) -> bool {
+    // The registry is released first, the command may mount or unmount components
+    let Some(callback) = registered(command) else {
+        return false;
+    };
+    callback.emit(());
+    true
+}
+
+/// The commands that can run right now, in the order of the default keymap
+pub fn available_commands() -> Vec<&'static str> {
+    DEFAULT_KEYMAP
+        .iter()
+        .map(|(command, _, _)| *command)
+        .filter(|command| registered(command).is_some())
+        .collect()
+}
+
+/// The default keymap with the shortcuts from the settings
+#[hook]
+pub fn use_keymap() -> Rc<Keymap> {
+    let (state, _dispatch) = use_store::<State>();
+    use_memo(
+        state
+            .settings
+            .as_ref()
+            .map(|settings| settings.keybindings.clone())
+            .unwrap_or_default(),
+        Keymap::new,
+    )
+}
+
+/// Runs the command of every shortcut that is pressed
+#[hook]
+pub fn use_shortcuts() {
+    let keymap = use_keymap();
+
+    use_effect_with(keymap, move |keymap| {
+        let keymap = keymap.clone();
+        let listener = EventListener::new_with_options(
+            &gloo::utils::document(),
+            "keydown",
+            EventListenerOptions::enable_prevent_default(),
+            move |e| {
+                let Some(e) = e.dyn_ref::<KeyboardEvent>() else {
+                    return;
+                };
+                let target = e.target().and_then(|x| x.dyn_into::<Element>().ok());
+                if target
+                    .as_ref()
+                    .is_some_and(|target| target.has_attribute(RECORDING_ATTRIBUTE))
+                {
+                    return;
+                }
+
+                let Some(command) = keymap.command(&shortcut_of_event(e)) else {
+                    return;
+                };
+                if TEXT_COMMANDS.contains(&command) && target.as_ref().is_some_and(in_form_field) {
+                    return;
+                }
+                if run_command(command) {
+                    e.prevent_default();
+                }
+            },
+        );
+        move || drop(listener)
+    });
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CommandButtonProps {
+    pub command: &'static str,
+    pub icon: IconId,
+    #[prop_or(1.5)]
+    pub size: f64,
+}
+
+/// A menubar button that runs `command`, its title shows the shortcut
+#[function_component(CommandButton)]
+pub fn command_button(
+    CommandButtonProps {
+        command,
+        icon,
+        size,
+    }: &CommandButtonProps,
+) -> Html {
+    let keymap = use_keymap();
+
+    let label = command_label(command).unwrap_or(command);
+    let title = match keymap.shortcuts(command).first() {
+        Some(shortcut) => format!("{label} ({shortcut})"),
+        None => label.to_string(),
+    };
+
+    let command = *command;
+    let onclick = Callback::from(move |_: MouseEvent| {
+        run_command(command);
+    });
+
+    html! { <Button callback={onclick} icon={*icon} {title} size={*size} /> }
+}
@@ -0,0 +1,246 @@
+use shared::{command_label, fuzzy_score, SYSTEM_THEME};
+use web_sys::{HtmlElement, HtmlInputElement};
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use super::{available_commands, run_command, use_keymap};
+use crate::app::open_chapter;
+use crate::app::settings::save_settings;
+use crate::app::settings::themes::{apply_settings_theme, theme_names};
+use crate::app::State;
+
+/// Entries shown at once, typing narrows the list down
+const MAX_ENTRIES: usize = 50;
+
+#[derive(Clone, PartialEq)]
+enum Action {
+    Command(&'static str),
+    OpenChapter(usize),
+    SwitchTheme(String),
+}
+
+#[derive(Clone, PartialEq)]
+struct Entry {
+    label: String,
+    /// Shown on the right, like the shortcut of a command
+    detail: String,
+    action: Action,
+}
+
+fn entries(state: &State, keymap: &shared::Keymap) -> Vec<Entry> {
+    let mut entries: Vec<Entry> = available_commands()
+        .into_iter()
+        .filter(|command| *command != "command_palette")
+        .map(|command| Entry {
+            label: command_label(command).unwrap_or(command).to_string(),
+            detail: keymap.shortcuts(command).join(", "),
+            action: Action::Command(command),
+        })
+        .collect();
+
+    if let Some(project) = state.project.as_ref() {
+        entries.extend(
+            project
+                .chapters
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| project.active_chapter != Some(*index))
+                .map(|(index, chapter)| Entry {
+                    label: format!("Open Chapter: {}", project.title(chapter)),
+                    detail: String::new(),
+                    action: Action::OpenChapter(index),
+                }),
+        );
+    }
+
+    let current_theme = state
+        .settings
+        .as_ref()
+        .map(|settings| settings.theme.as_str());
+    entries.extend(
+        theme_names(&state.themes)
+            .into_iter()
+            .chain([SYSTEM_THEME.to_string()])
+            .map(|theme| Entry {
+                label: format!("Switch Theme: {theme}"),
+                detail: if current_theme == Some(theme.as_str()) {
+                    "Current".to_string()
+                } else {
+                    String::new()
+                },
+                action: Action::SwitchTheme(theme),
+            }),
+    );
+
+    entries
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CommandPaletteProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Searches every command, chapter and theme by typing a part of its name
+#[function_component(CommandPalette)]
+pub fn command_palette(
+    CommandPaletteProps {
+        closing_callback: on_close,
+    }: &CommandPaletteProps,
+) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let keymap = use_keymap();
+    let query = use_state(String::new);
+    let selected = use_state(|| 0_usize);
+    let input_ref = use_node_ref();
+    let list_ref = use_node_ref();
+
+    {
+        let input_ref = input_ref.clone();
+        use_effect_with((), move |()| {
+            if let Some(input) = input_ref.cast::<HtmlInputElement>() {
+                let _ = input.focus();
+            }
+        });
+    }
+
+    let mut matches: Vec<(u32, Entry)> = entries(&state, &keymap)
+        .into_iter()
+        .filter_map(|entry| Some((fuzzy_score(&query, &entry.label)?, entry)))
+        .collect();
+    // Stable, so equally good matches keep their order
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    let matches: Vec<Entry> = matches
+        .into_iter()
+        .take(MAX_ENTRIES)
+        .map(|(_, entry)| entry)
+        .collect();
+    let selected_index = (*selected).min(matches.len().saturating_sub(1));
+
+    // The selected entry stays in view while moving through the list with the keyboard
+    {
+        let list_ref = list_ref.clone();
+        use_effect_with(selected_index, move |selected_index| {
+            let entry = list_ref
+                .cast::<HtmlElement>()
+                .and_then(|list| list.children().item(u32::try_from(*selected_index).ok()?));
+            if let Some(entry) = entry {
+                entry.scroll_into_view_with_bool(false);
+            }
+        });
+    }
+
+    let run = {
+        let on_close = on_close.clone();
+        Callback::from(move |entry: Entry| {
+            // Closed first, so commands can open their own modal
+            on_close.emit(MouseEvent::new("Dummy").unwrap());
+            match entry.action {
+                Action::Command(command) => {
+                    run_command(command);
+                }
+                Action::OpenChapter(index) => dispatch.reduce_mut(|x| open_chapter(x, index)),
+                Action::SwitchTheme(theme) => {
+                    dispatch.reduce_mut(|x| {
+                        x.settings.get_or_insert_with(Default::default).theme = theme;
+                    });
+                    let state = dispatch.get();
+                    apply_settings_theme(&state);
+                    save_settings(state.settings.clone().unwrap_or_default());
+                }
+            }
+        })
+    };
+
+    let on_input = {
+        let query = query.clone();
+        let selected = selected.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                query.set(input.value());
+                selected.set(0);
+            }
+        })
+    };
+
+    let on_key_down = {
+        let selected = selected.clone();
+        let on_close = on_close.clone();
+        let run = run.clone();
+        let matches = matches.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "ArrowDown" => {
+                e.prevent_default();
+                selected.set((selected_index + 1).min(matches.len().saturating_sub(1)));
+            }
+            "ArrowUp" => {
+                e.prevent_default();
+                selected.set(selected_index.saturating_sub(1));
+            }
+            "Enter" => {
+                e.prevent_default();
+                if let Some(entry) = matches.get(selected_index) {
+                    run.emit(entry.clone());
+                }
+            }
+            "Escape" => {
+                e.prevent_default();
+                on_close.emit(MouseEvent::new("Dummy").unwrap());
+            }
+            _ => {}
+        })
+    };
+
+    let rows = matches
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let onclick = {
+                let run = run.clone();
+                let entry = entry.clone();
+                Callback::from(move |_: MouseEvent| run.emit(entry.clone()))
+            };
+            let onmousemove = {
+                let selected = selected.clone();
+                Callback::from(move |_: MouseEvent| {
+                    if *selected != index {
+                        selected.set(index);
+                    }
+                })
+            };
+
+            html! {
+                <div
+                    {onclick}
+                    {onmousemove}
+                    class={classes!("flex", "justify-between", "px-2", "py-1", "rounded-lg", "cursor-pointer",
+                        (index == selected_index).then_some("bg-primary text-crust")
+                    )}
+                >
+                    <div class="truncate">{ &entry.label }</div>
+                    <div class="text-sm opacity-70 ml-4 shrink-0">{ &entry.detail }</div>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="flex flex-col w-[32rem] max-w-full">
+            <input
+                ref={input_ref}
+                type="text"
+                value={(*query).clone()}
+                oninput={on_input}
+                onkeydown={on_key_down}
+                placeholder="Type a command, chapter or theme"
+                class="w-full rounded-lg border-0 bg-crust text-text text-lg p-2 outline-none focus:ring-1 focus:ring-primary"
+            />
+            <div ref={list_ref} class="flex flex-col mt-2 max-h-96 overflow-y-auto">
+                if matches.is_empty() {
+                    <div class="px-2 py-1 text-subtext">{ "Nothing found" }</div>
+                } else {
+                    { rows }
+                }
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,401 @@
+use std::path::PathBuf;
+
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{ChapterMeta, Project, WritingStatus};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, open_chapter, ProjectProps, State, View};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Position,
+    Title,
+    Words,
+    Status,
+    Pov,
+}
+
+fn fetch_word_counts(project: Project, word_counts: UseStateHandle<Vec<usize>>) {
+    spawn_local(async move {
+        let counts_jsvalue = invoke(
+            "get_word_counts",
+            to_value(&ProjectProps { project }).unwrap(),
+        )
+        .await;
+        let counts: Vec<usize> = from_value(counts_jsvalue).unwrap_or_default();
+        word_counts.set(counts);
+    });
+}
+
+/// What the word counts depend on, they are only fetched again when it changes.
+/// Changes to the metadata of the chapters leave them as they are.
+fn word_count_key(state: &State) -> Option<(PathBuf, Vec<String>, u32)> {
+    let project = state.project.as_ref()?;
+    Some((project.path.clone(), project.chapters.clone(), state.saves))
+}
+
+fn update_meta(dispatch: &Dispatch<State>, chapter: &str, update: impl FnOnce(&mut ChapterMeta)) {
+    dispatch.reduce_mut(|x| {
+        if let Some(project) = x.project.as_mut() {
+            update(project.metadata.entry(chapter.to_string()).or_default());
+        }
+    });
+}
+
+fn open_in_editor(dispatch: &Dispatch<State>, index: usize) {
+    dispatch.reduce_mut(|x| {
+        open_chapter(x, index);
+        x.view = View::Editor;
+    });
+}
+
+#[derive(Properties, PartialEq)]
+struct StatusSelectProps {
+    pub chapter: String,
+    pub status: WritingStatus,
+}
+
+#[function_component(StatusSelect)]
+fn status_select(StatusSelectProps { chapter, status }: &StatusSelectProps) -> Html {
+    let (_, dispatch) = use_store::<State>();
+
+    let onchange = {
+        let chapter = chapter.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                let index = select.value().parse::<usize>().unwrap_or_default();
+                let new_status = WritingStatus::ALL[index.min(WritingStatus::ALL.len() - 1)];
+                update_meta(&dispatch, &chapter, |meta| meta.status = new_status);
+            }
+        })
+    };
+
+    html! {
+        <select
+            onchange={onchange}
+            class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+        >
+            { WritingStatus::ALL
+                .iter()
+                .enumerate()
+                .map(|(index, option)| {
+                    html! { <option value={index.to_string()} selected={option == status}>{ option.to_string() }</option> }
+                })
+                .collect::<Html>() }
+        </select>
+    }
+}
+
+#[function_component(Corkboard)]
+pub fn corkboard() -> Html {
+    let (state, _) = use_store::<State>();
+    let word_counts = use_state(Vec::<usize>::new);
+
+    {
+        let word_counts = word_counts.clone();
+        let project = state.project.clone();
+        use_effect_with(word_count_key(&state), move |_| {
+            if let Some(project) = project {
+                fetch_word_counts(project, word_counts);
+            }
+        });
+    }
+
+    let Some(project) = state.project.as_ref() else {
+        return html! {};
+    };
+
+    html! {
+        <div class="flex-1 overflow-auto bg-crust px-3">
+            <div class="grid grid-cols-[repeat(auto-fill,minmax(16rem,1fr))] gap-4">
+                { project
+                    .chapters
+                    .iter()
+                    .enumerate()
+                    .map(|(index, chapter)| {
+                        html! {
+                            <ChapterCard
+                                key={chapter.clone()}
+                                chapter={chapter.clone()}
+                                index={index}
+                                meta={project.meta(chapter)}
+                                words={word_counts.get(index).copied().unwrap_or_default()}
+                            />
+                        }
+                    })
+                    .collect::<Html>() }
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ChapterCardProps {
+    pub chapter: String,
+    pub index: usize,
+    pub meta: ChapterMeta,
+    pub words: usize,
+}
+
+#[function_component(ChapterCard)]
+fn chapter_card(
+    ChapterCardProps {
+        chapter,
+        index,
+        meta,
+        words,
+    }: &ChapterCardProps,
+) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let active = use_state(|| false);
+
+    let ondragstart = {
+        let index = *index;
+        let dispatch = dispatch.clone();
+        Callback::from(move |_: DragEvent| {
+            dispatch.reduce_mut(|x| x.dragger = Some(index));
+        })
+    };
+    let ondragend = {
+        let dispatch = dispatch.clone();
+        Callback::from(move |_: DragEvent| {
+            dispatch.reduce_mut(|x| x.dragger = None);
+        })
+    };
+    let ondragover = {
+        let active = active.clone();
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+            active.set(true);
+        })
+    };
+    let ondragleave = {
+        let active = active.clone();
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+            active.set(false);
+        })
+    };
+    let ondrop = {
+        let index = *index;
+        let active = active.clone();
+        let dispatch = dispatch.clone();
+        Callback::from(move |e: DragEvent| {
+            e.prevent_default();
+            dispatch.reduce_mut(|x| {
+                let (Some(project), Some(dragger_index)) = (x.project.as_mut(), x.dragger) else {
+                    return;
+                };
+
+                // The dragged card takes the place of the card it was dropped on
+                let target = if dragger_index < index {
+                    index + 1
+                } else {
+                    index
+                };
+                project.move_chapter(dragger_index, target);
+
+                x.dragger = None;
+            });
+            active.set(false);
+        })
+    };
+
+    let on_synopsis_change = {
+        let chapter = chapter.clone();
+        let dispatch = dispatch.clone();
+        Callback::from(move |e: Event| {
+            if let Some(input) = e.target_dyn_into::<HtmlTextAreaElement>() {
+                let synopsis = input.value();
+                update_meta(&dispatch, &chapter, |meta| meta.synopsis = synopsis);
+            }
+        })
+    };
+
+    let on_open = {
+        let index = *index;
+        Callback::from(move |_: MouseEvent| open_in_editor(&dispatch, index))
+    };
+
+    let is_active = state
+        .project
+        .as_ref()
+        .is_some_and(|project| project.active_chapter == Some(*index));
+    let title = state
+        .project
+        .as_ref()
+        .map_or_else(|| chapter.clone(), |project| project.title(chapter));
+
+    html! {
+        <div
+            class={classes!("bg-base", "rounded-lg", "p-3", "flex", "flex-col", "gap-2", "h-56", "cursor-grab", "border-2", "border-solid",
+                if *active { "border-primary" } else { "border-transparent" }
+            )}
+            draggable="true"
+            ondragstart={ondragstart}
+            ondragend={ondragend}
+            ondragover={ondragover}
+            ondragleave={ondragleave}
+            ondrop={ondrop}
+        >
+            <div class="flex items-center gap-2">
+                <div
+                    class={classes!("w-8", "h-8", "rounded-lg", "text-mantle", "flex", "justify-center", "items-center", "shrink-0",
+                        if is_active { "bg-secondary" } else { "bg-primary" })}
+                >
+                    { index + 1 }
+                </div>
+                <button
+                    class="text-lg font-bold truncate bg-transparent border-0 p-0 text-inherit cursor-pointer hover:underline text-start"
+                    title="Open in editor"
+                    onclick={on_open}
+                >
+                    { title }
+                </button>
+            </div>
+            <textarea
+                class="grow resize-none bg-mantle text-text rounded-lg border-0 focus:ring-0 p-2 font-standard"
+                placeholder="Synopsis"
+                value={meta.synopsis.clone()}
+                onchange={on_synopsis_change}
+            />
+            <div class="flex items-center justify-between text-subtext">
+                <StatusSelect chapter={chapter.clone()} status={meta.status} />
+                <div>{ format!("{words} Words") }</div>
+            </div>
+        </div>
+    }
+}
+
+#[function_component(Outliner)]
+pub fn outliner() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let word_counts = use_state(Vec::<usize>::new);
+    let sorting = use_state(|| (SortColumn::Position, true));
+
+    {
+        let word_counts = word_counts.clone();
+        let project = state.project.clone();
+        use_effect_with(word_count_key(&state), move |_| {
+            if let Some(project) = project {
+                fetch_word_counts(project, word_counts);
+            }
+        });
+    }
+
+    let Some(project) = state.project.as_ref() else {
+        return html! {};
+    };
+
+    let (column, ascending) = *sorting;
+    let mut rows: Vec<(usize, &String, ChapterMeta, usize)> = project
+        .chapters
+        .iter()
+        .enumerate()
+        .map(|(index, chapter)| {
+            let words = word_counts.get(index).copied().unwrap_or_default();
+            (index, chapter, project.meta(chapter), words)
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let ordering = match column {
+            SortColumn::Position => a.0.cmp(&b.0),
+            SortColumn::Title => project
+                .title(a.1)
+                .to_lowercase()
+                .cmp(&project.title(b.1).to_lowercase()),
+            SortColumn::Words => a.3.cmp(&b.3),
+            SortColumn::Status => a.2.status.cmp(&b.2.status),
+            SortColumn::Pov => a.2.pov.to_lowercase().cmp(&b.2.pov.to_lowercase()),
+        };
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+
+    let header = |name: &str, header_column: SortColumn| {
+        let sorting = sorting.clone();
+        let arrow = match (column == header_column, ascending) {
+            (false, _) => "",
+            (true, true) => " ▲",
+            (true, false) => " ▼",
+        };
+        html! {
+            <th
+                class="text-start p-2 cursor-pointer select-none hover:text-primary"
+                onclick={Callback::from(move |_: MouseEvent| {
+                    let (column, ascending) = *sorting;
+                    sorting.set((header_column, column != header_column || !ascending));
+                })}
+            >
+                { format!("{name}{arrow}") }
+            </th>
+        }
+    };
+
+    html! {
+        <div class="flex-1 overflow-auto bg-base rounded-md mx-3">
+            <table class="w-full border-collapse">
+                <thead class="bg-mantle">
+                    <tr>
+                        { header("#", SortColumn::Position) }
+                        { header("Title", SortColumn::Title) }
+                        { header("Words", SortColumn::Words) }
+                        { header("Status", SortColumn::Status) }
+                        { header("POV", SortColumn::Pov) }
+                    </tr>
+                </thead>
+                <tbody>
+                    { rows
+                        .into_iter()
+                        .map(|(index, chapter, meta, words)| {
+                            let on_open = {
+                                let dispatch = dispatch.clone();
+                                Callback::from(move |_: MouseEvent| open_in_editor(&dispatch, index))
+                            };
+                            let on_pov_change = {
+                                let chapter = chapter.clone();
+                                let dispatch = dispatch.clone();
+                                Callback::from(move |e: Event| {
+                                    if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                                        let pov = input.value();
+                                        update_meta(&dispatch, &chapter, |meta| meta.pov = pov);
+                                    }
+                                })
+                            };
+                            html! {
+                                <tr key={chapter.clone()} class="hover:bg-mantle">
+                                    <td class="p-2">{ index + 1 }</td>
+                                    <td class="p-2">
+                                        <button
+                                            class="bg-transparent border-0 p-0 text-inherit text-[length:inherit] cursor-pointer hover:underline"
+                                            onclick={on_open}
+                                        >
+                                            { project.title(chapter) }
+                                        </button>
+                                    </td>
+                                    <td class="p-2">{ words }</td>
+                                    <td class="p-2">
+                                        <StatusSelect chapter={chapter.clone()} status={meta.status} />
+                                    </td>
+                                    <td class="p-2">
+                                        <input
+                                            class="w-full bg-crust text-text p-1 rounded-lg border-0 font-standard text-base"
+                                            value={meta.pov.clone()}
+                                            onchange={on_pov_change}
+                                        />
+                                    </td>
+                                </tr>
+                            }
+                        })
+                        .collect::<Html>() }
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,156 @@
+use std::ops::Range;
+
+use ropey::Rope;
+use shared::{text_checksum, TextEdit};
+
+/// Cursor and selection in byte offsets. `anchor` stays put while `head` moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Selection {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+impl Selection {
+    pub const fn cursor(position: usize) -> Self {
+        Self {
+            anchor: position,
+            head: position,
+        }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+/// The text of the open chapter. Lines are separated by `\n` only.
+#[derive(Default)]
+pub struct TextBuffer {
+    rope: Rope,
+}
+
+impl TextBuffer {
+    pub fn new(text: &str) -> Self {
+        Self {
+            rope: Rope::from_str(&text.replace("\r\n", "\n")),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rope.len_bytes()
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    pub fn line_of(&self, position: usize) -> usize {
+        self.rope.byte_to_line(position.min(self.len()))
+    }
+
+    /// Byte range of `line` without its line break
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.rope.line_to_byte(line);
+        let end = if line + 1 < self.line_count() {
+            self.rope.line_to_byte(line + 1) - 1
+        } else {
+            self.len()
+        };
+        start..end
+    }
+
+    pub fn line(&self, line: usize) -> String {
+        self.slice(self.line_range(line))
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> String {
+        self.rope.byte_slice(range).to_string()
+    }
+
+    pub fn checksum(&self) -> u64 {
+        text_checksum(self.rope.chunks())
+    }
+
+    fn is_boundary(&self, position: usize) -> bool {
+        position <= self.len()
+            && self.rope.char_to_byte(self.rope.byte_to_char(position)) == position
+    }
+
+    /// Offset of the character before `position`
+    pub fn previous_boundary(&self, position: usize) -> usize {
+        let index = self.rope.byte_to_char(position);
+        self.rope.char_to_byte(index.saturating_sub(1))
+    }
+
+    /// Offset of the character after `position`
+    pub fn next_boundary(&self, position: usize) -> usize {
+        let index = self.rope.byte_to_char(position);
+        self.rope
+            .char_to_byte((index + 1).min(self.rope.len_chars()))
+    }
+
+    /// Start of the word before `position`, staying inside its line
+    pub fn word_start(&self, position: usize) -> usize {
+        let line_start = self.line_range(self.line_of(position)).start;
+        let before = self.slice(line_start..position);
+        let trimmed = before.trim_end();
+        let word = trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(index, c)| index + c.len_utf8());
+
+        if word == before.len() {
+            self.previous_boundary(position)
+        } else {
+            line_start + word
+        }
+    }
+
+    /// End of the word after `position`, staying inside its line
+    pub fn word_end(&self, position: usize) -> usize {
+        let line_end = self.line_range(self.line_of(position)).end;
+        let after = self.slice(position..line_end);
+        let skipped = after.len() - after.trim_start().len();
+        let word = after[skipped..]
+            .find(char::is_whitespace)
+            .map_or(after.len(), |index| skipped + index);
+
+        if word == 0 {
+            self.next_boundary(position)
+        } else {
+            position + word
+        }
+    }
+
+    /// Applies `edit` and returns the offset right after the inserted text,
+    /// or `None` if the buffer does not contain the removed text.
+    pub fn apply(&mut self, edit: &TextEdit) -> Option<usize> {
+        let end = edit.position + edit.removed.len();
+        if !self.is_boundary(edit.position)
+            || !self.is_boundary(end)
+            || self.rope.get_byte_slice(edit.position..end)? != edit.removed.as_str()
+        {
+            return None;
+        }
+
+        let start = self.rope.byte_to_char(edit.position);
+        self.rope.remove(start..self.rope.byte_to_char(end));
+        self.rope.insert(start, &edit.inserted);
+
+        Some(edit.position + edit.inserted.len())
+    }
+}
+
+impl std::fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for chunk in self.rope.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
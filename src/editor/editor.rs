@@ -0,0 +1,498 @@
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use gloo::events::{EventListener, EventListenerOptions};
+use gloo::timers::callback::Timeout;
+use shared::{EditHistory, Revision, TextEdit};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlElement, InputEvent};
+use yew::prelude::*;
+
+#[path = "buffer.rs"]
+mod buffer;
+pub use buffer::{Selection, TextBuffer};
+
+#[path = "view.rs"]
+pub mod view;
+
+/// Milliseconds without edits after which the tracked changes catch up with the text
+const TRACKING_DELAY: u32 = 300;
+
+/// The part of the document an update touched
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Change {
+    /// Another document was opened
+    Document,
+    /// The lines `old` were replaced by the lines `new`
+    Lines {
+        old: Range<usize>,
+        new: Range<usize>,
+    },
+    /// Only the tracked changes were edited, the text stayed the same
+    Revision,
+}
+
+/// Applies `edit` to `buffer`, returning the new cursor and the touched lines
+fn apply_edit(buffer: &mut TextBuffer, edit: &TextEdit) -> Option<(usize, Change)> {
+    let start = buffer.line_of(edit.position);
+    let old = start..start + edit.removed.matches('\n').count() + 1;
+    let new = start..start + edit.inserted.matches('\n').count() + 1;
+
+    let cursor = buffer.apply(edit)?;
+    Some((cursor, Change::Lines { old, new }))
+}
+
+/// The open chapter together with the undo histories of all chapters opened this session.
+///
+/// All edits go through here, the contenteditable only shows the buffer.
+#[derive(Default)]
+pub struct Editor {
+    buffer: TextBuffer,
+    selection: Selection,
+    /// Undo histories keyed by chapter folder
+    histories: HashMap<PathBuf, EditHistory>,
+    active: Option<PathBuf>,
+    /// Tracked changes of the open chapter while it is in revision mode
+    revision: Option<Revision>,
+    /// Whether the text changed since the tracked changes were last updated
+    tracking_behind: bool,
+    /// Updates the tracked changes once typing pauses
+    tracking: Option<Timeout>,
+    view: NodeRef,
+    listener: Option<Callback<Change>>,
+}
+
+impl Editor {
+    pub const fn buffer(&self) -> &TextBuffer {
+        &self.buffer
+    }
+
+    pub const fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    pub fn text(&self) -> String {
+        self.buffer.to_string()
+    }
+
+    /// The tracked changes, they may not have caught up with the latest edits yet
+    pub const fn revision(&self) -> Option<&Revision> {
+        self.revision.as_ref()
+    }
+
+    pub const fn is_tracking_behind(&self) -> bool {
+        self.tracking_behind
+    }
+
+    /// The tracked changes with all edits in them
+    pub fn tracked_revision(&mut self) -> Option<&Revision> {
+        self.catch_up();
+        self.revision.as_ref()
+    }
+
+    /// Folder of the open chapter
+    pub fn active(&self) -> Option<&Path> {
+        self.active.as_deref()
+    }
+
+    pub fn is_loaded(&self, path: &Path) -> bool {
+        self.histories.contains_key(path)
+    }
+
+    /// Shows `content` of the chapter folder `path`. `saved` is used if it has no history yet,
+    /// `revision` are the tracked changes of the chapter if it is in revision mode.
+    pub fn open(
+        &mut self,
+        path: PathBuf,
+        content: &str,
+        saved: Option<EditHistory>,
+        revision: Option<Revision>,
+    ) -> Change {
+        let checksum = self.buffer.checksum();
+        if let Some(history) = self.active_history() {
+            history.checksum = Some(checksum);
+        }
+
+        self.buffer = TextBuffer::new(content);
+        self.selection = Selection::default();
+
+        let checksum = self.buffer.checksum();
+        let history = self
+            .histories
+            .entry(path.clone())
+            .or_insert_with(|| saved.unwrap_or_default());
+        // Changed outside of the editor since, the edits don't apply anymore
+        if !history.fits(checksum) {
+            *history = EditHistory::default();
+        }
+        self.active = Some(path);
+
+        self.revision = revision;
+        self.track_changes();
+        self.catch_up();
+
+        Change::Document
+    }
+
+    pub fn edit(&mut self, mut edit: TextEdit) -> Option<Change> {
+        let (cursor, change) = apply_edit(&mut self.buffer, &edit)?;
+        self.selection = Selection::cursor(cursor);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let now = js_sys::Date::now() as u64;
+        edit.time = now;
+        if let Some(history) = self.active_history() {
+            history.record(edit);
+        }
+        self.track_changes();
+
+        Some(change)
+    }
+
+    pub fn replace_selection(&mut self, text: &str) -> Option<Change> {
+        let range = self.selection.range();
+        if range.is_empty() && text.is_empty() {
+            return None;
+        }
+
+        self.edit(TextEdit {
+            position: range.start,
+            removed: self.buffer.slice(range),
+            inserted: text.replace("\r\n", "\n"),
+            time: 0,
+        })
+    }
+
+    /// Deletes the selection, or from the cursor to the offset `target` finds
+    pub fn delete(&mut self, target: impl FnOnce(&TextBuffer, usize) -> usize) -> Option<Change> {
+        if self.selection.is_empty() {
+            self.selection.anchor = target(&self.buffer, self.selection.head);
+        }
+        self.replace_selection("")
+    }
+
+    /// Surrounds the selection with the markdown `style`
+    pub fn wrap_selection(&mut self, style: &str) -> Option<Change> {
+        let selected = self.buffer.slice(self.selection.range());
+        self.replace_selection(&format!("{style}{selected}{style}"))
+    }
+
+    pub fn undo(&mut self) -> Option<Change> {
+        let buffer = &mut self.buffer;
+        let history = self.histories.get_mut(self.active.as_ref()?)?;
+
+        let mut change = None;
+        let cursor = history.undo(|edit| {
+            let (cursor, lines) = apply_edit(buffer, edit)?;
+            change = Some(lines);
+            Some(cursor)
+        })?;
+        self.selection = Selection::cursor(cursor);
+        self.track_changes();
+
+        change
+    }
+
+    pub fn redo(&mut self) -> Option<Change> {
+        let buffer = &mut self.buffer;
+        let history = self.histories.get_mut(self.active.as_ref()?)?;
+
+        let mut change = None;
+        let cursor = history.redo(|edit| {
+            let (cursor, lines) = apply_edit(buffer, edit)?;
+            change = Some(lines);
+            Some(cursor)
+        })?;
+        self.selection = Selection::cursor(cursor);
+        self.track_changes();
+
+        change
+    }
+
+    /// Marks the active history as ending in the current text and returns it for writing to disk
+    pub fn saved(&mut self) -> Option<(PathBuf, EditHistory)> {
+        let path = self.active.clone()?;
+        let checksum = self.buffer.checksum();
+        let history = self.active_history()?;
+        history.checksum = Some(checksum);
+        Some((path, history.clone()))
+    }
+
+    fn active_history(&mut self) -> Option<&mut EditHistory> {
+        self.histories.get_mut(self.active.as_ref()?)
+    }
+
+    /// Notes that the text changed. Diffing the whole text on every key press
+    /// is too slow for long chapters, the tracked changes catch up once typing pauses.
+    fn track_changes(&mut self) {
+        self.tracking_behind = self.revision.is_some();
+    }
+
+    /// Records the text as it is now in the tracked changes
+    fn catch_up(&mut self) {
+        if !self.tracking_behind {
+            return;
+        }
+        self.tracking_behind = false;
+        let text = self.text();
+        if let Some(revision) = self.revision.as_mut() {
+            revision.update(&text);
+        }
+    }
+
+    /// Brings the tracked changes up to date with the text, if they are behind
+    pub fn update_revision(&mut self) -> Option<Change> {
+        if !self.tracking_behind {
+            return None;
+        }
+        self.catch_up();
+        Some(Change::Revision)
+    }
+
+    /// Starts tracking changes against the text as it is now
+    pub fn start_revision(&mut self) -> Option<Change> {
+        if self.revision.is_some() || self.active.is_none() {
+            return None;
+        }
+        self.revision = Some(Revision::new(&self.text()));
+        Some(Change::Revision)
+    }
+
+    /// Stops tracking changes, the text stays as it is
+    pub fn end_revision(&mut self) -> Option<Change> {
+        self.tracking_behind = false;
+        self.revision.take().map(|_| Change::Revision)
+    }
+
+    pub fn next_revision_round(&mut self) -> Option<Change> {
+        self.catch_up();
+        self.revision.as_mut()?.next_round();
+        Some(Change::Revision)
+    }
+
+    /// Accepts the change at span `index` of the revision, or all changes
+    pub fn accept_change(&mut self, index: Option<usize>) -> Option<Change> {
+        self.catch_up();
+        let revision = self.revision.as_mut()?;
+        match index {
+            Some(index) => revision.accept(index),
+            None => revision.accept_all(),
+        }
+        Some(Change::Revision)
+    }
+
+    /// Rejects the change at span `index` of the revision, or all changes.
+    /// Putting the text back is an edit that can be undone.
+    pub fn reject_change(&mut self, index: Option<usize>) -> Option<Change> {
+        self.catch_up();
+        let revision = self.revision.as_mut()?;
+        match index {
+            Some(index) => revision.reject(index),
+            None => revision.reject_all(),
+        }
+
+        let text = revision.text();
+        let change = match TextEdit::between(&self.text(), &text) {
+            Some(edit) => self.edit(edit),
+            None => Some(Change::Revision),
+        };
+        // The text is the one of the revision already
+        self.tracking_behind = false;
+        change
+    }
+
+    /// Moves the cursor to the start of `line` and shows it
+    pub fn go_to_line(&mut self, line: usize) {
+        let line = line.min(self.buffer.line_count().saturating_sub(1));
+        self.selection = Selection::cursor(self.buffer.line_range(line).start);
+
+        if let Some(view) = self.view.cast::<HtmlElement>() {
+            let _ = view.focus();
+            view::select(&view, &self.buffer, self.selection);
+        }
+    }
+
+    /// Takes the selection from the view, returns whether it was inside of it
+    fn read_selection(&mut self) -> bool {
+        let Some(view) = self.view.cast::<Element>() else {
+            return false;
+        };
+        let Some(selection) = view::read_selection(&view, &self.buffer) else {
+            return false;
+        };
+        self.selection = selection;
+        true
+    }
+
+    /// Takes over changes the browser made to the view itself, like composed input
+    fn sync_from_view(&mut self) -> Option<Change> {
+        let view = self.view.cast::<Element>()?;
+        let edit = TextEdit::between(&self.text(), &view::read_text(&view))?;
+        self.edit(edit)
+    }
+
+    fn render(&self, change: &Change) {
+        let Some(view) = self.view.cast::<HtmlElement>() else {
+            return;
+        };
+
+        match change {
+            Change::Document => view::render(&view, &self.buffer),
+            Change::Revision => {}
+            Change::Lines { old, new } => {
+                view::patch(&view, &self.buffer, old.clone(), new.clone());
+                if !view::has_focus(&view) {
+                    let _ = view.focus();
+                }
+                view::select(&view, &self.buffer, self.selection);
+            }
+        }
+    }
+}
+
+/// Shared access to the [`Editor`], compared by identity
+#[derive(Clone, Default)]
+pub struct EditorHandle(Rc<RefCell<Editor>>);
+
+impl PartialEq for EditorHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl EditorHandle {
+    pub fn borrow(&self) -> Ref<'_, Editor> {
+        self.0.borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, Editor> {
+        self.0.borrow_mut()
+    }
+
+    /// The node the contenteditable view has to be bound to
+    pub fn view_ref(&self) -> NodeRef {
+        self.0.borrow().view.clone()
+    }
+
+    /// Sets the callback told about every change after the view was updated
+    pub fn set_listener(&self, listener: Callback<Change>) {
+        self.0.borrow_mut().listener = Some(listener);
+    }
+
+    /// Runs `update` on the editor and brings the view up to date with its change
+    pub fn update(&self, update: impl FnOnce(&mut Editor) -> Option<Change>) {
+        let (change, listener) = {
+            let mut editor = self.0.borrow_mut();
+            let Some(change) = update(&mut editor) else {
+                return;
+            };
+            editor.render(&change);
+            // Every edit starts the wait over
+            if editor.tracking_behind {
+                let handle = Rc::downgrade(&self.0);
+                editor.tracking = Some(Timeout::new(TRACKING_DELAY, move || {
+                    if let Some(editor) = handle.upgrade() {
+                        Self(editor).update(Editor::update_revision);
+                    }
+                }));
+            }
+            (change, editor.listener.clone())
+        };
+
+        if let Some(listener) = listener {
+            listener.emit(change);
+        }
+    }
+
+    /// Turns the input the browser is about to make into an edit of the buffer
+    pub fn before_input(&self, event: &InputEvent) {
+        let input_type = event.input_type();
+        // Composition and spellcheck replacements can't be cancelled, they are synced afterwards
+        if matches!(
+            input_type.as_str(),
+            "insertCompositionText" | "insertReplacementText"
+        ) {
+            return;
+        }
+        event.prevent_default();
+
+        let pasted = event
+            .data_transfer()
+            .and_then(|data| data.get_data("text/plain").ok());
+        let text = event.data().or(pasted).unwrap_or_default();
+
+        self.update(|editor| {
+            editor.read_selection();
+            match input_type.as_str() {
+                "insertText" | "insertFromPaste" | "insertFromDrop" | "insertFromYank" => {
+                    editor.replace_selection(&text)
+                }
+                "insertParagraph" | "insertLineBreak" => editor.replace_selection("\n"),
+                "deleteContentBackward" => editor.delete(TextBuffer::previous_boundary),
+                "deleteContentForward" => editor.delete(TextBuffer::next_boundary),
+                "deleteWordBackward" => editor.delete(TextBuffer::word_start),
+                "deleteWordForward" => editor.delete(TextBuffer::word_end),
+                "deleteSoftLineBackward" | "deleteHardLineBackward" => editor
+                    .delete(|buffer, position| buffer.line_range(buffer.line_of(position)).start),
+                "deleteSoftLineForward" | "deleteHardLineForward" => editor
+                    .delete(|buffer, position| buffer.line_range(buffer.line_of(position)).end),
+                "deleteByCut" | "deleteByDrag" | "deleteContent" => editor.replace_selection(""),
+                "historyUndo" => editor.undo(),
+                "historyRedo" => editor.redo(),
+                "formatBold" => editor.wrap_selection("**"),
+                "formatItalic" => editor.wrap_selection("_"),
+                "formatUnderline" => editor.wrap_selection("__"),
+                _ => None,
+            }
+        });
+    }
+
+    /// Picks up input the browser made without asking first
+    pub fn input(&self) {
+        self.update(Editor::sync_from_view);
+    }
+
+    /// Remembers the selection while it is inside the view, so toolbar buttons can use it.
+    /// Returns whether it was inside.
+    pub fn selection_changed(&self) -> bool {
+        self.0.borrow_mut().read_selection()
+    }
+}
+
+/// Lets the contenteditable view of `editor` report what the user wants to do,
+/// the editor makes the edits. `on_selection` is told when the selection moved inside of it.
+#[hook]
+pub fn use_editor_view(editor: &EditorHandle, on_selection: Callback<()>) {
+    let editor = editor.clone();
+    use_effect_with(editor.view_ref(), move |view_ref| {
+        let view = view_ref.cast::<HtmlElement>().unwrap();
+        let before_input = {
+            let editor = editor.clone();
+            EventListener::new_with_options(
+                &view,
+                "beforeinput",
+                EventListenerOptions::enable_prevent_default(),
+                move |event| {
+                    if let Some(event) = event.dyn_ref::<InputEvent>() {
+                        editor.before_input(event);
+                    }
+                },
+            )
+        };
+        let input = {
+            let editor = editor.clone();
+            EventListener::new(&view, "input", move |_| editor.input())
+        };
+        let selection_change =
+            EventListener::new(&gloo::utils::document(), "selectionchange", move |_| {
+                if editor.selection_changed() {
+                    on_selection.emit(());
+                }
+            });
+
+        move || drop((before_input, input, selection_change))
+    });
+}
@@ -0,0 +1,217 @@
+use std::ops::Range;
+
+use wasm_bindgen::JsCast;
+use web_sys::{window, Element, Node};
+
+use super::buffer::{Selection, TextBuffer};
+
+fn line_element(line: &str) -> Element {
+    let document = window().unwrap().document().unwrap();
+    let element = document.create_element("div").unwrap();
+    if line.is_empty() {
+        // Keeps empty lines from collapsing
+        let _ = element.append_child(&document.create_element("br").unwrap());
+    } else {
+        element.set_text_content(Some(line));
+    }
+    element
+}
+
+/// Replaces the children `old` of `parent` with `elements`
+pub fn replace_children(
+    parent: &Element,
+    old: Range<usize>,
+    elements: impl IntoIterator<Item = Element>,
+) {
+    let children = parent.children();
+    #[allow(clippy::cast_possible_truncation)]
+    let start = old.start as u32;
+    for _ in old {
+        // The collection is live, the next child moves up
+        if let Some(child) = children.item(start) {
+            child.remove();
+        }
+    }
+
+    let next: Option<Node> = children.item(start).map(Into::into);
+    for element in elements {
+        let _ = parent.insert_before(&element, next.as_ref());
+    }
+}
+
+pub fn render(view: &Element, buffer: &TextBuffer) {
+    let count = view.child_element_count() as usize;
+    replace_children(
+        view,
+        0..count,
+        (0..buffer.line_count()).map(|line| line_element(&buffer.line(line))),
+    );
+}
+
+/// Replaces the views of the lines `old` with the current lines `new` of the buffer
+pub fn patch(view: &Element, buffer: &TextBuffer, old: Range<usize>, new: Range<usize>) {
+    replace_children(view, old, new.map(|line| line_element(&buffer.line(line))));
+}
+
+/// The text as shown in the view, for changes the browser made on its own
+pub fn read_text(view: &Element) -> String {
+    let mut lines = Vec::new();
+    let mut child = view.first_element_child();
+    while let Some(line) = child {
+        lines.push(line.text_content().unwrap_or_default());
+        child = line.next_element_sibling();
+    }
+    lines.join("\n")
+}
+
+/// Index of the child of `parent` that is or contains `node`
+pub fn child_index(parent: &Element, node: &Node) -> Option<usize> {
+    let mut child = node.clone();
+    while !parent.is_same_node(child.parent_node().as_ref()) {
+        child = child.parent_node()?;
+    }
+
+    let children = parent.children();
+    let (mut low, mut high) = (0, children.length());
+    while low < high {
+        let middle = (low + high) / 2;
+        let current = children.item(middle)?;
+        if current.is_same_node(Some(&child)) {
+            return Some(middle as usize);
+        }
+        if current.compare_document_position(&child) & Node::DOCUMENT_POSITION_FOLLOWING != 0 {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    None
+}
+
+/// Byte offset in the buffer of a DOM position inside `view`
+fn buffer_position(view: &Element, buffer: &TextBuffer, node: &Node, offset: u32) -> Option<usize> {
+    if view.is_same_node(Some(node)) {
+        let line = offset as usize;
+        return Some(if line < buffer.line_count() {
+            buffer.line_range(line).start
+        } else {
+            buffer.len()
+        });
+    }
+
+    let index = child_index(view, node)?;
+    let range = buffer.line_range(index);
+    #[allow(clippy::cast_possible_truncation)]
+    let line = view.children().item(index as u32)?;
+
+    // Everything between the start of the line and the position
+    let document = window().unwrap().document().unwrap();
+    let prefix = document.create_range().ok()?;
+    prefix.set_start(&line, 0).ok()?;
+    prefix.set_end(node, offset).ok()?;
+    let before: String = prefix.to_string().into();
+
+    Some((range.start + before.len()).min(range.end))
+}
+
+fn dom_position(view: &Element, buffer: &TextBuffer, position: usize) -> Option<(Node, u32)> {
+    let line = buffer.line_of(position);
+    let range = buffer.line_range(line);
+    #[allow(clippy::cast_possible_truncation)]
+    let element = view.children().item(line as u32)?;
+
+    match element.first_child() {
+        Some(text) if text.node_type() == Node::TEXT_NODE => {
+            let before = buffer.slice(range.start..position.min(range.end));
+            #[allow(clippy::cast_possible_truncation)]
+            let offset = before.encode_utf16().count() as u32;
+            Some((text, offset))
+        }
+        _ => Some((element.into(), 0)),
+    }
+}
+
+/// The selection of the document if it is inside `view`
+pub fn read_selection(view: &Element, buffer: &TextBuffer) -> Option<Selection> {
+    let selection = window().unwrap().get_selection().ok()??;
+    let anchor_node = selection.anchor_node()?;
+    let focus_node = selection.focus_node()?;
+    if !view.contains(Some(&anchor_node)) || !view.contains(Some(&focus_node)) {
+        return None;
+    }
+
+    Some(Selection {
+        anchor: buffer_position(view, buffer, &anchor_node, selection.anchor_offset())?,
+        head: buffer_position(view, buffer, &focus_node, selection.focus_offset())?,
+    })
+}
+
+pub fn select(view: &Element, buffer: &TextBuffer, selection: Selection) {
+    let (Some((anchor_node, anchor_offset)), Some((focus_node, focus_offset))) = (
+        dom_position(view, buffer, selection.anchor),
+        dom_position(view, buffer, selection.head),
+    ) else {
+        return;
+    };
+
+    if let Ok(Some(dom_selection)) = window().unwrap().get_selection() {
+        let _ = dom_selection.set_base_and_extent(
+            &anchor_node,
+            anchor_offset,
+            &focus_node,
+            focus_offset,
+        );
+    }
+
+    reveal(view, buffer.line_of(selection.head));
+}
+
+/// Scrolls `line` into view if it is outside of it
+fn reveal(view: &Element, line: usize) {
+    #[allow(clippy::cast_possible_truncation)]
+    let Some(element) = view.children().item(line as u32) else {
+        return;
+    };
+    let line_rect = element.get_bounding_client_rect();
+    let view_rect = view.get_bounding_client_rect();
+
+    if line_rect.bottom() > view_rect.bottom() {
+        element.scroll_into_view_with_bool(false);
+    } else if line_rect.top() < view_rect.top() {
+        element.scroll_into_view_with_bool(true);
+    }
+}
+
+/// The first line at the top of the scrolled `view`, with how much of it is scrolled past
+pub fn top_line(view: &Element) -> Option<(usize, f64)> {
+    let top = view.get_bounding_client_rect().top();
+    let children = view.children();
+
+    // First line that ends below the top
+    let (mut low, mut high) = (0, children.length());
+    while low < high {
+        let middle = (low + high) / 2;
+        if children.item(middle)?.get_bounding_client_rect().bottom() <= top {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+
+    let rect = children.item(low)?.get_bounding_client_rect();
+    let scrolled = if rect.height() > 0.0 {
+        ((top - rect.top()) / rect.height()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    Some((low as usize, scrolled))
+}
+
+pub fn has_focus(view: &Element) -> bool {
+    window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .active_element()
+        .is_some_and(|active| view.is_same_node(Some(active.unchecked_ref())))
+}
@@ -0,0 +1,354 @@
+use std::path::PathBuf;
+
+use gloo::events::EventListener;
+use gloo::timers::callback::Interval;
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::ProjectLock;
+use web_sys::HtmlInputElement;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, PathArgs, State};
+
+/// Milliseconds between checks whether the project has been idle long enough to lock it
+const IDLE_CHECK: u32 = 15_000;
+
+#[derive(Serialize)]
+struct PassphraseArgs {
+    path: String,
+    passphrase: String,
+}
+
+/// The encryption state of the open project, `None` until it was checked
+pub fn project_lock(state: &State) -> Option<ProjectLock> {
+    let project = state.project.as_ref()?;
+    state
+        .lock
+        .as_ref()
+        .filter(|(path, _)| *path == project.path)
+        .map(|(_, lock)| *lock)
+}
+
+/// Whether the chapters and notes of the open project can be read and written
+pub fn is_readable(state: &State) -> bool {
+    matches!(
+        project_lock(state),
+        Some(ProjectLock::Unencrypted | ProjectLock::Unlocked)
+    )
+}
+
+async fn update_lock(path: PathBuf, dispatch: &Dispatch<State>) {
+    let args = PathArgs {
+        path: path.to_string_lossy().to_string(),
+    };
+    let lock_jsvalue = invoke("encryption_status", to_value(&args).unwrap()).await;
+    let lock: ProjectLock = from_value(lock_jsvalue).unwrap_or_default();
+    dispatch.reduce_mut(|state| state.lock = Some((path, lock)));
+}
+
+/// Forgets the key of the open project, the lock screen shows until the passphrase is entered again
+pub async fn lock_project(dispatch: &Dispatch<State>) {
+    let Some(path) = dispatch.get().project.as_ref().map(|x| x.path.clone()) else {
+        return;
+    };
+    let args = PathArgs {
+        path: path.to_string_lossy().to_string(),
+    };
+    invoke("lock_project", to_value(&args).unwrap()).await;
+    update_lock(path, dispatch).await;
+}
+
+/// Finds out whether the open project is encrypted, and runs `lock` once it
+/// was unlocked and there was no input for `minutes`. 0 never locks it.
+#[hook]
+pub fn use_project_lock(minutes: u32, lock: Callback<()>) {
+    let (state, dispatch) = use_store::<State>();
+    let last_input = use_mut_ref(js_sys::Date::now);
+
+    let project_path = state.project.as_ref().map(|x| x.path.clone());
+    use_effect_with(project_path, move |path| {
+        if let Some(path) = path.clone() {
+            spawn_local(async move { update_lock(path, &dispatch).await });
+        }
+    });
+
+    {
+        let last_input = last_input.clone();
+        use_effect_with((), move |()| {
+            let document = gloo::utils::document();
+            let listeners: Vec<EventListener> = ["keydown", "mousedown", "mousemove", "wheel"]
+                .into_iter()
+                .map(|event| {
+                    let last_input = last_input.clone();
+                    EventListener::new(&document, event, move |_| {
+                        *last_input.borrow_mut() = js_sys::Date::now();
+                    })
+                })
+                .collect();
+            move || drop(listeners)
+        });
+    }
+
+    let unlocked = project_lock(&state) == Some(ProjectLock::Unlocked);
+    use_effect_with((minutes, unlocked), move |&(minutes, unlocked)| {
+        // The time only counts from when the project was unlocked
+        *last_input.borrow_mut() = js_sys::Date::now();
+        let interval = (unlocked && minutes > 0).then(|| {
+            Interval::new(IDLE_CHECK, move || {
+                let idle = js_sys::Date::now() - *last_input.borrow();
+                if idle >= f64::from(minutes) * 60_000.0 {
+                    lock.emit(());
+                }
+            })
+        });
+        move || drop(interval)
+    });
+}
+
+/// Covers the window while the open project is locked
+#[function_component(LockScreen)]
+pub fn lock_screen() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let passphrase_ref = use_node_ref();
+    let error = use_state(String::new);
+
+    {
+        let passphrase_ref = passphrase_ref.clone();
+        use_effect_with((), move |()| {
+            if let Some(input) = passphrase_ref.cast::<HtmlInputElement>() {
+                let _ = input.focus();
+            }
+        });
+    }
+
+    let on_unlock = {
+        let dispatch = dispatch.clone();
+        let passphrase_ref = passphrase_ref.clone();
+        let error = error.clone();
+        Callback::from(move |e: SubmitEvent| {
+            e.prevent_default();
+            let (Some(project), Some(input)) = (
+                dispatch.get().project.clone(),
+                passphrase_ref.cast::<HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            let args = PassphraseArgs {
+                path: project.path.to_string_lossy().to_string(),
+                passphrase: input.value(),
+            };
+            input.set_value("");
+            let dispatch = dispatch.clone();
+            let error = error.clone();
+            spawn_local(async move {
+                let unlocked = invoke("unlock_project", to_value(&args).unwrap()).await;
+                if unlocked.as_bool().unwrap_or_default() {
+                    update_lock(project.path, &dispatch).await;
+                } else {
+                    error.set("Wrong passphrase.".to_string());
+                }
+            });
+        })
+    };
+
+    let on_close_project = Callback::from(move |_: MouseEvent| {
+        dispatch.reduce_mut(|state| state.project = None);
+    });
+
+    let name = state
+        .project
+        .as_ref()
+        .and_then(|x| x.path.file_name())
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    html! {
+        <div
+            class="absolute top-0 left-0 z-[60] bg-base h-full w-full flex items-center justify-center text-text"
+        >
+            <form onsubmit={on_unlock} class="flex flex-col w-96">
+                <div class="text-xl font-bold">{ format!("\"{name}\" is locked") }</div>
+                <div class="text-sm pt-2">
+                    { "The chapters and notes of this project are encrypted. Enter the passphrase to keep writing." }
+                </div>
+                <input
+                    ref={passphrase_ref}
+                    type="password"
+                    placeholder="Passphrase"
+                    class="mt-8 bg-crust text-text p-2 rounded-lg border-0 font-standard text-base"
+                />
+                <div class="pt-2 text-sm text-subtext">{ (*error).clone() }</div>
+                <div class="flex justify-end w-full pt-8">
+                    <button
+                        type="submit"
+                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                    >
+                        { "Unlock" }
+                    </button>
+                    <button
+                        type="button"
+                        onclick={on_close_project}
+                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                    >
+                        { "Close Project" }
+                    </button>
+                </div>
+            </form>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EncryptionProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Turns the encryption of the chapters and notes of the open project on and off
+#[function_component(EncryptionMenu)]
+pub fn encryption_menu(
+    EncryptionProps {
+        closing_callback: on_close,
+    }: &EncryptionProps,
+) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let passphrase_ref = use_node_ref();
+    let repeat_ref = use_node_ref();
+    let message = use_state(String::new);
+    let lock = project_lock(&state);
+
+    // Runs `command` on the open project and shows what went wrong, if anything did
+    let run = {
+        let dispatch = dispatch.clone();
+        let message = message.clone();
+        let project = state.project.clone();
+        move |command: &'static str, passphrase: Option<String>, done: &'static str| {
+            let Some(project) = project.clone() else {
+                return;
+            };
+            let dispatch = dispatch.clone();
+            let message = message.clone();
+            let args = PassphraseArgs {
+                path: project.path.to_string_lossy().to_string(),
+                passphrase: passphrase.unwrap_or_default(),
+            };
+            message.set("Working...".to_string());
+            spawn_local(async move {
+                let error = invoke(command, to_value(&args).unwrap())
+                    .await
+                    .as_string()
+                    .unwrap_or_default();
+                update_lock(project.path, &dispatch).await;
+                message.set(if error.is_empty() {
+                    done.to_string()
+                } else {
+                    error
+                });
+            });
+        }
+    };
+
+    let on_encrypt = {
+        let passphrase_ref = passphrase_ref.clone();
+        let repeat_ref = repeat_ref.clone();
+        let message = message.clone();
+        let run = run.clone();
+        Callback::from(move |_: MouseEvent| {
+            let (Some(passphrase), Some(repeat)) = (
+                passphrase_ref.cast::<HtmlInputElement>(),
+                repeat_ref.cast::<HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            if passphrase.value().is_empty() {
+                message.set("Please enter a passphrase.".to_string());
+            } else if passphrase.value() != repeat.value() {
+                message.set("The passphrases don't match.".to_string());
+            } else {
+                run(
+                    "encrypt_project",
+                    Some(passphrase.value()),
+                    "The chapters and notes are encrypted.",
+                );
+            }
+        })
+    };
+
+    let on_decrypt = Callback::from(move |_: MouseEvent| {
+        run(
+            "decrypt_project",
+            None,
+            "The chapters and notes are no longer encrypted.",
+        );
+    });
+
+    let body = if lock == Some(ProjectLock::Unlocked) {
+        html! {
+            <div class="text-sm pt-2">
+                { "The chapters and notes of this project are encrypted. The project locks itself when you don't write for a while, see the settings." }
+            </div>
+        }
+    } else {
+        html! {
+            <>
+                <div class="text-sm pt-2">
+                    { "Encrypts the chapters and notes of this project with a passphrase. Without it they can't be read, not even by PaperSmith. Keep it somewhere safe, a forgotten passphrase can't be recovered." }
+                </div>
+                <div class="flex w-full pt-8 justify-between">
+                    <label class="font-bold self-center" for="encryption-passphrase">
+                        { "Passphrase" }
+                    </label>
+                    <input
+                        id="encryption-passphrase"
+                        type="password"
+                        ref={passphrase_ref}
+                        class="bg-crust text-text p-2 rounded-lg border-0 font-standard text-base"
+                    />
+                </div>
+                <div class="flex w-full pt-4 justify-between">
+                    <label class="font-bold self-center" for="encryption-repeat">
+                        { "Repeat Passphrase" }
+                    </label>
+                    <input
+                        id="encryption-repeat"
+                        type="password"
+                        ref={repeat_ref}
+                        class="bg-crust text-text p-2 rounded-lg border-0 font-standard text-base"
+                    />
+                </div>
+            </>
+        }
+    };
+
+    html! {
+        <>
+            <div class="text-xl font-bold">{ "Project Encryption" }</div>
+            { body }
+            <div class="pt-4 text-sm text-subtext">{ (*message).clone() }</div>
+            <div class="flex justify-end w-full pt-8">
+                if lock == Some(ProjectLock::Unlocked) {
+                    <button
+                        onclick={on_decrypt}
+                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                    >
+                        { "Remove Encryption" }
+                    </button>
+                } else if lock == Some(ProjectLock::Unencrypted) {
+                    <button
+                        onclick={on_encrypt}
+                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                    >
+                        { "Encrypt" }
+                    </button>
+                }
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </>
+    }
+}
@@ -0,0 +1,99 @@
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use shared::Project;
+use web_sys::HtmlInputElement;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, State};
+
+#[derive(Serialize)]
+struct PackArgs {
+    project: Project,
+    backups: bool,
+    statistics: bool,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PackProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Packs the project into a single `.papersmith` file for sharing or moving it
+#[function_component(PackMenu)]
+pub fn pack_menu(
+    PackProps {
+        closing_callback: on_close,
+    }: &PackProps,
+) -> Html {
+    let (state, _dispatch) = use_store::<State>();
+    let backups_ref = use_node_ref();
+    let statistics_ref = use_node_ref();
+    let message = use_state(String::new);
+
+    let on_pack = {
+        let backups_ref = backups_ref.clone();
+        let statistics_ref = statistics_ref.clone();
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| {
+            let (Some(project), Some(backups), Some(statistics)) = (
+                state.project.clone(),
+                backups_ref.cast::<HtmlInputElement>(),
+                statistics_ref.cast::<HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            let args = PackArgs {
+                project,
+                backups: backups.checked(),
+                statistics: statistics.checked(),
+            };
+            let message = message.clone();
+
+            spawn_local(async move {
+                let path = invoke("pack_project", to_value(&args).unwrap()).await;
+                message.set(path.as_string().map_or_else(
+                    || "The project was not packed.".to_string(),
+                    |path| format!("Packed to {path}"),
+                ));
+            });
+        })
+    };
+
+    html!(
+        <>
+            <div class="text-xl font-bold">{ "Pack Project" }</div>
+            <div class="text-sm pt-2">
+                { "Puts the chapters, notes, codex and extras into one file that can be sent or moved to another computer. Opening it unpacks the project next to it." }
+            </div>
+            <div class="flex w-full pt-8 justify-between">
+                <label class="font-bold self-center" for="pack-backups">
+                    { "Include Backups" }
+                </label>
+                <input id="pack-backups" type="checkbox" ref={backups_ref} />
+            </div>
+            <div class="flex w-full pt-8 justify-between">
+                <label class="font-bold self-center" for="pack-statistics">
+                    { "Include Writing Statistics" }
+                </label>
+                <input id="pack-statistics" type="checkbox" ref={statistics_ref} />
+            </div>
+            <div class="pt-4 text-sm text-subtext">{ (*message).clone() }</div>
+            <div class="flex justify-end w-full pt-8">
+                <button
+                    onclick={on_pack}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Pack" }
+                </button>
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </>
+    )
+}
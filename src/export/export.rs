@@ -0,0 +1,161 @@
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use shared::{ExportFormat, Project};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::notepad::markdown::render_document;
+use crate::app::{invoke, FileWriteData, State};
+
+#[path = "archive.rs"]
+mod archive;
+pub use archive::PackMenu;
+
+#[derive(Serialize)]
+struct CompileArgs {
+    project: Project,
+    comments: bool,
+}
+
+const EXPORT_STYLE: &str = "body { max-width: 40em; margin: 2em auto; font-family: serif; line-height: 1.5; }
+mark.annotation { background: #fff3a0; }
+.comment { font-size: 0.8em; color: #555; margin: 0 0.3em; padding: 0 0.3em; border-left: 2px solid #999; }
+[data-footnote-definition] { display: flex; gap: 0.3em; font-size: 0.9em; }";
+
+fn html_document(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{EXPORT_STYLE}\n</style>\n</head>\n<body>\n{body}\n</body>\n</html>\n"
+    )
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ExportProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Exports the whole project into its `Exports/` folder
+#[function_component(ExportMenu)]
+pub fn export_menu(
+    ExportProps {
+        closing_callback: on_close,
+    }: &ExportProps,
+) -> Html {
+    let (state, _dispatch) = use_store::<State>();
+    let format_ref = use_node_ref();
+    let comments_ref = use_node_ref();
+    let message = use_state(String::new);
+    let default_format = state
+        .settings
+        .as_ref()
+        .map(|settings| settings.export_format)
+        .unwrap_or_default();
+
+    let on_export = {
+        let format_ref = format_ref.clone();
+        let comments_ref = comments_ref.clone();
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| {
+            let (Some(project), Some(format), Some(comments)) = (
+                state.project.clone(),
+                format_ref.cast::<HtmlSelectElement>(),
+                comments_ref.cast::<HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            let format = format.value();
+            let comments = comments.checked();
+            let message = message.clone();
+
+            spawn_local(async move {
+                if format == "docx" {
+                    let args = CompileArgs { project, comments };
+                    let path = invoke("export_docx", to_value(&args).unwrap()).await;
+                    message.set(path.as_string().map_or_else(
+                        || "The export failed.".to_string(),
+                        |path| format!("Exported to {path}"),
+                    ));
+                    return;
+                }
+
+                let title = project
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let mut path = project.path.clone();
+                path.push("Exports");
+                path.push(&title);
+                path.set_extension(&format);
+
+                let manuscript = invoke(
+                    "compile_manuscript",
+                    to_value(&CompileArgs { project, comments }).unwrap(),
+                )
+                .await
+                .as_string()
+                .unwrap_or_default();
+                let content = if format == "html" {
+                    html_document(&title, &render_document(&manuscript))
+                } else {
+                    manuscript
+                };
+
+                let write_data = FileWriteData {
+                    path: path.to_string_lossy().to_string(),
+                    content,
+                };
+                invoke("write_to_file", to_value(&write_data).unwrap()).await;
+
+                message.set(format!("Exported to {}", path.to_string_lossy()));
+            });
+        })
+    };
+
+    html!(
+        <>
+            <div class="text-xl font-bold">{ "Export" }</div>
+            <div class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Format" }</div>
+                <select
+                    ref={format_ref}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { ExportFormat::ALL
+                        .iter()
+                        .map(|format| html! {
+                            <option
+                                value={format.extension()}
+                                selected={*format == default_format}
+                            >
+                                { format.label() }
+                            </option>
+                        })
+                        .collect::<Html>() }
+                </select>
+            </div>
+            <div class="flex w-full pt-8 justify-between">
+                <label class="font-bold self-center" for="export-comments">
+                    { "Include Comments" }
+                </label>
+                <input id="export-comments" type="checkbox" ref={comments_ref} />
+            </div>
+            <div class="pt-4 text-sm text-subtext">{ (*message).clone() }</div>
+            <div class="flex justify-end w-full pt-8">
+                <button
+                    onclick={on_export}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Export" }
+                </button>
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </>
+    )
+}
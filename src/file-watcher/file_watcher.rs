@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::Project;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsValue;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::editor::EditorHandle;
+use crate::app::encryption::is_readable;
+use crate::app::modal::Modal;
+use crate::app::{invoke, listen, PathArgs, State};
+
+#[derive(Serialize)]
+struct WatchArgs {
+    path: Option<String>,
+}
+
+fn payload(event: &JsValue) -> JsValue {
+    js_sys::Reflect::get(event, &"payload".into()).unwrap_or(JsValue::NULL)
+}
+
+/// Keeps the open project in sync with its files when they are changed outside
+/// of PaperSmith, like by a sync tool or another editor
+#[hook]
+pub fn use_file_watcher(editor: EditorHandle, modal: UseStateHandle<Html>) {
+    let (state, dispatch) = use_store::<State>();
+
+    let project_path = state
+        .project
+        .as_ref()
+        .map(|project| project.path.to_string_lossy().to_string());
+    use_effect_with(project_path, move |path| {
+        let args = WatchArgs { path: path.clone() };
+        spawn_local(async move {
+            invoke("watch_project", to_value(&args).unwrap()).await;
+        });
+    });
+
+    use_effect_with((), move |()| {
+        spawn_local(async move {
+            let on_chapter_change = {
+                let dispatch = dispatch.clone();
+                Closure::<dyn FnMut(JsValue)>::new(move |event| {
+                    if let Some(chapter) = payload(&event).as_string() {
+                        chapter_changed(&chapter, &dispatch, &editor, &modal);
+                    }
+                })
+            };
+            listen("chapter-changed", &on_chapter_change).await;
+            on_chapter_change.forget();
+
+            let on_note_change = {
+                let dispatch = dispatch.clone();
+                Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                    dispatch.reduce_mut(|state| state.note_reloads += 1);
+                })
+            };
+            listen("note-changed", &on_note_change).await;
+            on_note_change.forget();
+
+            let on_project_change = Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                project_changed(&dispatch);
+            });
+            listen("project-changed", &on_project_change).await;
+            on_project_change.forget();
+        });
+    });
+}
+
+/// Loads the chapter again if it is the open one. With unsaved changes the
+/// user decides which version to keep.
+fn chapter_changed(
+    chapter: &str,
+    dispatch: &Dispatch<State>,
+    editor: &EditorHandle,
+    modal: &UseStateHandle<Html>,
+) {
+    let state = dispatch.get();
+    let Some(project) = state.project.as_ref() else {
+        return;
+    };
+    // Locked chapters are loaded again once the project is unlocked
+    if !is_readable(&state) {
+        return;
+    }
+    let active = project
+        .active_chapter
+        .and_then(|index| project.chapters.get(index));
+    if active.map(String::as_str) != Some(chapter) {
+        return;
+    }
+    let chapter_path = project.path.join("Chapters").join(chapter);
+    let title = project.title(chapter);
+    let changes = state.changes;
+
+    let dispatch = dispatch.clone();
+    let editor = editor.clone();
+    let modal = modal.clone();
+    spawn_local(async move {
+        let content = invoke(
+            "get_file_content",
+            to_value(&PathArgs {
+                path: chapter_path
+                    .join("Content.md")
+                    .to_string_lossy()
+                    .to_string(),
+            })
+            .unwrap(),
+        )
+        .await
+        .as_string()
+        .unwrap_or_default();
+        if editor.borrow().text() == content {
+            return;
+        }
+
+        if changes {
+            modal.set(html! {
+                <Modal
+                    content={html! {
+                        <ConflictPrompt
+                            {chapter_path}
+                            {title}
+                            {content}
+                            {editor}
+                            closing_callback={
+                                let modal = modal.clone();
+                                Callback::from(move |()| modal.set(html!()))
+                            }
+                        />
+                    }}
+                />
+            });
+        } else {
+            reload_chapter(chapter_path, &content, &editor, &dispatch);
+        }
+    });
+}
+
+/// Shows `content` in the editor as the saved text of the chapter
+pub fn reload_chapter(
+    chapter_path: PathBuf,
+    content: &str,
+    editor: &EditorHandle,
+    dispatch: &Dispatch<State>,
+) {
+    editor.update(|editor| {
+        let revision = editor.tracked_revision().cloned();
+        Some(editor.open(chapter_path, content, None, revision))
+    });
+    dispatch.reduce_mut(|state| state.changes = false);
+}
+
+/// Reads the project file and the chapter folders again, the open chapter stays open
+fn project_changed(dispatch: &Dispatch<State>) {
+    let Some(project) = dispatch.get().project.clone() else {
+        return;
+    };
+    let dispatch = dispatch.clone();
+    spawn_local(async move {
+        let project_jsvalue = invoke(
+            "reload_project",
+            to_value(&PathArgs {
+                path: project.path.to_string_lossy().to_string(),
+            })
+            .unwrap(),
+        )
+        .await;
+        let Ok(Some(mut reloaded)) = from_value::<Option<Project>>(project_jsvalue) else {
+            return;
+        };
+        reloaded.path.clone_from(&project.path);
+
+        let active = project
+            .active_chapter
+            .and_then(|index| project.chapters.get(index));
+        if let Some(index) =
+            active.and_then(|active| reloaded.chapters.iter().position(|x| x == active))
+        {
+            reloaded.active_chapter = Some(index);
+        }
+        if dispatch.get().project.as_ref() != Some(&reloaded) {
+            dispatch.reduce_mut(|state| state.project = Some(reloaded));
+        }
+    });
+}
+
+#[derive(Properties, PartialEq)]
+struct ConflictPromptProps {
+    chapter_path: PathBuf,
+    title: String,
+    /// The chapter as it is on disk now
+    content: String,
+    editor: EditorHandle,
+    closing_callback: Callback<()>,
+}
+
+/// Asks whether to take the version on disk or to keep the unsaved changes
+#[function_component(ConflictPrompt)]
+fn conflict_prompt(
+    ConflictPromptProps {
+        chapter_path,
+        title,
+        content,
+        editor,
+        closing_callback,
+    }: &ConflictPromptProps,
+) -> Html {
+    let (_state, dispatch) = use_store::<State>();
+
+    let on_reload = {
+        let chapter_path = chapter_path.clone();
+        let content = content.clone();
+        let editor = editor.clone();
+        let closing_callback = closing_callback.clone();
+        Callback::from(move |_: MouseEvent| {
+            reload_chapter(chapter_path.clone(), &content, &editor, &dispatch);
+            closing_callback.emit(());
+        })
+    };
+    let on_keep = closing_callback.reform(|_: MouseEvent| ());
+
+    html! {
+        <>
+            <div class="text-xl font-bold">
+                { format!("\"{title}\" was changed outside of PaperSmith") }
+            </div>
+            <br />
+            <div>
+                { "The chapter has unsaved changes. Reloading it replaces them with the version on disk, keeping them overwrites that version on the next save." }
+            </div>
+            <div class="flex justify-end w-full pt-8">
+                <button
+                    onclick={on_reload}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Reload from Disk" }
+                </button>
+                <button
+                    onclick={on_keep}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Keep My Version" }
+                </button>
+            </div>
+        </>
+    }
+}
@@ -0,0 +1,258 @@
+use std::cell::Cell;
+
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{LogEntry, LogLevel, BACKEND, FRONTEND};
+use wasm_bindgen::prelude::wasm_bindgen;
+use wasm_bindgen::JsValue;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+
+use crate::app::{invoke, PathArgs};
+
+#[wasm_bindgen]
+extern "C" {
+    /// Like `invoke`, but called right away. Messages of a panic are still sent,
+    /// the futures of `invoke` would never run after it.
+    #[wasm_bindgen(js_namespace = ["window", "__TAURI__", "tauri"], js_name = invoke)]
+    fn invoke_now(cmd: &str, args: JsValue) -> js_sys::Promise;
+}
+
+#[derive(Serialize)]
+struct LogArgs {
+    level: LogLevel,
+    target: String,
+    msg: String,
+    call: Option<u32>,
+}
+
+thread_local! {
+    /// Call id of the command the frontend is working on, sent along with its messages
+    static CALL: Cell<Option<u32>> = Cell::default();
+    static LAST_CALL: Cell<u32> = Cell::default();
+}
+
+/// A new call id for a command, the messages logged from now on carry it
+pub fn begin_call() -> u32 {
+    let call = LAST_CALL.with(|x| x.get()) + 1;
+    LAST_CALL.with(|x| x.set(call));
+    CALL.with(|x| x.set(Some(call)));
+    call
+}
+
+/// The messages logged from now on carry `call` again, once its command returned
+pub fn continue_call(call: u32) {
+    CALL.with(|x| x.set(Some(call)));
+}
+
+/// Sends the messages of `log::info!` and the like to the log file of the backend
+struct BackendLogger;
+
+impl log::Log for BackendLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let level = match record.level() {
+            log::Level::Error => LogLevel::Error,
+            log::Level::Warn => LogLevel::Warn,
+            log::Level::Info => LogLevel::Info,
+            log::Level::Debug => LogLevel::Debug,
+            log::Level::Trace => LogLevel::Trace,
+        };
+        let args = LogArgs {
+            level,
+            target: record.target().to_string(),
+            msg: record.args().to_string(),
+            call: CALL.with(Cell::get),
+        };
+        gloo_console::log!(format!("{level} {}: {}", args.target, args.msg));
+        let _ = invoke_now("log", to_value(&args).unwrap());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: BackendLogger = BackendLogger;
+
+/// Installs the logger of the frontend, panics are logged as errors.
+/// The backend decides which levels end up in the log file.
+pub fn init_logging() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log::LevelFilter::Trace);
+    }
+    std::panic::set_hook(Box::new(|info| {
+        log::error!("{info}");
+        console_error_panic_hook::hook(info);
+    }));
+}
+
+fn level_color(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Error => "text-red-500",
+        LogLevel::Warn => "text-yellow-500",
+        LogLevel::Info => "text-text",
+        LogLevel::Debug | LogLevel::Trace => "text-subtext",
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LogViewerProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Shows the latest entries of the log file, to find out what went wrong and
+/// to copy them into bug reports
+#[function_component(LogViewer)]
+pub fn log_viewer(
+    LogViewerProps {
+        closing_callback: on_close,
+    }: &LogViewerProps,
+) -> Html {
+    let entries = use_state(Vec::<LogEntry>::new);
+    let level = use_state(|| LogLevel::Trace);
+    let source = use_state(String::new);
+    let search = use_state(String::new);
+    let message = use_state(String::new);
+
+    let reload = {
+        let entries = entries.clone();
+        Callback::from(move |()| {
+            let entries = entries.clone();
+            spawn_local(async move {
+                let entries_jsvalue = invoke("read_log", JsValue::NULL).await;
+                entries.set(from_value(entries_jsvalue).unwrap_or_default());
+            });
+        })
+    };
+    {
+        let reload = reload.clone();
+        use_effect_with((), move |()| reload.emit(()));
+    }
+
+    let shown: Vec<&LogEntry> = entries
+        .iter()
+        .filter(|x| x.level <= *level)
+        .filter(|x| source.is_empty() || x.source == *source)
+        .filter(|x| {
+            let search = search.to_lowercase();
+            x.message.to_lowercase().contains(&search)
+                || x.target.to_lowercase().contains(&search)
+                || x.origin().contains(&search)
+        })
+        .collect();
+    let text: String = shown.iter().map(|x| x.to_line()).collect();
+
+    let on_level_change = {
+        let level = level.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                level.set(LogLevel::from_label(&select.value()).unwrap_or_default());
+            }
+        })
+    };
+    let on_source_change = {
+        let source = source.clone();
+        Callback::from(move |e: Event| {
+            if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                source.set(select.value());
+            }
+        })
+    };
+    let on_search = {
+        let search = search.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                search.set(input.value());
+            }
+        })
+    };
+
+    let on_copy = {
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| {
+            let _ = gloo::utils::window()
+                .navigator()
+                .clipboard()
+                .write_text(&text);
+            message.set("Copied the shown entries.".to_string());
+        })
+    };
+
+    let on_open_folder = Callback::from(|_: MouseEvent| {
+        spawn_local(async {
+            let Some(path) = invoke("get_log_folder", JsValue::NULL).await.as_string() else {
+                return;
+            };
+            invoke("open_explorer", to_value(&PathArgs { path }).unwrap()).await;
+        });
+    });
+
+    let select_class = "bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary";
+
+    html! {
+        <>
+            <div class="text-xl font-bold">{ "Log" }</div>
+            <div class="flex w-full pt-4 gap-4">
+                <select class={select_class} onchange={on_level_change}>
+                    { for LogLevel::ALL.iter().map(|x| html! {
+                        <option value={x.label()} selected={*x == *level}>{ x.label() }</option>
+                    }) }
+                </select>
+                <select class={select_class} onchange={on_source_change}>
+                    <option value="" selected={source.is_empty()}>{ "Backend and Frontend" }</option>
+                    <option value={BACKEND} selected={*source == BACKEND}>{ "Backend" }</option>
+                    <option value={FRONTEND} selected={*source == FRONTEND}>{ "Frontend" }</option>
+                </select>
+                <input
+                    oninput={on_search}
+                    placeholder="Search"
+                    class="grow bg-crust text-text p-2 rounded-lg border-0 font-standard text-base"
+                />
+            </div>
+            <div class="mt-4 h-[50vh] overflow-auto bg-crust rounded-lg p-2 font-mono text-xs">
+                if shown.is_empty() {
+                    <div class="text-subtext">{ "No entries." }</div>
+                }
+                { for shown.iter().map(|x| html! {
+                    <div class={classes!("whitespace-pre-wrap", level_color(x.level))}>
+                        <span class="text-subtext">{ format!("{} ", x.time) }</span>
+                        { format!("{:<5} {} {}: {}", x.level, x.origin(), x.target, x.message) }
+                    </div>
+                }) }
+            </div>
+            <div class="pt-4 text-sm text-subtext">{ (*message).clone() }</div>
+            <div class="flex justify-end w-full pt-8">
+                <button
+                    onclick={reload.reform(|_| ())}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Refresh" }
+                </button>
+                <button
+                    onclick={on_copy}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Copy" }
+                </button>
+                <button
+                    onclick={on_open_folder}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Open Log Folder" }
+                </button>
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </>
+    }
+}
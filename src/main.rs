@@ -1,8 +1,8 @@
 mod app;
 
-use app::App;
+use app::{init_logging, App};
 
 fn main() {
-    console_error_panic_hook::set_once();
+    init_logging();
     yew::Renderer::<App>::new().render();
 }
@@ -12,7 +12,7 @@ pub fn modal(Props { content }: &Props) -> Html {
             <div
                 class="absolute top-0 left-0 z-50 bg-black/60 h-full w-full flex items-center justify-center text-text"
             >
-                <div class="bg-base rounded-lg max-w-[80%] min-w-[40%] p-8">
+                <div class="bg-base rounded-lg max-w-[80%] min-w-[40%] max-h-[90%] overflow-y-auto p-8">
                     { content.clone() }
                 </div>
             </div>
@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use pulldown_cmark::{
+    html::push_html, CowStr, Event, LinkType, Options, Parser, Tag, TagEnd, TextMergeStream,
+};
+use shared::{find_mentions, footnote_order, replace_comments, split_link, CodexEntry};
+use web_sys::{Element, Node};
+
+use crate::app::editor::view::{child_index, replace_children};
+use crate::app::editor::TextBuffer;
+
+/// Source lines of the buffer that are parsed and rendered together
+struct Block {
+    lines: Range<usize>,
+    source: String,
+    html: String,
+}
+
+/// The rendered preview of the buffer, one element per block.
+/// Only blocks whose source changed get parsed again.
+#[derive(Default)]
+pub struct Preview {
+    blocks: Vec<Block>,
+    /// Footnote labels in the order they are numbered throughout the chapter
+    footnotes: Vec<String>,
+}
+
+impl Preview {
+    /// Drops all rendered blocks, so the next update renders everything again
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    pub fn update(&mut self, rendered: &Element, buffer: &TextBuffer, codex: &[CodexEntry]) {
+        // The numbers of footnotes depend on the whole chapter
+        let footnotes = footnote_order(&buffer.to_string());
+        if footnotes != self.footnotes {
+            self.footnotes = footnotes;
+            self.clear();
+        }
+
+        let sources = split_blocks(buffer);
+        let mut old = std::mem::take(&mut self.blocks);
+
+        let prefix = old
+            .iter()
+            .zip(&sources)
+            .take_while(|(block, (_, source))| block.source == *source)
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(sources[prefix..].iter().rev())
+            .take_while(|(block, (_, source))| block.source == *source)
+            .count();
+
+        let kept_end = old.len() - suffix;
+        let kept: Vec<Block> = old.drain(kept_end..).collect();
+        // Blocks that only moved keep their rendering
+        let mut moved: HashMap<String, String> = old
+            .drain(prefix..)
+            .map(|block| (block.source, block.html))
+            .collect();
+
+        let mut sources = sources.into_iter();
+        let mut blocks = old;
+        for (block, (lines, _)) in blocks.iter_mut().zip(sources.by_ref()) {
+            block.lines = lines;
+        }
+        let changed_count = sources.len() - suffix;
+        let changed: Vec<Block> = sources
+            .by_ref()
+            .take(changed_count)
+            .map(|(lines, source)| {
+                let html = moved
+                    .remove(&source)
+                    .unwrap_or_else(|| render_block(&source, codex, &self.footnotes));
+                Block {
+                    lines,
+                    source,
+                    html,
+                }
+            })
+            .collect();
+
+        let count = rendered.child_element_count() as usize;
+        replace_children(
+            rendered,
+            prefix..count.saturating_sub(suffix),
+            changed.iter().map(block_element),
+        );
+
+        blocks.extend(changed);
+        for (mut block, (lines, _)) in kept.into_iter().zip(sources) {
+            block.lines = lines;
+            blocks.push(block);
+        }
+        self.blocks = blocks;
+    }
+}
+
+impl Preview {
+    fn block_of_line(&self, line: usize) -> Option<usize> {
+        let index = self.blocks.partition_point(|block| block.lines.end <= line);
+        (index < self.blocks.len()).then_some(index)
+    }
+
+    /// First source line of the block shown by `node`
+    pub fn line_at(&self, rendered: &Element, node: &Node) -> Option<usize> {
+        let index = child_index(rendered, node)?;
+        Some(self.blocks.get(index)?.lines.start)
+    }
+
+    /// Top and height of the block showing `line`, relative to the scrolled content
+    fn block_bounds(&self, rendered: &Element, line: usize) -> Option<(usize, f64, f64)> {
+        let index = self.block_of_line(line)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let element = rendered.children().item(index as u32)?;
+        let rect = element.get_bounding_client_rect();
+        let top = rect.top() - rendered.get_bounding_client_rect().top()
+            + f64::from(rendered.scroll_top());
+        Some((index, top, rect.height()))
+    }
+
+    /// Scrolls so the preview of `line` is at the top, `scrolled` of it already passed
+    pub fn scroll_to_line(&self, rendered: &Element, line: usize, scrolled: f64) {
+        let Some((index, top, height)) = self.block_bounds(rendered, line) else {
+            return;
+        };
+        let lines = &self.blocks[index].lines;
+        #[allow(clippy::cast_precision_loss)]
+        let progress = ((line - lines.start) as f64 + scrolled) / lines.len() as f64;
+
+        #[allow(clippy::cast_possible_truncation)]
+        rendered.set_scroll_top((top + progress * height) as i32);
+    }
+
+    /// Scrolls the preview of `line` into view if it is outside of it
+    pub fn reveal_line(&self, rendered: &Element, line: usize) {
+        let Some((_, top, height)) = self.block_bounds(rendered, line) else {
+            return;
+        };
+        let scroll_top = f64::from(rendered.scroll_top());
+        let visible = f64::from(rendered.client_height());
+
+        #[allow(clippy::cast_possible_truncation)]
+        if top < scroll_top {
+            rendered.set_scroll_top(top as i32);
+        } else if top + height > scroll_top + visible {
+            rendered.set_scroll_top((top + height.min(visible) - visible) as i32);
+        }
+    }
+}
+
+fn block_element(block: &Block) -> Element {
+    let element = gloo::utils::document().create_element("div").unwrap();
+    element.set_inner_html(&block.html);
+    element
+}
+
+/// Opening fence of a code block, as its character and length
+fn fence(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == marker).count();
+    (length >= 3).then_some((marker, length))
+}
+
+fn closes_fence(line: &str, (marker, length): (char, usize)) -> bool {
+    let trimmed = line.trim();
+    trimmed.chars().take_while(|c| *c == marker).count() >= length
+        && trimmed.chars().all(|c| c == marker)
+}
+
+fn is_list_item(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    let rest = &trimmed[digits..];
+
+    if digits > 0 {
+        rest.starts_with(". ") || rest.starts_with(") ") || rest == "." || rest == ")"
+    } else {
+        ["- ", "* ", "+ "]
+            .iter()
+            .any(|marker| trimmed.starts_with(marker))
+            || ["-", "*", "+"].contains(&trimmed)
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// End of the block of text lines starting at `start`
+fn text_block_end(lines: &[String], start: usize) -> usize {
+    let list = is_list_item(&lines[start]);
+    let mut end = start + 1;
+    loop {
+        while end < lines.len() && !is_blank(&lines[end]) && fence(&lines[end]).is_none() {
+            end += 1;
+        }
+
+        // Loose lists go on after empty lines
+        let next = end
+            + lines[end..]
+                .iter()
+                .take_while(|line| is_blank(line))
+                .count();
+        let continues = list
+            && next > end
+            && lines.get(next).is_some_and(|line| {
+                is_list_item(line) || line.starts_with("  ") || line.starts_with('\t')
+            });
+        if !continues {
+            return end;
+        }
+        end = next;
+    }
+}
+
+/// Splits the buffer into blocks that can be parsed on their own.
+/// Fenced code and loose lists stay together even across empty lines.
+fn split_blocks(buffer: &TextBuffer) -> Vec<(Range<usize>, String)> {
+    let lines: Vec<String> = (0..buffer.line_count())
+        .map(|line| buffer.line(line))
+        .collect();
+
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = if is_blank(&lines[start]) {
+            start
+                + lines[start..]
+                    .iter()
+                    .take_while(|line| is_blank(line))
+                    .count()
+        } else if let Some(opening) = fence(&lines[start]) {
+            lines[start + 1..]
+                .iter()
+                .position(|line| closes_fence(line, opening))
+                .map_or(lines.len(), |index| start + index + 2)
+        } else {
+            text_block_end(&lines, start)
+        };
+
+        blocks.push((start..end, lines[start..end].join("\n")));
+        start = end;
+    }
+
+    blocks
+}
+
+fn footnote_number(footnotes: &[String], label: &str) -> String {
+    footnotes
+        .iter()
+        .position(|x| x == label)
+        .map_or_else(|| "?".to_string(), |index| (index + 1).to_string())
+}
+
+/// Renders the markdown of a single block including the PaperSmith extensions:
+/// `__underline__`, `::highlight::`, `!(image)`, `[[links]]` and `{>>comments<<}`
+fn render_block(source: &str, codex: &[CodexEntry], footnotes: &[String]) -> String {
+    // Runs of empty lines only make a single break
+    if is_blank(source) {
+        return "<br>".to_string();
+    }
+
+    // Comments get a marker in the text and are shown in the gutter next to the block
+    let mut gutter = Vec::new();
+    let source = &replace_comments(source, |index, comment| {
+        let number = index + 1;
+        gutter.push(format!(
+            r#"<div><sup class="text-secondary">{number}</sup> {}</div>"#,
+            escape_attribute(&comment.text)
+        ));
+        let marker = format!(r#"<sup class="text-secondary">{number}</sup>"#);
+        match &comment.annotated {
+            Some(range) => format!(
+                r#"<span class="border-0 border-b-2 border-dotted border-secondary">{}</span>{marker}"#,
+                &source[range.clone()]
+            ),
+            None => marker,
+        }
+    });
+
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+    // References are found even when the definition is in another block
+    options.insert(Options::ENABLE_OLD_FOOTNOTES);
+
+    let source = &escape_underlines(source, options);
+    let events = Parser::new_ext(source, options).map(|event| match event {
+        // Numbered by the whole chapter, not only this block
+        Event::FootnoteReference(label) => {
+            let number = footnote_number(footnotes, &label);
+            Event::InlineHtml(
+                format!(
+                    r#"<sup class="text-primary cursor-pointer" data-footnote="{number}">{number}</sup>"#
+                )
+                .into(),
+            )
+        }
+        Event::Start(Tag::FootnoteDefinition(label)) => {
+            let number = footnote_number(footnotes, &label);
+            Event::Html(
+                format!(
+                    r#"<div class="flex gap-1 text-sm" data-footnote-definition="{number}"><sup class="text-primary">{number}</sup><div>"#
+                )
+                .into(),
+            )
+        }
+        Event::End(TagEnd::FootnoteDefinition) => Event::Html("</div></div>".into()),
+        // Every line of the editor is a line in the preview
+        Event::SoftBreak => Event::HardBreak,
+        _ => event,
+    });
+    let events: Vec<Event> = TextMergeStream::new(events).collect();
+
+    let mut html_output = String::new();
+    push_html(&mut html_output, extend_text(events, codex).into_iter());
+
+    if !gutter.is_empty() {
+        html_output = format!(
+            r#"<div class="flex gap-3"><div class="flex-1 min-w-0">{html_output}</div><aside class="w-1/3 shrink-0 text-sm border-0 border-l-2 border-solid border-secondary pl-2 select-none">{}</aside></div>"#,
+            gutter.concat()
+        );
+    }
+
+    html_output
+}
+
+/// Renders a whole document at once, for exporting it.
+/// Comments still in it are kept apart from the text.
+pub fn render_document(source: &str) -> String {
+    let source = replace_comments(source, |_, comment| {
+        let note = format!(
+            r#"<span class="comment">{}</span>"#,
+            escape_attribute(&comment.text)
+        );
+        match &comment.annotated {
+            Some(range) => format!(
+                r#"<mark class="annotation">{}</mark>{note}"#,
+                &source[range.clone()]
+            ),
+            None => note,
+        }
+    });
+
+    render_block(&source, &[], &footnote_order(&source))
+}
+
+/// Markers that are switched on and off in text, with the tag they become
+const TOGGLES: [(&str, &str); 2] = [("::", "mark"), ("__", "u")];
+
+/// Escapes the underscores of `__` outside of code and html, so the parser keeps
+/// them in the text for `extend_text` instead of making it bold. Of an odd number
+/// of underscores the one next to the word is left for emphasis.
+fn escape_underlines(source: &str, options: Options) -> String {
+    // Where the parser keeps underscores as they are
+    let literal: Vec<Range<usize>> = Parser::new_ext(source, options)
+        .into_offset_iter()
+        .filter_map(|(event, range)| match event {
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                Some(range.start..range.start + label.len() + 3)
+            }
+            Event::Start(
+                Tag::CodeBlock(_)
+                | Tag::Link {
+                    link_type: LinkType::Autolink | LinkType::Email,
+                    ..
+                },
+            )
+            | Event::Code(_)
+            | Event::Html(_)
+            | Event::InlineHtml(_)
+            | Event::FootnoteReference(_)
+            | Event::Rule => Some(range),
+            _ => None,
+        })
+        .collect();
+
+    let bytes = source.as_bytes();
+    let mut escaped = String::with_capacity(source.len());
+    let mut last_end = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] != b'_' || literal.iter().any(|range| range.contains(&index)) {
+            index += 1;
+            continue;
+        }
+        let end = index + bytes[index..].iter().take_while(|x| **x == b'_').count();
+        // An underscore after a backslash is escaped already
+        let start = if index > 0 && bytes[index - 1] == b'\\' {
+            index + 1
+        } else {
+            index
+        };
+        let count = (end - start) / 2 * 2;
+        let opening = bytes
+            .get(end)
+            .is_some_and(|x| !x.is_ascii_whitespace() && !x.is_ascii_punctuation());
+        let first = if opening { start } else { end - count };
+
+        escaped.push_str(&source[last_end..first]);
+        escaped.push_str(&"\\_".repeat(count));
+        last_end = first + count;
+        index = end;
+    }
+    escaped.push_str(&source[last_end..]);
+    escaped
+}
+
+/// A marker of the PaperSmith extensions in text
+enum Marker {
+    /// `::` or `__`, by its index in `TOGGLES`
+    Toggle(usize),
+    /// `!(image)` or `[[link]]`, with the html it becomes
+    Html(String),
+}
+
+/// Markers in `text`, with their byte range
+fn find_markers(text: &str) -> Vec<(Range<usize>, Marker)> {
+    let mut markers = Vec::new();
+    let mut index = 0;
+    while let Some(c) = text[index..].chars().next() {
+        let rest = &text[index..];
+        let marker = if let Some(toggle) = TOGGLES
+            .iter()
+            .position(|(marker, _)| rest.starts_with(marker))
+        {
+            Some((TOGGLES[toggle].0.len(), Marker::Toggle(toggle)))
+        } else if rest.starts_with("!(") {
+            rest.find(')').map(|end| {
+                let html = format!(r#"<img src="{}"/>"#, escape_attribute(rest[2..end].trim()));
+                (end + 1, Marker::Html(html))
+            })
+        } else if rest.starts_with("[[") {
+            // Opens the chapter or codex entry named `target` when clicked
+            rest.find("]]").map(|end| {
+                let (target, label) = split_link(&rest[2..end]);
+                let html = format!(
+                    r#"<span class="text-primary underline cursor-pointer" data-link="{}">{}</span>"#,
+                    escape_attribute(target),
+                    escape_attribute(label)
+                );
+                (end + 2, Marker::Html(html))
+            })
+        } else {
+            None
+        };
+
+        match marker {
+            Some((length, marker)) => {
+                markers.push((index..index + length, marker));
+                index += length;
+            }
+            None => index += c.len_utf8(),
+        }
+    }
+    markers
+}
+
+/// Replaces highlight, underline, image and link markers in text outside of code
+/// and links the mentions of `codex` entries.
+/// Toggles pair up inside the element they are in, one without a partner stays text.
+fn extend_text<'a>(events: Vec<Event<'a>>, codex: &[CodexEntry]) -> Vec<Event<'a>> {
+    // Replaced markers as their event, their range in its text and their html
+    let mut replaced: Vec<(usize, Range<usize>, Option<String>)> = Vec::new();
+    let mut prose = vec![false; events.len()];
+    // Open elements by their start event, and whether their text is shown as it is
+    let mut elements: Vec<(usize, bool)> = Vec::new();
+    // Unpaired toggles by element and toggle, as their index in `replaced`
+    let mut opened: HashMap<(Option<usize>, usize), usize> = HashMap::new();
+
+    for (index, event) in events.iter().enumerate() {
+        match event {
+            Event::Start(tag) => {
+                elements.push((index, matches!(tag, Tag::CodeBlock(_) | Tag::Image { .. })));
+            }
+            Event::End(_) => {
+                elements.pop();
+            }
+            Event::Text(text) if !elements.iter().any(|(_, literal)| *literal) => {
+                prose[index] = true;
+                let element = elements.last().map(|(start, _)| *start);
+                for (range, marker) in find_markers(text) {
+                    let html = match marker {
+                        Marker::Toggle(toggle) => {
+                            let tag = TOGGLES[toggle].1;
+                            if let Some(opening) = opened.remove(&(element, toggle)) {
+                                replaced[opening].2 = Some(format!("<{tag}>"));
+                                Some(format!("</{tag}>"))
+                            } else {
+                                opened.insert((element, toggle), replaced.len());
+                                None
+                            }
+                        }
+                        Marker::Html(html) => Some(html),
+                    };
+                    replaced.push((index, range, html));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut replaced = replaced
+        .into_iter()
+        .filter_map(|(event, range, html)| Some((event, range, html?)))
+        .peekable();
+    let mut extended = Vec::with_capacity(events.len());
+    for (index, event) in events.into_iter().enumerate() {
+        let Event::Text(text) = event else {
+            extended.push(event);
+            continue;
+        };
+        if !prose[index] {
+            extended.push(Event::Text(text));
+            continue;
+        }
+
+        let mut last_end = 0;
+        while let Some((_, range, html)) = replaced.next_if(|(event, _, _)| *event == index) {
+            push_text(&mut extended, &text[last_end..range.start], codex);
+            extended.push(Event::InlineHtml(html.into()));
+            last_end = range.end;
+        }
+        push_text(&mut extended, &text[last_end..], codex);
+    }
+
+    extended
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+/// Adds `text` with the mentions of `codex` entries in it linked. Mentions are
+/// found in the text itself, before it is escaped.
+fn push_text(events: &mut Vec<Event<'_>>, text: &str, codex: &[CodexEntry]) {
+    let mut last_end = 0;
+
+    for (range, index) in find_mentions(text, codex) {
+        let kind = codex[index].kind;
+        events.push(Event::Text(CowStr::from(
+            text[last_end..range.start].to_string(),
+        )));
+        events.push(Event::InlineHtml(
+            format!(
+                r#"<span class="underline decoration-dotted decoration-primary cursor-pointer" data-codex="{index}" title="{kind}: {}">{}</span>"#,
+                escape_attribute(&codex[index].name),
+                escape_attribute(&text[range.clone()]),
+            )
+            .into(),
+        ));
+        last_end = range.end;
+    }
+    events.push(Event::Text(CowStr::from(text[last_end..].to_string())));
+}
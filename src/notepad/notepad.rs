@@ -1,51 +1,244 @@
-use pulldown_cmark::{html::push_html, Options, Parser};
-use regex::Regex;
-use web_sys::HtmlElement;
+use web_sys::Element;
 use yew::prelude::*;
+use yew::virtual_dom::VNode;
 use yewdux::prelude::*;
 
 #[path = "zoom_handlers.rs"]
 mod zoom_edit_container_handlers;
-use zoom_edit_container_handlers::ZoomControls;
+use zoom_edit_container_handlers::{zoom_decrease_handler, zoom_increase_handler, ZoomControls};
 
+#[path = "markdown.rs"]
+pub mod markdown;
+use markdown::Preview;
+
+#[path = "revision.rs"]
+mod revision;
+use revision::render_revision;
+
+use crate::app::codex::open_codex_editor;
+use crate::app::commands::use_commands;
+use crate::app::editor::{use_editor_view, view, Change, Editor, EditorHandle};
+use crate::app::notes::follow_link;
 use crate::app::State;
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub pages_ref: NodeRef,
-    pub text_input_ref: NodeRef,
+    pub editor: EditorHandle,
+    pub modal: UseStateHandle<VNode>,
 }
 
 #[function_component(Notepads)]
 pub fn notepads(
     Props {
         pages_ref,
-        text_input_ref,
+        editor,
+        modal,
     }: &Props,
 ) -> Html {
-    let (_state, dispatch) = use_store::<State>();
+    let (state, dispatch) = use_store::<State>();
     let zoom_compile_ref = use_node_ref();
     let zoom_edit_ref = use_node_ref();
     let font_size_edit = use_state(|| 16.0);
     let font_size_compile = use_state(|| 16.0);
     let render_ref = use_node_ref();
-    let on_text_input = {
+    let preview = use_mut_ref(Preview::default);
+    // The current round while the chapter is in revision mode
+    let revising = use_state(|| None::<u32>);
+
+    // The preview shows the block the cursor is in
+    {
+        let on_selection = {
+            let editor = editor.clone();
+            let render_ref = render_ref.clone();
+            let preview = preview.clone();
+            Callback::from(move |()| {
+                let editor = editor.borrow();
+                if let Some(rendered) = render_ref.cast::<Element>() {
+                    let line = editor.buffer().line_of(editor.selection().head);
+                    preview.borrow().reveal_line(&rendered, line);
+                }
+            })
+        };
+        use_editor_view(editor, on_selection);
+    }
+
+    // Keeps the preview in line with the editor, mentions of new or renamed
+    // codex entries get linked once the codex changes.
+    // In revision mode the tracked changes are shown instead.
+    {
+        let editor = editor.clone();
+        let render_ref = render_ref.clone();
+        let preview = preview.clone();
+        let revising = revising.clone();
+        let dispatch = dispatch.clone();
+        use_effect_with(state.codex.clone(), move |codex| {
+            let codex = codex.clone();
+            preview.borrow_mut().clear();
+
+            let listener = {
+                let editor = editor.clone();
+                Callback::from(move |change: Change| {
+                    let editor = editor.borrow();
+                    if let Some(rendered) = render_ref.cast::<Element>() {
+                        let mut preview = preview.borrow_mut();
+                        if let Some(revision) = editor.revision() {
+                            // Edits show up once the tracked changes caught up with them
+                            if !editor.is_tracking_behind() {
+                                preview.clear();
+                                render_revision(&rendered, revision);
+                            }
+                        } else {
+                            preview.update(&rendered, editor.buffer(), &codex);
+                            preview.reveal_line(
+                                &rendered,
+                                editor.buffer().line_of(editor.selection().head),
+                            );
+                        }
+                    }
+                    revising.set(editor.revision().map(|revision| revision.round));
+
+                    if change != Change::Document {
+                        dispatch.reduce_mut(|x| x.changes = true);
+                    }
+                })
+            };
+            editor.set_listener(listener.clone());
+            listener.emit(Change::Document);
+        });
+    }
+
+    // The browser's own undo stack knows nothing about the edits of the editor
+    {
+        let command = |action: fn(&mut Editor) -> Option<Change>| {
+            let editor = editor.clone();
+            Callback::from(move |()| editor.update(action))
+        };
+        let style = |style: &'static str| {
+            let editor = editor.clone();
+            Callback::from(move |()| editor.update(|editor| editor.wrap_selection(style)))
+        };
+        use_commands(vec![
+            ("undo", command(Editor::undo)),
+            ("redo", command(Editor::redo)),
+            ("bold", style("**")),
+            ("italic", style("_")),
+            ("underline", style("__")),
+            ("highlight", style("::")),
+            (
+                "zoom_in",
+                zoom_increase_handler(font_size_edit.clone(), zoom_edit_ref.clone())
+                    .reform(|()| MouseEvent::new("Dummy").unwrap()),
+            ),
+            (
+                "zoom_out",
+                zoom_decrease_handler(font_size_edit.clone(), zoom_edit_ref.clone())
+                    .reform(|()| MouseEvent::new("Dummy").unwrap()),
+            ),
+        ]);
+    }
+
+    // The preview follows the editor while scrolling
+    let on_editor_scroll = {
+        let editor = editor.clone();
+        let render_ref = render_ref.clone();
+        let preview = preview.clone();
+        Callback::from(move |_: Event| {
+            let (Some(view), Some(rendered)) = (
+                editor.view_ref().cast::<Element>(),
+                render_ref.cast::<Element>(),
+            ) else {
+                return;
+            };
+            if let Some((line, scrolled)) = view::top_line(&view) {
+                preview.borrow().scroll_to_line(&rendered, line, scrolled);
+            }
+        })
+    };
+
+    let on_render_click = {
+        let modal = modal.clone();
+        let codex = state.codex.clone();
+        let editor = editor.clone();
         let render_ref = render_ref.clone();
-        let text_input_ref = text_input_ref.clone();
-        Callback::from(move |e: InputEvent| {
-            if let Some(input) = text_input_ref.cast::<HtmlElement>() {
-                let inner_text = input.inner_text();
-                // gloo_console::log!(format!("Printing text: {}", &inner_text));
-                let new_lines: Vec<String> = inner_text.lines().map(String::from).collect();
-                if e.data().is_some() {
-                    dispatch.reduce_mut(|x| x.changes = true);
+        let preview = preview.clone();
+        Callback::from(move |e: MouseEvent| {
+            let Some(target) = e.target_dyn_into::<Element>() else {
+                return;
+            };
+            let change = |name: &str| {
+                target
+                    .get_attribute(name)
+                    .and_then(|index| index.parse::<usize>().ok())
+            };
+            if let Some(index) = change("data-accept") {
+                editor.update(|editor| editor.accept_change(Some(index)));
+                return;
+            }
+            if let Some(index) = change("data-reject") {
+                editor.update(|editor| editor.reject_change(Some(index)));
+                return;
+            }
+            if let Some(link) = target.get_attribute("data-link") {
+                follow_link(&link, &dispatch, &modal);
+                return;
+            }
+            if let Some(index) = target
+                .get_attribute("data-codex")
+                .and_then(|index| index.parse::<usize>().ok())
+            {
+                if let Some(entry) = codex.get(index) {
+                    open_codex_editor(&modal, Some(entry.clone()), entry.kind);
                 }
-                //lines.set(new_lines);
-                rendering_handler(&render_ref, &new_lines);
+                return;
+            }
+
+            let Some(rendered) = render_ref.cast::<Element>() else {
+                return;
+            };
+            if let Some(number) = target.get_attribute("data-footnote") {
+                let selector = format!(r#"[data-footnote-definition="{number}"]"#);
+                if let Ok(Some(definition)) = rendered.query_selector(&selector) {
+                    definition.scroll_into_view_with_bool(true);
+                }
+                return;
+            }
+
+            // Jump to the source of the clicked block, unless text is being selected
+            let selecting = gloo::utils::window()
+                .get_selection()
+                .ok()
+                .flatten()
+                .is_some_and(|selection| !selection.is_collapsed());
+            if selecting {
+                return;
+            }
+            let line = preview.borrow().line_at(&rendered, &target);
+            if let Some(line) = line {
+                editor.borrow_mut().go_to_line(line);
             }
         })
     };
 
+    let revision_action = |action: fn(&mut Editor) -> Option<Change>| {
+        let editor = editor.clone();
+        Callback::from(move |_: MouseEvent| editor.update(action))
+    };
+    let button_class = "rounded-lg px-2 py-1 bg-crust hover:bg-mantle border-0 cursor-pointer text-inherit text-sm";
+
+    let settings = state.settings.clone().unwrap_or_default();
+    let page_width = if settings.page_width == 0 {
+        String::new()
+    } else {
+        format!(" max-width: {}ch; margin: 0 auto;", settings.page_width)
+    };
+    let editor_style = format!(
+        "font-size: {}px; font-family: {}; line-height: {};{page_width}",
+        *font_size_edit,
+        settings.editor_font,
+        f64::from(settings.line_height) / 100.0
+    );
+
     html!(
         <div class="flex flex-grow  bg-crust justify-evenly gap-5 px-3" ref={pages_ref.clone()}>
             <div
@@ -59,10 +252,12 @@ pub fn notepads(
                 <div
                     class="flex-grow p-4 overflow-x-hidden outline-none break-words"
                     id="notepad-textarea-edit"
-                    ref={text_input_ref}
-                    style={format!("font-size: {}px;", *font_size_edit)}
+                    ref={editor.view_ref()}
+                    style={editor_style}
                     contenteditable="true"
-                    oninput={on_text_input}
+                    spellcheck={(!settings.spellcheck_language.is_empty()).to_string()}
+                    lang={settings.spellcheck_language.clone()}
+                    onscroll={on_editor_scroll}
                     tabindex="0"
                 />
             </div>
@@ -76,61 +271,55 @@ pub fn notepads(
                         font_size={font_size_compile.clone()}
                         container={zoom_compile_ref}
                     />
+                    <div class="ml-auto flex items-center gap-1 py-1">
+                        if let Some(round) = *revising {
+                            <span class="font-bold text-sm mr-1">
+                                { format!("Revision Round {round}") }
+                            </span>
+                            <button
+                                class={button_class}
+                                onclick={revision_action(Editor::next_revision_round)}
+                            >
+                                { "New Round" }
+                            </button>
+                            <button
+                                class={button_class}
+                                onclick={revision_action(|editor| editor.accept_change(None))}
+                            >
+                                { "Accept All" }
+                            </button>
+                            <button
+                                class={button_class}
+                                onclick={revision_action(|editor| editor.reject_change(None))}
+                            >
+                                { "Reject All" }
+                            </button>
+                            <button
+                                class={button_class}
+                                title="Stop tracking changes and keep the text as it is"
+                                onclick={revision_action(Editor::end_revision)}
+                            >
+                                { "End Revision" }
+                            </button>
+                        } else {
+                            <button
+                                class={button_class}
+                                title="Track changes against the chapter as it is now"
+                                onclick={revision_action(Editor::start_revision)}
+                            >
+                                { "Start Revision" }
+                            </button>
+                        }
+                    </div>
                 </div>
                 <div
                     class="flex-grow p-4 overflow-x-hidden break-words space-y-0"
                     id="notepad-textarea-compile"
                     style={format!("font-size: {}px; word-break: break-word;", *font_size_compile)}
                     ref={render_ref}
+                    onclick={on_render_click}
                 />
             </div>
         </div>
     )
 }
-
-// ad br tag after end of each line (make it one string)
-fn rendering_handler(render_ref: &NodeRef, new_lines: &[String]) {
-    let mut last_was_empty = false;
-
-    let mark_regex = Regex::new(r"::(.*?)::").unwrap();
-    let underline_regex = Regex::new(r"__(.*?)__").unwrap();
-    let image_regex = Regex::new(r"!\(\s*(.*?)\s*\)").unwrap();
-
-    let html_strings: Vec<String> = new_lines
-        .iter()
-        .map(|line| {
-            // gloo_console::log!(line);
-            let mut options = Options::empty();
-            options.insert(Options::ENABLE_STRIKETHROUGH);
-            options.insert(Options::ENABLE_TABLES);
-
-            if line.trim().is_empty() {
-                if last_was_empty {
-                    String::new()
-                } else {
-                    last_was_empty = true;
-                    "<br>".to_string()
-                }
-            } else {
-                last_was_empty = false;
-
-                let line_with_mark = mark_regex.replace_all(line, r"<mark>$1</mark>");
-                let line_with_underline =
-                    underline_regex.replace_all(&line_with_mark, r"<u>$1</u>");
-                let line_with_image =
-                    image_regex.replace_all(&line_with_underline, r#"<img src="$1"/>"#);
-
-                let parser = Parser::new_ext(line_with_image.as_ref(), options);
-                let mut html_output = String::new();
-                push_html(&mut html_output, parser);
-                html_output
-            }
-        })
-        .collect();
-
-    let html_string: String = html_strings.join("\n");
-
-    if let Some(rendered) = render_ref.cast::<HtmlElement>() {
-        rendered.set_inner_html(html_string.as_str());
-    }
-}
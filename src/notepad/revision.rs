@@ -0,0 +1,42 @@
+use shared::Revision;
+use web_sys::Element;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Every round gets its own color, spread around the color wheel
+fn round_color(round: u32) -> String {
+    format!("hsl({} 70% 45%)", (200 + round * 137) % 360)
+}
+
+/// Shows the text with its tracked changes instead of the markdown preview.
+/// Hovering a change shows buttons to accept or reject it.
+pub fn render_revision(rendered: &Element, revision: &Revision) {
+    let mut html = String::from(r#"<div class="whitespace-pre-wrap">"#);
+
+    for (index, span) in revision.spans.iter().enumerate() {
+        let text = escape(&span.text);
+        let Some(round) = span.round() else {
+            html.push_str(&text);
+            continue;
+        };
+
+        let tag = if span.deleted.is_some() { "del" } else { "ins" };
+        let title = if span.deleted.is_some() {
+            format!("Deleted in round {round}")
+        } else {
+            format!("Inserted in round {round}")
+        };
+        html.push_str(&format!(
+            r#"<span class="group relative"><{tag} style="color: {}" title="{title}">{text}</{tag}><span class="hidden group-hover:inline absolute -top-5 left-0 z-10 bg-mantle rounded px-1 whitespace-nowrap text-sm"><span class="cursor-pointer text-primary px-1" data-accept="{index}" title="Accept">✓</span><span class="cursor-pointer text-secondary px-1" data-reject="{index}" title="Reject">✗</span></span></span>"#,
+            round_color(round)
+        ));
+    }
+
+    html.push_str("</div>");
+    rendered.set_inner_html(&html);
+}
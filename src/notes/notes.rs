@@ -0,0 +1,473 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use gloo::timers::callback::Timeout;
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{NoteMatch, Project};
+use web_sys::{Element, HtmlInputElement};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yew::virtual_dom::VNode;
+use yewdux::prelude::*;
+
+use crate::app::codex::open_codex_editor;
+use crate::app::commands::use_commands;
+use crate::app::editor::{use_editor_view, Change, Editor, EditorHandle};
+use crate::app::encryption::is_readable;
+use crate::app::notepad::markdown::Preview;
+use crate::app::{invoke, open_chapter, FileWriteData, PathArgs, State, View};
+
+/// Milliseconds after the last edit until the note is written
+const AUTOSAVE_DELAY: u32 = 1_000;
+
+/// The note shown in the notes view
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct OpenNote {
+    /// Folder of the chapter whose note it is, `None` for the note of the project
+    pub chapter: Option<String>,
+    /// Line to go to once the note is shown
+    pub line: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct SearchArgs {
+    path: String,
+    query: String,
+}
+
+/// `Note.md` of the chapter, or of the project if the chapter doesn't exist (anymore)
+fn note_path(project: &Project, chapter: Option<&String>) -> PathBuf {
+    match chapter.filter(|chapter| project.chapters.contains(chapter)) {
+        Some(chapter) => project.path.join("Chapters").join(chapter).join("Note.md"),
+        None => project.path.join("Note.md"),
+    }
+}
+
+fn note_title(project: &Project, chapter: Option<&String>) -> String {
+    match chapter.filter(|chapter| project.chapters.contains(chapter)) {
+        Some(chapter) => format!("Notes: {}", project.title(chapter)),
+        None => "Project Notes".to_string(),
+    }
+}
+
+/// Shows the note of `chapter`, or of the project, in the notes view
+pub fn open_note(state: &mut State, chapter: Option<String>, line: Option<usize>) {
+    state.note = OpenNote { chapter, line };
+    state.view = View::Notes;
+}
+
+/// Opens the chapter or codex entry a `[[link]]` names, chapters by title or folder
+pub fn follow_link(target: &str, dispatch: &Dispatch<State>, modal: &UseStateHandle<VNode>) {
+    let state = dispatch.get();
+    let target = target.to_lowercase();
+    let chapter = state.project.as_ref().and_then(|project| {
+        project.chapters.iter().position(|chapter| {
+            project.title(chapter).to_lowercase() == target || chapter.to_lowercase() == target
+        })
+    });
+    if let Some(index) = chapter {
+        dispatch.reduce_mut(|x| {
+            open_chapter(x, index);
+            x.view = View::Editor;
+        });
+    } else if let Some(entry) = state
+        .codex
+        .iter()
+        .find(|entry| entry.name.to_lowercase() == target)
+    {
+        open_codex_editor(modal, Some(entry.clone()), entry.kind);
+    }
+}
+
+/// The note the editor shows and the pending write of its changes
+#[derive(Default)]
+struct Autosave {
+    path: Option<PathBuf>,
+    /// Set while there are changes that aren't written yet
+    timeout: Option<Timeout>,
+}
+
+/// Writes the changes of the note right away, if there are any
+fn save_note(editor: &EditorHandle, autosave: &RefCell<Autosave>) {
+    let mut autosave = autosave.borrow_mut();
+    if autosave.timeout.take().is_none() {
+        return;
+    }
+    let Some(path) = autosave.path.clone() else {
+        return;
+    };
+    let write_data = FileWriteData {
+        path: path.to_string_lossy().to_string(),
+        content: editor.borrow().text(),
+    };
+    spawn_local(async move {
+        invoke("write_to_file", to_value(&write_data).unwrap()).await;
+    });
+}
+
+/// Goes to the line a search result opened the note at
+fn go_to_requested_line(editor: &EditorHandle, dispatch: &Dispatch<State>) {
+    if let Some(line) = dispatch.get().note.line {
+        editor.borrow_mut().go_to_line(line);
+        dispatch.reduce_mut(|x| x.note.line = None);
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct NoteEditorProps {
+    pub modal: UseStateHandle<VNode>,
+}
+
+/// The note of the project or of a chapter next to its preview. Changes are
+/// written shortly after typing stops and when another note is opened.
+#[function_component(NoteEditor)]
+pub fn note_editor(NoteEditorProps { modal }: &NoteEditorProps) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let editor = (*use_state(EditorHandle::default)).clone();
+    let render_ref = use_node_ref();
+    let preview = use_mut_ref(Preview::default);
+    let autosave = use_mut_ref(Autosave::default);
+    let saved = use_state(|| true);
+
+    {
+        let on_selection = {
+            let editor = editor.clone();
+            let render_ref = render_ref.clone();
+            let preview = preview.clone();
+            Callback::from(move |()| {
+                let editor = editor.borrow();
+                if let Some(rendered) = render_ref.cast::<Element>() {
+                    let line = editor.buffer().line_of(editor.selection().head);
+                    preview.borrow().reveal_line(&rendered, line);
+                }
+            })
+        };
+        use_editor_view(&editor, on_selection);
+    }
+
+    // Keeps the preview in line with the editor and writes edits a moment later
+    {
+        let editor = editor.clone();
+        let render_ref = render_ref.clone();
+        let preview = preview.clone();
+        let autosave = autosave.clone();
+        let saved = saved.clone();
+        use_effect_with(state.codex.clone(), move |codex| {
+            let codex = codex.clone();
+            preview.borrow_mut().clear();
+
+            let listener = {
+                let editor = editor.clone();
+                Callback::from(move |change: Change| {
+                    if let Some(rendered) = render_ref.cast::<Element>() {
+                        let editor = editor.borrow();
+                        let mut preview = preview.borrow_mut();
+                        preview.update(&rendered, editor.buffer(), &codex);
+                        preview.reveal_line(
+                            &rendered,
+                            editor.buffer().line_of(editor.selection().head),
+                        );
+                    }
+                    if change == Change::Document {
+                        return;
+                    }
+                    saved.set(false);
+                    let timeout = {
+                        let editor = editor.clone();
+                        let autosave = autosave.clone();
+                        let saved = saved.clone();
+                        Timeout::new(AUTOSAVE_DELAY, move || {
+                            save_note(&editor, &autosave);
+                            saved.set(true);
+                        })
+                    };
+                    autosave.borrow_mut().timeout = Some(timeout);
+                })
+            };
+            editor.set_listener(listener.clone());
+            listener.emit(Change::Document);
+        });
+    }
+
+    // Loads the note once it is opened, and again when it is changed outside of
+    // PaperSmith or the project is unlocked. Unwritten changes go first.
+    {
+        let editor = editor.clone();
+        let autosave = autosave.clone();
+        let saved = saved.clone();
+        let dispatch = dispatch.clone();
+        let path = state
+            .project
+            .as_ref()
+            .map(|project| note_path(project, state.note.chapter.as_ref()));
+        use_effect_with(
+            (
+                path,
+                state.note.line,
+                state.note_reloads,
+                is_readable(&state),
+            ),
+            move |(path, _, _, readable)| {
+                let same = autosave.borrow().path == *path;
+                // Unwritten edits aren't replaced, they are written in a moment anyway
+                if same && autosave.borrow().timeout.is_some() {
+                    go_to_requested_line(&editor, &dispatch);
+                    return;
+                }
+                save_note(&editor, &autosave);
+                saved.set(true);
+                let (Some(path), true) = (path.clone(), *readable) else {
+                    return;
+                };
+                spawn_local(async move {
+                    let content = invoke(
+                        "get_file_content",
+                        to_value(&PathArgs {
+                            path: path.to_string_lossy().to_string(),
+                        })
+                        .unwrap(),
+                    )
+                    .await
+                    .as_string()
+                    .unwrap_or_default();
+                    if !same || editor.borrow().text() != content {
+                        editor
+                            .update(|editor| Some(editor.open(path.clone(), &content, None, None)));
+                    }
+                    autosave.borrow_mut().path = Some(path);
+                    go_to_requested_line(&editor, &dispatch);
+                });
+            },
+        );
+    }
+
+    // Nothing is lost when the notes view is closed right after typing
+    {
+        let editor = editor.clone();
+        let autosave = autosave.clone();
+        use_effect_with((), move |()| move || save_note(&editor, &autosave));
+    }
+
+    {
+        let command = |action: fn(&mut Editor) -> Option<Change>| {
+            let editor = editor.clone();
+            Callback::from(move |()| editor.update(action))
+        };
+        let style = |style: &'static str| {
+            let editor = editor.clone();
+            Callback::from(move |()| editor.update(|editor| editor.wrap_selection(style)))
+        };
+        use_commands(vec![
+            ("undo", command(Editor::undo)),
+            ("redo", command(Editor::redo)),
+            ("bold", style("**")),
+            ("italic", style("_")),
+            ("underline", style("__")),
+            ("highlight", style("::")),
+        ]);
+    }
+
+    let on_render_click = {
+        let modal = modal.clone();
+        let codex = state.codex.clone();
+        let editor = editor.clone();
+        let render_ref = render_ref.clone();
+        let preview = preview.clone();
+        Callback::from(move |e: MouseEvent| {
+            let Some(target) = e.target_dyn_into::<Element>() else {
+                return;
+            };
+            if let Some(link) = target.get_attribute("data-link") {
+                follow_link(&link, &dispatch, &modal);
+                return;
+            }
+            if let Some(index) = target
+                .get_attribute("data-codex")
+                .and_then(|index| index.parse::<usize>().ok())
+            {
+                if let Some(entry) = codex.get(index) {
+                    open_codex_editor(&modal, Some(entry.clone()), entry.kind);
+                }
+                return;
+            }
+            let Some(rendered) = render_ref.cast::<Element>() else {
+                return;
+            };
+            let line = preview.borrow().line_at(&rendered, &target);
+            if let Some(line) = line {
+                editor.borrow_mut().go_to_line(line);
+            }
+        })
+    };
+
+    let Some(project) = state.project.as_ref() else {
+        return html! {};
+    };
+    let settings = state.settings.clone().unwrap_or_default();
+    let editor_style = format!(
+        "font-family: {}; line-height: {};",
+        settings.editor_font,
+        f64::from(settings.line_height) / 100.0
+    );
+
+    html! {
+        <div class="flex flex-grow bg-crust justify-evenly gap-5 px-3">
+            <div
+                class="bg-base max-h-full flex flex-1 flex-col overflow-hidden mx-2 rounded-md max-w-[45vw]"
+            >
+                <div
+                    class="border-b-[2px] border-t-0 border-x-0 border-solid flex items-center justify-between p-2"
+                >
+                    <span class="font-bold truncate">
+                        { note_title(project, state.note.chapter.as_ref()) }
+                    </span>
+                    <span class="text-sm text-subtext shrink-0">
+                        { if *saved { "Saved" } else { "Unsaved changes" } }
+                    </span>
+                </div>
+                <div
+                    class="flex-grow p-4 overflow-x-hidden outline-none break-words"
+                    ref={editor.view_ref()}
+                    style={editor_style}
+                    contenteditable="true"
+                    spellcheck={(!settings.spellcheck_language.is_empty()).to_string()}
+                    lang={settings.spellcheck_language.clone()}
+                    tabindex="0"
+                />
+            </div>
+            <div
+                class="bg-base max-h-full flex flex-1 flex-col overflow-hidden mx-2 rounded-md max-w-[45vw]"
+            >
+                <div
+                    class="border-b-[2px] border-t-0 border-x-0 border-solid flex items-center p-2 text-sm text-subtext"
+                >
+                    { "Link chapters and codex entries with [[Title]]" }
+                </div>
+                <div
+                    class="flex-grow p-4 overflow-x-hidden break-words space-y-0"
+                    ref={render_ref}
+                    onclick={on_render_click}
+                />
+            </div>
+        </div>
+    }
+}
+
+/// The notes of the project and its chapters for the sidebar, or the lines
+/// of them matching the search
+#[function_component(NoteList)]
+pub fn note_list() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let query = use_state(String::new);
+    let matches = use_state(Vec::<NoteMatch>::new);
+
+    {
+        let matches = matches.clone();
+        let path = state
+            .project
+            .as_ref()
+            .map(|project| project.path.to_string_lossy().to_string());
+        use_effect_with(((*query).clone(), path), move |(query, path)| {
+            let (Some(path), false) = (path.clone(), query.trim().is_empty()) else {
+                matches.set(Vec::new());
+                return;
+            };
+            let args = SearchArgs {
+                path,
+                query: query.clone(),
+            };
+            spawn_local(async move {
+                let matches_jsvalue = invoke("search_notes", to_value(&args).unwrap()).await;
+                matches.set(from_value(matches_jsvalue).unwrap_or_default());
+            });
+        });
+    }
+
+    let Some(project) = state.project.as_ref() else {
+        return html! {};
+    };
+
+    let on_search = {
+        let query = query.clone();
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                query.set(input.value());
+            }
+        })
+    };
+    let open = |chapter: Option<String>, line: Option<usize>| {
+        let dispatch = dispatch.clone();
+        Callback::from(move |_: MouseEvent| {
+            let chapter = chapter.clone();
+            dispatch.reduce_mut(|x| open_note(x, chapter, line));
+        })
+    };
+    let is_open = |chapter: Option<&String>| {
+        state.view == View::Notes && state.note.chapter.as_ref() == chapter
+    };
+    let item_class = |open: bool| {
+        classes!(
+            "text-start",
+            "rounded-lg",
+            "p-2",
+            "cursor-pointer",
+            "border-0",
+            "text-inherit",
+            "text-[length:inherit]",
+            if open {
+                "bg-base"
+            } else {
+                "bg-crust hover:bg-mantle"
+            }
+        )
+    };
+
+    let body = if query.trim().is_empty() {
+        std::iter::once(None)
+            .chain(project.chapters.iter().map(Some))
+            .map(|chapter| {
+                html! {
+                    <button
+                        class={item_class(is_open(chapter))}
+                        onclick={open(chapter.cloned(), None)}
+                    >
+                        { note_title(project, chapter) }
+                    </button>
+                }
+            })
+            .collect::<Html>()
+    } else if matches.is_empty() {
+        html! { <div class="text-subtext text-center my-4">{ "No notes contain this." }</div> }
+    } else {
+        matches
+            .iter()
+            .map(|found| {
+                html! {
+                    <button
+                        class={item_class(false)}
+                        onclick={open(found.chapter.clone(), Some(found.line))}
+                    >
+                        <div class="font-bold text-sm">
+                            { format!("{}, line {}", note_title(project, found.chapter.as_ref()), found.line + 1) }
+                        </div>
+                        <div class="text-subtext text-sm">{ found.text.clone() }</div>
+                    </button>
+                }
+            })
+            .collect::<Html>()
+    };
+
+    html! {
+        <div class="overflow-scroll grow shrink p-2 flex flex-col gap-1">
+            <input
+                type="search"
+                value={(*query).clone()}
+                oninput={on_search}
+                placeholder="Search Notes"
+                class="bg-crust text-text p-2 mb-1 rounded-lg border-0 font-standard text-base"
+            />
+            { body }
+        </div>
+    }
+}
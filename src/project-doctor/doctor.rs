@@ -0,0 +1,121 @@
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{DoctorReport, Project, ProjectProblem};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, ProjectProps, State};
+
+/// Lists the problems of the project file and the chapter folders `project`
+/// finds, empty if the project is fine
+pub async fn check_project(project: Project) -> Vec<ProjectProblem> {
+    let problems_jsvalue = invoke(
+        "check_project",
+        to_value(&ProjectProps { project }).unwrap(),
+    )
+    .await;
+    from_value(problems_jsvalue).unwrap_or_default()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ProjectDoctorProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Checks that the chapter list matches the chapter folders and repairs it
+#[function_component(ProjectDoctor)]
+pub fn project_doctor(
+    ProjectDoctorProps {
+        closing_callback: on_close,
+    }: &ProjectDoctorProps,
+) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let problems = use_state(|| None::<Vec<ProjectProblem>>);
+    let changes = use_state(|| None::<Vec<String>>);
+
+    {
+        let problems = problems.clone();
+        let project = state.project.clone();
+        use_effect_with((), move |()| {
+            if let Some(project) = project {
+                spawn_local(async move {
+                    problems.set(Some(check_project(project).await));
+                });
+            }
+        });
+    }
+
+    let on_repair = {
+        let changes = changes.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(project) = state.project.clone() else {
+                return;
+            };
+            let changes = changes.clone();
+            let dispatch = dispatch.clone();
+            spawn_local(async move {
+                let report_jsvalue = invoke(
+                    "repair_project",
+                    to_value(&ProjectProps { project }).unwrap(),
+                )
+                .await;
+                if let Ok(report) = from_value::<DoctorReport>(report_jsvalue) {
+                    dispatch.reduce_mut(|state| state.project = Some(report.project));
+                    changes.set(Some(report.changes));
+                }
+            });
+        })
+    };
+
+    let list = |lines: Vec<String>| {
+        html! {
+            <ul class="m-0">
+                { lines.into_iter().map(|x| html! { <li>{ x }</li> }).collect::<Html>() }
+            </ul>
+        }
+    };
+
+    let body = match (changes.as_ref(), problems.as_ref()) {
+        (Some(changes), _) => html! {
+            <>
+                <div class="font-semibold">{ "Repaired:" }</div>
+                { list(changes.clone()) }
+            </>
+        },
+        (None, None) => html! { <div>{ "Checking the project..." }</div> },
+        (None, Some(problems)) if problems.is_empty() => html! {
+            <div>{ "No problems found, the chapter list matches the chapter folders." }</div>
+        },
+        (None, Some(problems)) => html! {
+            <>
+                <div class="font-semibold">{ "The project file doesn't match the project folder:" }</div>
+                { list(problems.iter().map(ToString::to_string).collect()) }
+            </>
+        },
+    };
+    let can_repair = changes.is_none() && problems.as_ref().is_some_and(|x| !x.is_empty());
+
+    html! {
+        <>
+            <div class="text-xl font-bold">{ "Check Project" }</div>
+            <br />
+            { body }
+            <div class="flex justify-end w-full pt-8">
+                if can_repair {
+                    <button
+                        onclick={on_repair}
+                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                    >
+                        { "Repair" }
+                    </button>
+                }
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </>
+    }
+}
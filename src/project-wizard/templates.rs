@@ -0,0 +1,245 @@
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{Project, ProjectTemplate, TemplateSettings};
+use wasm_bindgen::JsValue;
+use web_sys::HtmlInputElement;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, State};
+
+#[derive(Serialize)]
+struct NameArgs {
+    name: String,
+}
+
+#[derive(Serialize)]
+struct SaveTemplateArgs {
+    project: Project,
+    name: String,
+    description: String,
+    settings: TemplateSettings,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TemplatePickerProps {
+    /// The chosen template, `None` for an empty project
+    pub selected: UseStateHandle<Option<ProjectTemplate>>,
+}
+
+/// Lists the templates a project can start from, with a preview of the chosen one
+#[function_component(TemplatePicker)]
+pub fn template_picker(TemplatePickerProps { selected }: &TemplatePickerProps) -> Html {
+    let templates = use_state(Vec::<ProjectTemplate>::new);
+    let reload = use_state(|| 0_u32);
+
+    {
+        let templates = templates.clone();
+        use_effect_with(*reload, move |_| {
+            spawn_local(async move {
+                let templates_jsvalue = invoke("list_templates", JsValue::NULL).await;
+                templates.set(from_value(templates_jsvalue).unwrap_or_default());
+            });
+        });
+    }
+
+    let card = |label: String, template: Option<ProjectTemplate>| {
+        let is_selected = selected.as_ref().map(|x| &x.name) == template.as_ref().map(|x| &x.name);
+        let on_select = {
+            let selected = selected.clone();
+            let template = template.clone();
+            Callback::from(move |_: MouseEvent| selected.set(template.clone()))
+        };
+        let on_delete = template.as_ref().filter(|x| !x.builtin).map(|template| {
+            let name = template.name.clone();
+            let selected = selected.clone();
+            let reload = reload.clone();
+            Callback::from(move |e: MouseEvent| {
+                e.stop_propagation();
+                let args = NameArgs { name: name.clone() };
+                if selected.as_ref().is_some_and(|x| x.name == name) {
+                    selected.set(None);
+                }
+                let reload = reload.clone();
+                spawn_local(async move {
+                    invoke("delete_template", to_value(&args).unwrap()).await;
+                    reload.set(*reload + 1);
+                });
+            })
+        });
+
+        html! {
+            <div
+                onclick={on_select}
+                class={classes!("flex", "items-center", "rounded-lg", "px-2", "py-1", "m-1", "cursor-pointer",
+                    if is_selected { "bg-primary text-crust" } else { "bg-crust hover:bg-mantle" }
+                )}
+            >
+                { label }
+                if let Some(on_delete) = on_delete {
+                    <button
+                        onclick={on_delete}
+                        title="Delete this template"
+                        class="ml-2 px-1 rounded-lg border-0 bg-transparent text-inherit cursor-pointer hover:text-secondary"
+                    >
+                        { "×" }
+                    </button>
+                }
+            </div>
+        }
+    };
+
+    let cards = std::iter::once(card("Empty".to_string(), None))
+        .chain(
+            templates
+                .iter()
+                .map(|template| card(template.name.clone(), Some(template.clone()))),
+        )
+        .collect::<Html>();
+
+    let preview = match selected.as_ref() {
+        None => html! {
+            <div class="text-subtext">{ "An empty project, chapters are added from the sidebar." }</div>
+        },
+        Some(template) => {
+            let chapters = template
+                .chapters
+                .iter()
+                .map(|chapter| chapter.name.clone())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let codex = template
+                .codex
+                .iter()
+                .map(|entry| format!("{} ({})", entry.name, entry.kind))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let settings = template.settings.describe();
+            html! {
+                <div class="flex flex-col text-sm">
+                    <div class="text-base pb-2">{ &template.description }</div>
+                    if !chapters.is_empty() {
+                        <div><span class="font-semibold">{ "Chapters: " }</span>{ chapters }</div>
+                    }
+                    if !codex.is_empty() {
+                        <div><span class="font-semibold">{ "Codex: " }</span>{ codex }</div>
+                    }
+                    if !template.note.trim().is_empty() {
+                        <div><span class="font-semibold">{ "Project note: " }</span>{ "prepared" }</div>
+                    }
+                    if !settings.is_empty() {
+                        <div class="font-semibold pt-2">{ "Changes these settings:" }</div>
+                        <ul class="m-0">
+                            { settings.iter().map(|x| html! { <li>{ x }</li> }).collect::<Html>() }
+                        </ul>
+                    }
+                </div>
+            }
+        }
+    };
+
+    html! {
+        <>
+            <div class="flex flex-wrap -mx-1">{ cards }</div>
+            <div class="rounded-lg bg-crust p-2 mt-2 min-h-16">{ preview }</div>
+        </>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SaveTemplateProps {
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Saves the open project with its chapters, notes, codex and editor settings
+/// as a template for new projects
+#[function_component(SaveTemplateMenu)]
+pub fn save_template_menu(
+    SaveTemplateProps {
+        closing_callback: on_close,
+    }: &SaveTemplateProps,
+) -> Html {
+    let (state, _dispatch) = use_store::<State>();
+    let name = use_state(String::new);
+    let description = use_state(String::new);
+    let error_message = use_state(String::new);
+
+    let input_handler = |value: UseStateHandle<String>| {
+        Callback::from(move |e: InputEvent| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                value.set(input.value());
+            }
+        })
+    };
+
+    let on_confirm = {
+        let on_close = on_close.clone();
+        let name = name.clone();
+        let description = description.clone();
+        let error_message = error_message.clone();
+        Callback::from(move |_: MouseEvent| {
+            let Some(project) = state.project.clone() else {
+                return;
+            };
+            let args = SaveTemplateArgs {
+                project,
+                name: (*name).clone(),
+                description: (*description).clone(),
+                settings: TemplateSettings::from_settings(
+                    &state.settings.clone().unwrap_or_default(),
+                ),
+            };
+            let on_close = on_close.clone();
+            let error_message = error_message.clone();
+            spawn_local(async move {
+                let message = invoke("save_project_as_template", to_value(&args).unwrap())
+                    .await
+                    .as_string()
+                    .unwrap_or_default();
+                if message.is_empty() {
+                    on_close.emit(MouseEvent::new("Dummy").unwrap());
+                } else {
+                    error_message.set(message);
+                }
+            });
+        })
+    };
+
+    html! {
+        <>
+            <div class="text-xl font-bold">{ "Save Project as Template" }</div>
+            <div class="text-sm pt-2">
+                { "New projects made from it start with the chapters, notes, codex entries and editor settings of this project." }
+            </div>
+            <br />
+            <div class="font-semibold">{ "Name:" }</div>
+            <input
+                oninput={input_handler(name)}
+                class="w-full bg-crust text-text p-2 my-2 rounded-lg border-0 font-standard text-base"
+            />
+            <div class="font-semibold">{ "Description:" }</div>
+            <input
+                oninput={input_handler(description)}
+                class="w-full bg-crust text-text p-2 my-2 rounded-lg border-0 font-standard text-base"
+            />
+            <div class="flex justify-end w-full pt-8">
+                <div class="text-text underline decoration-primary break-words mr-auto">
+                    { (*error_message).clone() }
+                </div>
+                <button
+                    onclick={on_confirm}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Save" }
+                </button>
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                >
+                    { "Close" }
+                </button>
+            </div>
+        </>
+    }
+}
@@ -1,7 +1,8 @@
-use crate::app::{invoke, State};
+use crate::app::settings::save_settings;
+use crate::app::{invoke, open_project, State};
 use serde::Serialize;
 use serde_wasm_bindgen::to_value;
-use shared::Project;
+use shared::{Project, ProjectTemplate};
 use std::path::PathBuf;
 use wasm_bindgen::JsValue;
 use yew::platform::spawn_local;
@@ -10,6 +11,11 @@ use yew::prelude::*;
 use yew_icons::{Icon, IconId};
 use yewdux::prelude::*;
 
+#[path = "templates.rs"]
+mod templates;
+pub use templates::SaveTemplateMenu;
+use templates::TemplatePicker;
+
 #[derive(Properties, PartialEq)]
 pub struct Props {
     pub closing_callback: Callback<MouseEvent>,
@@ -26,6 +32,12 @@ pub struct PathArgs {
     pub path: String,
 }
 
+#[derive(Serialize)]
+struct CreateProjectArgs {
+    path: String,
+    template: Option<ProjectTemplate>,
+}
+
 #[function_component(ProjectWizard)]
 pub fn project_wizard(
     Props {
@@ -42,6 +54,7 @@ pub fn project_wizard(
     let is_hovered = use_state(|| false);
     let is_data_valid = use_state(|| true);
     let error_message = use_state(String::new);
+    let template = use_state(|| None::<ProjectTemplate>);
 
     let on_title_input = text_input_handler(title.clone());
     let on_location_input = text_input_handler(location.clone());
@@ -183,10 +196,12 @@ pub fn project_wizard(
 
     let on_confirm = {
         let on_close = on_close.clone();
+        let template = template.clone();
         Callback::from(move |_| {
             let location = location.clone();
             let title = title.clone();
             let dispatch = dispatch.clone();
+            let template = (*template).clone();
             if !*is_data_valid {
                 return;
             }
@@ -194,16 +209,27 @@ pub fn project_wizard(
                 let complete_path = PathBuf::from(&*location).join(&*title);
                 let project_jsvalue = invoke(
                     "create_project",
-                    serde_wasm_bindgen::to_value(&PathArgs {
+                    serde_wasm_bindgen::to_value(&CreateProjectArgs {
                         path: complete_path.into_os_string().into_string().unwrap(),
+                        template: template.clone(),
                     })
                     .unwrap(),
                 )
                 .await;
                 let project_or_none: Option<Project> =
                     serde_wasm_bindgen::from_value(project_jsvalue).unwrap();
-                if project_or_none.is_some() {
-                    dispatch.reduce_mut(|state| state.project = project_or_none);
+                if let Some(project) = project_or_none {
+                    dispatch.reduce_mut(|state| open_project(state, project));
+
+                    // The template settings replace the current ones
+                    if let Some(template) = template.filter(|x| x.settings != Default::default()) {
+                        dispatch.reduce_mut(|state| {
+                            template
+                                .settings
+                                .apply(state.settings.get_or_insert_with(Default::default));
+                        });
+                        save_settings(dispatch.get().settings.clone().unwrap_or_default());
+                    }
                 }
             });
             on_close.emit(MouseEvent::new("Dummy").unwrap());
@@ -243,6 +269,9 @@ pub fn project_wizard(
                     { icon }
                 </button>
             </div>
+            <br />
+            <div class="font-semibold">{ "Template:" }</div>
+            <TemplatePicker selected={template} />
             <div id="footer" class="flex justify-end w-full pt-8">
                 <div class="text-text underline decoration-primary break-words mr-auto">
                     { (*error_message).clone() }
@@ -0,0 +1,303 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use chrono::{Local, TimeZone};
+use gloo::timers::callback::Interval;
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{diff, text_checksum, Diff, RecoveredChapter, RecoveredText};
+use wasm_bindgen::JsValue;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::editor::EditorHandle;
+use crate::app::encryption::is_readable;
+use crate::app::file_watcher::reload_chapter;
+use crate::app::modal::Modal;
+use crate::app::{invoke, FileWriteData, PathArgs, State};
+
+/// Milliseconds between writes of the unsaved text to the journal
+const JOURNAL_INTERVAL: u32 = 5_000;
+
+#[derive(Serialize)]
+struct IdArgs {
+    id: String,
+}
+
+/// Writes the unsaved text of the open chapter to the recovery journal every few
+/// seconds, so it survives a crash or a power cut
+#[hook]
+pub fn use_recovery_journal(editor: EditorHandle) {
+    let (_state, dispatch) = use_store::<State>();
+
+    use_effect_with((), move |()| {
+        // Checksum of the text last written, unchanged text isn't written again
+        let journaled: Rc<RefCell<Option<u64>>> = Rc::default();
+        let interval = Interval::new(JOURNAL_INTERVAL, move || {
+            let state = dispatch.get();
+            if !state.changes || !is_readable(&state) {
+                *journaled.borrow_mut() = None;
+                return;
+            }
+            let editor = editor.borrow();
+            let Some(path) = editor.active() else {
+                return;
+            };
+            let content = editor.text();
+            let checksum = text_checksum([content.as_str()]);
+            if *journaled.borrow() == Some(checksum) {
+                return;
+            }
+            *journaled.borrow_mut() = Some(checksum);
+
+            let args = FileWriteData {
+                path: path.to_string_lossy().to_string(),
+                content,
+            };
+            spawn_local(async move {
+                invoke("write_journal", to_value(&args).unwrap()).await;
+            });
+        });
+        move || drop(interval)
+    });
+}
+
+fn format_saved(saved: i64) -> String {
+    Local
+        .timestamp_opt(saved, 0)
+        .single()
+        .map_or_else(String::new, |time| {
+            time.format("%Y-%m-%d %H:%M").to_string()
+        })
+}
+
+/// The saved text with the recovered one worked in, inserts green and deletes struck out
+fn render_diff(text: &RecoveredText) -> Html {
+    let mut position = 0;
+    diff(&text.saved, &text.recovered)
+        .into_iter()
+        .map(|part| match part {
+            Diff::Equal(length) => {
+                position += length;
+                html! { <span>{ &text.saved[position - length..position] }</span> }
+            }
+            Diff::Delete(length) => {
+                position += length;
+                html! {
+                    <span class="text-red-500 line-through">
+                        { &text.saved[position - length..position] }
+                    </span>
+                }
+            }
+            Diff::Insert(inserted) => html! { <span class="text-green-500">{ inserted }</span> },
+        })
+        .collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct RecoveryPromptProps {
+    pub editor: EditorHandle,
+}
+
+/// Offers the unsaved text left over from a session that didn't close properly,
+/// shown once on start when there is any
+#[function_component(RecoveryPrompt)]
+pub fn recovery_prompt(RecoveryPromptProps { editor }: &RecoveryPromptProps) -> Html {
+    let (_state, dispatch) = use_store::<State>();
+    let recovered = use_state(Vec::<RecoveredChapter>::new);
+    let compared = use_state(|| None::<(String, RecoveredText)>);
+    let message = use_state(String::new);
+
+    {
+        let recovered = recovered.clone();
+        use_effect_with((), move |()| {
+            spawn_local(async move {
+                let recovered_jsvalue = invoke("list_recovered", JsValue::NULL).await;
+                recovered.set(from_value(recovered_jsvalue).unwrap_or_default());
+            });
+        });
+    }
+
+    if recovered.is_empty() {
+        return html! {};
+    }
+
+    let forget = {
+        let recovered = recovered.clone();
+        let compared = compared.clone();
+        move |id: &str| {
+            recovered.set(recovered.iter().filter(|x| x.id != id).cloned().collect());
+            if compared.as_ref().is_some_and(|(x, _)| x == id) {
+                compared.set(None);
+            }
+        }
+    };
+
+    let on_compare = {
+        let compared = compared.clone();
+        let message = message.clone();
+        Callback::from(move |id: String| {
+            let compared = compared.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                let text_jsvalue = invoke(
+                    "read_recovered",
+                    to_value(&IdArgs { id: id.clone() }).unwrap(),
+                )
+                .await;
+                match from_value::<Option<RecoveredText>>(text_jsvalue) {
+                    Ok(Some(text)) => {
+                        message.set(String::new());
+                        compared.set(Some((id, text)));
+                    }
+                    _ => message.set(
+                        "The recovered text can't be read, unlock the project first.".to_string(),
+                    ),
+                }
+            });
+        })
+    };
+
+    let on_restore = {
+        let editor = editor.clone();
+        let message = message.clone();
+        let forget = forget.clone();
+        Callback::from(move |chapter: RecoveredChapter| {
+            let editor = editor.clone();
+            let dispatch = dispatch.clone();
+            let message = message.clone();
+            let forget = forget.clone();
+            spawn_local(async move {
+                let error = invoke(
+                    "restore_recovered",
+                    to_value(&IdArgs {
+                        id: chapter.id.clone(),
+                    })
+                    .unwrap(),
+                )
+                .await
+                .as_string()
+                .unwrap_or_default();
+                if !error.is_empty() {
+                    message.set(error);
+                    return;
+                }
+                message.set(format!("Restored \"{}\".", chapter.title));
+                forget(&chapter.id);
+
+                // The open chapter shows the restored text right away
+                let chapter_path = chapter.project.join("Chapters").join(&chapter.chapter);
+                if editor.borrow().active() != Some(chapter_path.as_path()) {
+                    return;
+                }
+                let content = invoke(
+                    "get_file_content",
+                    to_value(&PathArgs {
+                        path: chapter_path
+                            .join("Content.md")
+                            .to_string_lossy()
+                            .to_string(),
+                    })
+                    .unwrap(),
+                )
+                .await
+                .as_string()
+                .unwrap_or_default();
+                reload_chapter(chapter_path, &content, &editor, &dispatch);
+            });
+        })
+    };
+
+    let on_discard = Callback::from(move |id: String| {
+        forget(&id);
+        spawn_local(async move {
+            invoke("discard_recovered", to_value(&IdArgs { id }).unwrap()).await;
+        });
+    });
+
+    // Whatever is left is offered again on the next start
+    let on_later = {
+        let recovered = recovered.clone();
+        Callback::from(move |_: MouseEvent| recovered.set(Vec::new()))
+    };
+
+    let button_class = "rounded-lg px-2 py-1 ml-2 bg-primary text-crust hover:scale-105 border-0";
+
+    html! {
+        <Modal
+            content={html! {
+                <>
+                    <div class="text-xl font-bold">{ "Recovered Unsaved Changes" }</div>
+                    <br />
+                    <div>
+                        { "PaperSmith didn't close properly last time. These chapters had changes that weren't saved yet." }
+                    </div>
+                    <div class="flex flex-col gap-2 pt-4">
+                        { for recovered.iter().map(|chapter| {
+                            let project = chapter
+                                .project
+                                .file_name()
+                                .map(|x| x.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            html! {
+                                <div class="flex items-center bg-crust rounded-lg p-2">
+                                    <div class="grow">
+                                        <div class="font-bold">{ &chapter.title }</div>
+                                        <div class="text-sm text-subtext">
+                                            { format!("{project}, {}", format_saved(chapter.saved)) }
+                                        </div>
+                                    </div>
+                                    <button
+                                        onclick={on_compare.reform({
+                                            let id = chapter.id.clone();
+                                            move |_| id.clone()
+                                        })}
+                                        class={button_class}
+                                    >
+                                        { "Compare" }
+                                    </button>
+                                    <button
+                                        onclick={on_restore.reform({
+                                            let chapter = chapter.clone();
+                                            move |_| chapter.clone()
+                                        })}
+                                        class={button_class}
+                                    >
+                                        { "Restore" }
+                                    </button>
+                                    <button
+                                        onclick={on_discard.reform({
+                                            let id = chapter.id.clone();
+                                            move |_| id.clone()
+                                        })}
+                                        class="rounded-lg px-2 py-1 ml-2 bg-secondary text-crust hover:scale-105 border-0"
+                                    >
+                                        { "Discard" }
+                                    </button>
+                                </div>
+                            }
+                        }) }
+                    </div>
+                    if let Some((_, text)) = compared.as_ref() {
+                        <div class="pt-4 text-sm text-subtext">
+                            { "Compared to the saved chapter, the recovered text adds the green parts and removes the struck out ones." }
+                        </div>
+                        <div class="mt-2 max-h-[40vh] overflow-auto bg-crust rounded-lg p-2 whitespace-pre-wrap">
+                            { render_diff(text) }
+                        </div>
+                    }
+                    <div class="pt-4 text-sm text-subtext">{ (*message).clone() }</div>
+                    <div class="flex justify-end w-full pt-8">
+                        <button
+                            onclick={on_later}
+                            class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                        >
+                            { "Decide Later" }
+                        </button>
+                    </div>
+                </>
+            }}
+        />
+    }
+}
@@ -0,0 +1,158 @@
+use shared::{command_label, normalize_shortcut, Keymap, DEFAULT_KEYMAP};
+use web_sys::HtmlElement;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::commands::shortcut_of_event;
+use crate::app::State;
+
+const MODIFIER_KEYS: [&str; 4] = ["Control", "Alt", "Shift", "Meta"];
+
+/// Lists every command with its shortcuts. Clicking a shortcut records a new
+/// one, Backspace removes it and Escape cancels.
+#[function_component(KeybindingEditor)]
+pub fn keybinding_editor() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let recording = use_state(|| None::<&'static str>);
+    let recorder_ref = use_node_ref();
+
+    let settings = state.settings.clone().unwrap_or_default();
+    let keymap = Keymap::new(&settings.keybindings);
+    let conflicts = keymap.conflicts();
+
+    // The recorder takes the focus as soon as it shows up
+    {
+        let recorder_ref = recorder_ref.clone();
+        use_effect_with(*recording, move |_| {
+            if let Some(recorder) = recorder_ref.cast::<HtmlElement>() {
+                let _ = recorder.focus();
+            }
+        });
+    }
+
+    let set_binding = {
+        let dispatch = dispatch.clone();
+        move |command: &'static str, shortcuts: Option<Vec<String>>| {
+            dispatch.reduce_mut(|state| {
+                let settings = state.settings.get_or_insert_with(Default::default);
+                match shortcuts {
+                    Some(shortcuts) => settings.keybindings.insert(command.to_string(), shortcuts),
+                    None => settings.keybindings.remove(command),
+                };
+            });
+        }
+    };
+
+    let on_record = {
+        let recording = recording.clone();
+        let set_binding = set_binding.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let Some(command) = *recording else {
+                return;
+            };
+            e.prevent_default();
+            e.stop_propagation();
+
+            match e.key().as_str() {
+                key if MODIFIER_KEYS.contains(&key) => return,
+                "Escape" => {}
+                "Backspace" | "Delete" => set_binding(command, Some(Vec::new())),
+                _ => {
+                    let Some(shortcut) = normalize_shortcut(&shortcut_of_event(&e)) else {
+                        return;
+                    };
+                    set_binding(command, Some(vec![shortcut]));
+                }
+            }
+            recording.set(None);
+        })
+    };
+
+    let rows = DEFAULT_KEYMAP
+        .iter()
+        .map(|(command, label, _)| {
+            let command = *command;
+            let shortcuts = keymap.shortcuts(command);
+            let conflicting: Vec<&str> = shortcuts
+                .iter()
+                .filter_map(|shortcut| conflicts.get(shortcut))
+                .flatten()
+                .filter(|other| **other != command)
+                .filter_map(|other| command_label(other))
+                .collect();
+
+            let on_start = {
+                let recording = recording.clone();
+                Callback::from(move |_: MouseEvent| recording.set(Some(command)))
+            };
+            let on_reset = {
+                let set_binding = set_binding.clone();
+                Callback::from(move |_: MouseEvent| set_binding(command, None))
+            };
+
+            let shortcut_label = if shortcuts.is_empty() {
+                "None".to_string()
+            } else {
+                shortcuts.join(", ")
+            };
+
+            html! {
+                <div class="flex flex-col py-1">
+                    <div class="flex justify-between items-center">
+                        <div>{ *label }</div>
+                        <div class="flex items-center">
+                            if *recording == Some(command) {
+                                <button
+                                    ref={recorder_ref.clone()}
+                                    onkeydown={on_record.clone()}
+                                    onblur={let recording = recording.clone(); Callback::from(move |_: FocusEvent| recording.set(None))}
+                                    // Keeps the shortcuts from running while recording
+                                    data-recording="true"
+                                    class="rounded-lg px-2 py-1 bg-primary text-crust border-0 min-w-32"
+                                >
+                                    { "Press keys…" }
+                                </button>
+                            } else {
+                                <button
+                                    onclick={on_start}
+                                    title="Click and press the new shortcut"
+                                    class={classes!("rounded-lg", "px-2", "py-1", "bg-crust", "hover:bg-mantle", "text-inherit", "border-0", "cursor-pointer", "min-w-32",
+                                        (!conflicting.is_empty()).then_some("outline outline-2 outline-secondary")
+                                    )}
+                                >
+                                    { shortcut_label }
+                                </button>
+                            }
+                            if settings.keybindings.contains_key(command) {
+                                <button
+                                    onclick={on_reset}
+                                    title="Back to the default shortcut"
+                                    class="rounded-lg px-2 py-1 ml-2 bg-crust hover:bg-mantle text-inherit border-0 cursor-pointer"
+                                >
+                                    { "Reset" }
+                                </button>
+                            }
+                        </div>
+                    </div>
+                    if !conflicting.is_empty() {
+                        <div class="text-sm text-secondary text-end">
+                            { format!("Also bound to {}", conflicting.join(", ")) }
+                        </div>
+                    }
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html! {
+        <div class="flex flex-col w-full pt-8">
+            <div class="font-bold">{ "Keyboard Shortcuts" }</div>
+            if !conflicts.is_empty() {
+                <div class="text-sm text-secondary">
+                    { "Some shortcuts are bound twice, only the first command runs." }
+                </div>
+            }
+            { rows }
+        </div>
+    }
+}
@@ -1,9 +1,11 @@
-use gloo::utils::document;
+use std::rc::Rc;
+
 use serde::Serialize;
-use serde_json::json;
+use shared::ExportFormat;
+use shared::LogLevel;
 use shared::Settings;
-use wasm_bindgen::{JsCast, JsValue};
-use web_sys::HtmlDocument;
+use shared::SYSTEM_THEME;
+use wasm_bindgen::JsValue;
 use web_sys::HtmlSelectElement;
 use yew::platform::spawn_local;
 use yew::prelude::*;
@@ -12,7 +14,13 @@ use yewdux::prelude::*;
 use crate::app::invoke;
 use crate::app::State;
 
-use shared::FileWriteData;
+#[path = "keybindings.rs"]
+mod keybindings;
+use keybindings::KeybindingEditor;
+
+#[path = "themes.rs"]
+pub mod themes;
+use themes::{apply_theme, theme_names, ThemeManager};
 
 #[derive(Properties, PartialEq)]
 pub struct SettingsProps {
@@ -20,10 +28,79 @@ pub struct SettingsProps {
 }
 
 #[derive(Serialize)]
-pub struct LogArgs {
-    pub msg: String,
+struct SettingsArgs {
+    path: String,
+    settings: Settings,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SettingsErrorsProps {
+    pub errors: Vec<String>,
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Tells what was wrong with `settings.json` when it was loaded
+#[function_component(SettingsErrors)]
+pub fn settings_errors(
+    SettingsErrorsProps {
+        errors,
+        closing_callback: on_close,
+    }: &SettingsErrorsProps,
+) -> Html {
+    html!(
+        <>
+            <div class="text-xl font-bold">{ "Some settings could not be loaded" }</div>
+            <div class="pt-4">{ "They were reset to their defaults:" }</div>
+            <ul class="text-sm">
+                { errors.iter().map(|error| html! { <li>{ error }</li> }).collect::<Html>() }
+            </ul>
+            <div class="flex justify-end w-full pt-8">
+                <button
+                    onclick={on_close}
+                    class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                >
+                    { "Ok" }
+                </button>
+            </div>
+        </>
+    )
 }
 
+/// Writes the settings into the data folder
+pub fn save_settings(settings: Settings) {
+    spawn_local(async move {
+        let path_jsvalue = invoke("get_data_dir", JsValue::null()).await;
+
+        let mut path = path_jsvalue.as_string().expect("Cast failed").clone();
+
+        path.push_str("/PaperSmith");
+
+        invoke(
+            "write_settings",
+            serde_wasm_bindgen::to_value(&SettingsArgs { path, settings }).unwrap(),
+        )
+        .await;
+    });
+}
+
+const EDITOR_FONTS: [(&str, &str); 5] = [
+    ("Arial, sans-serif", "Arial"),
+    ("Verdana, sans-serif", "Verdana"),
+    ("Georgia, serif", "Georgia"),
+    ("\"Times New Roman\", serif", "Times New Roman"),
+    ("\"Courier New\", monospace", "Courier New"),
+];
+
+const SPELLCHECK_LANGUAGES: [(&str, &str); 7] = [
+    ("", "Off"),
+    ("en-US", "English (US)"),
+    ("en-GB", "English (UK)"),
+    ("de-DE", "German"),
+    ("fr-FR", "French"),
+    ("es-ES", "Spanish"),
+    ("it-IT", "Italian"),
+];
+
 #[function_component(SettingsMenu)]
 pub fn settings_menu(
     SettingsProps {
@@ -39,44 +116,13 @@ pub fn settings_menu(
         let state = state.clone();
 
         Callback::from(move |_| {
-            let state = state.clone();
-
-            spawn_local(async move {
-                let settings = state
-                    .settings
-                    .clone()
-                    .unwrap_or_else(|| Settings::default());
-
-                switch_theme(settings.theme.clone());
-
-                let content = json!({
-                    "theme": settings.theme,
-                    "interval": settings.interval,
-                })
-                .to_string();
-
-                let name = String::from("settings");
-
-                let path_jsvalue = invoke("get_data_dir", JsValue::null()).await;
-
-                let mut path = path_jsvalue.as_string().expect("Cast failed").clone();
-
-                path.push_str("/PaperSmith");
-
-                gloo_console::log!(path.clone());
-
-                let settings = FileWriteData {
-                    path,
-                    name,
-                    content,
-                };
+            let settings = state
+                .settings
+                .clone()
+                .unwrap_or_else(|| Settings::default());
 
-                invoke(
-                    "write_to_json",
-                    serde_wasm_bindgen::to_value(&settings).unwrap(),
-                )
-                .await;
-            });
+            apply_theme(settings.active_theme(state.dark_mode), &state.themes);
+            save_settings(settings);
 
             on_close.emit(MouseEvent::new("Dummy").unwrap());
         })
@@ -86,16 +132,57 @@ pub fn settings_menu(
 
     let interval_ref = use_node_ref();
 
-    let themes = [
-        "Light".to_string(),
-        "Light Dark".to_string(),
-        "Medium".to_string(),
-        "Dark".to_string(),
-        "Very Dark".to_string(),
-    ];
+    let themes = theme_names(&state.themes);
+
+    let font_ref = use_node_ref();
+    let line_height_ref = use_node_ref();
+    let page_width_ref = use_node_ref();
+    let language_ref = use_node_ref();
+    let export_ref = use_node_ref();
+    let backup_ref = use_node_ref();
+    let lock_ref = use_node_ref();
+    let log_level_ref = use_node_ref();
+
+    let on_font_change = update_on_change(&state, &dispatch, &font_ref, |settings, value| {
+        settings.editor_font = value;
+    });
+    let on_line_height_change =
+        update_on_change(&state, &dispatch, &line_height_ref, |settings, value| {
+            settings.line_height = value.parse().unwrap_or(settings.line_height);
+        });
+    let on_page_width_change =
+        update_on_change(&state, &dispatch, &page_width_ref, |settings, value| {
+            settings.page_width = value.parse().unwrap_or(settings.page_width);
+        });
+    let on_language_change =
+        update_on_change(&state, &dispatch, &language_ref, |settings, value| {
+            settings.spellcheck_language = value;
+        });
+    let on_export_change = update_on_change(&state, &dispatch, &export_ref, |settings, value| {
+        settings.export_format =
+            ExportFormat::from_extension(&value).unwrap_or(settings.export_format);
+    });
+    let on_backup_change = update_on_change(&state, &dispatch, &backup_ref, |settings, value| {
+        settings.backup_retention = value.parse().unwrap_or(settings.backup_retention);
+    });
+    let on_lock_change = update_on_change(&state, &dispatch, &lock_ref, |settings, value| {
+        settings.lock_after = value.parse().unwrap_or(settings.lock_after);
+    });
+    let on_log_level_change =
+        update_on_change(&state, &dispatch, &log_level_ref, |settings, value| {
+            settings.log_level = LogLevel::from_label(&value).unwrap_or(settings.log_level);
+        });
+
+    let light_ref = use_node_ref();
+    let dark_ref = use_node_ref();
 
     let intervals = [0, 1, 3, 5, 10, 15, 30];
 
+    let retention_ref = use_node_ref();
+    let history_ref = use_node_ref();
+
+    let retentions = [0, 7, 14, 30, 90];
+
     let onchange = {
         let state = state.clone();
         let dispatch = dispatch.clone();
@@ -121,13 +208,7 @@ pub fn settings_menu(
 
                 dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
 
-                spawn_local(async move {
-                    let msg = LogArgs {
-                        msg: format!("{prev:?} -> {value:?}"),
-                    };
-                    gloo_console::log!(format!("{value:?}"));
-                    invoke("log", serde_wasm_bindgen::to_value(&msg).unwrap()).await;
-                });
+                log::info!("Theme: {prev:?} -> {value:?}");
             }
         })
     };
@@ -149,33 +230,168 @@ pub fn settings_menu(
             if let Some(select) = select {
                 let value = select.value();
 
-                let prev = settings.interval;
+                let prev = settings.autosave_interval;
 
                 let mut temp_settings = state.settings.as_ref().unwrap().clone();
 
-                temp_settings.interval = select.value().parse::<u32>().unwrap() * 60 * 1000;
+                temp_settings.autosave_interval = select.value().parse::<u32>().unwrap();
+
+                dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
+
+                log::info!("Autosave interval: {prev:?} -> {value:?}");
+            }
+        })
+    };
+
+    let on_retention_change = {
+        let state = state.clone();
+        let dispatch = dispatch.clone();
+        let select_ref = retention_ref.clone();
+
+        Callback::from(move |_| {
+            let select = select_ref.cast::<HtmlSelectElement>();
+
+            if let Some(select) = select {
+                let mut temp_settings = state.settings.clone().unwrap_or_default();
+
+                temp_settings.trash_retention = select.value().parse::<u32>().unwrap_or_default();
 
                 dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
+            }
+        })
+    };
 
-                spawn_local(async move {
-                    let msg = LogArgs {
-                        msg: format!("{prev:?} -> {value:?}"),
-                    };
-                    gloo_console::log!(format!("{value:?}"));
-                    invoke("log", serde_wasm_bindgen::to_value(&msg).unwrap()).await;
-                });
+    let on_light_change = {
+        let state = state.clone();
+        let dispatch = dispatch.clone();
+        let select_ref = light_ref.clone();
+
+        Callback::from(move |_| {
+            let select = select_ref.cast::<HtmlSelectElement>();
+
+            if let Some(select) = select {
+                let mut temp_settings = state.settings.clone().unwrap_or_default();
+
+                temp_settings.light_theme = select.value();
+
+                dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
             }
         })
     };
 
+    let on_dark_change = {
+        let state = state.clone();
+        let dispatch = dispatch.clone();
+        let select_ref = dark_ref.clone();
+
+        Callback::from(move |_| {
+            let select = select_ref.cast::<HtmlSelectElement>();
+
+            if let Some(select) = select {
+                let mut temp_settings = state.settings.clone().unwrap_or_default();
+
+                temp_settings.dark_theme = select.value();
+
+                dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
+            }
+        })
+    };
+
+    let on_history_change = {
+        let state = state.clone();
+        let dispatch = dispatch.clone();
+        let select_ref = history_ref.clone();
+
+        Callback::from(move |_| {
+            let select = select_ref.cast::<HtmlSelectElement>();
+
+            if let Some(select) = select {
+                let mut temp_settings = state.settings.clone().unwrap_or_default();
+
+                temp_settings.persist_history = select.value() == "true";
+
+                dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
+            }
+        })
+    };
+
+    let reopen_ref = use_node_ref();
+    let on_reopen_change = update_on_change(&state, &dispatch, &reopen_ref, |settings, value| {
+        settings.reopen_last_project = value == "true";
+    });
+
     let settings = state
         .settings
         .clone()
         .unwrap_or_else(|| Settings::default());
 
-    let themes_vec = themes_to_html(&themes, settings.theme.clone());
+    let system_theme = [SYSTEM_THEME.to_string()];
+    let themes_vec = themes_to_html(
+        &[&system_theme[..], &themes[..]].concat(),
+        settings.theme.clone(),
+    );
+    let light_themes_vec = themes_to_html(&themes, settings.light_theme.clone());
+    let dark_themes_vec = themes_to_html(&themes, settings.dark_theme.clone());
+
+    let interval_vec = get_intervals(&intervals, settings.autosave_interval);
 
-    let interval_vec = get_intervals(&intervals, settings.interval.clone());
+    let font_options: Vec<(String, String)> = EDITOR_FONTS
+        .iter()
+        .map(|(font, label)| ((*font).to_string(), (*label).to_string()))
+        .collect();
+    let line_height_options: Vec<(String, String)> = [100, 125, 150, 175, 200]
+        .iter()
+        .map(|height| (height.to_string(), format!("{height}%")))
+        .collect();
+    let page_width_options: Vec<(String, String)> = [0, 60, 70, 80, 100, 120]
+        .iter()
+        .map(|width| {
+            let label = if *width == 0 {
+                "Full Width".to_string()
+            } else {
+                format!("{width} characters")
+            };
+            (width.to_string(), label)
+        })
+        .collect();
+    let language_options: Vec<(String, String)> = SPELLCHECK_LANGUAGES
+        .iter()
+        .map(|(tag, label)| ((*tag).to_string(), (*label).to_string()))
+        .collect();
+    let export_options: Vec<(String, String)> = ExportFormat::ALL
+        .iter()
+        .map(|format| (format.extension().to_string(), format.label().to_string()))
+        .collect();
+    let backup_options: Vec<(String, String)> = [0, 5, 10, 20, 50]
+        .iter()
+        .map(|count| {
+            let label = if *count == 0 {
+                "All".to_string()
+            } else {
+                format!("{count} backups")
+            };
+            (count.to_string(), label)
+        })
+        .collect();
+
+    let lock_options: Vec<(String, String)> = [0, 5, 10, 15, 30, 60]
+        .iter()
+        .map(|minutes| {
+            let label = if *minutes == 0 {
+                "Never".to_string()
+            } else {
+                format!("After {minutes} minutes")
+            };
+            (minutes.to_string(), label)
+        })
+        .collect();
+
+    let log_level_options: Vec<(String, String)> = LogLevel::ALL
+        .iter()
+        .map(|level| (level.label().to_string(), level.label().to_string()))
+        .collect();
+
+    let retention_vec = get_retentions(&retentions, settings.trash_retention);
 
     html!(
         <>
@@ -194,6 +410,29 @@ pub fn settings_menu(
                         </select>
                 </div>
             </div>
+            if settings.theme == SYSTEM_THEME {
+                <div id="light_theme_change" class="flex w-full pt-4 justify-between">
+                    <div class="self-center">{ "Theme in Light Mode" }</div>
+                    <select
+                        ref={light_ref}
+                        onchange={on_light_change}
+                        class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                    >
+                        { light_themes_vec }
+                    </select>
+                </div>
+                <div id="dark_theme_change" class="flex w-full pt-4 justify-between">
+                    <div class="self-center">{ "Theme in Dark Mode" }</div>
+                    <select
+                        ref={dark_ref}
+                        onchange={on_dark_change}
+                        class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                    >
+                        { dark_themes_vec }
+                    </select>
+                </div>
+            }
+            <ThemeManager />
             <br />
             <div id="interval_change" class="flex w-full pt-8 justify-between">
                 <div class="font-bold self-center">{ "Auto-Save Interval" }</div>
@@ -208,6 +447,134 @@ pub fn settings_menu(
             </select>
                 </div>
             </div>
+            <br />
+            <div id="retention_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Keep Deleted Chapters" }</div>
+                <div>
+                    <select
+                        ref={retention_ref}
+                        onchange={on_retention_change}
+                        class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                    >
+                        { retention_vec }
+                    </select>
+                </div>
+            </div>
+            <br />
+            <div class="text-lg font-bold pt-8">{ "Editor" }</div>
+            <div id="font_change" class="flex w-full pt-4 justify-between">
+                <div class="self-center">{ "Font" }</div>
+                <select
+                    ref={font_ref}
+                    onchange={on_font_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&font_options, &settings.editor_font) }
+                </select>
+            </div>
+            <div id="line_height_change" class="flex w-full pt-4 justify-between">
+                <div class="self-center">{ "Line Height" }</div>
+                <select
+                    ref={line_height_ref}
+                    onchange={on_line_height_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&line_height_options, &settings.line_height.to_string()) }
+                </select>
+            </div>
+            <div id="page_width_change" class="flex w-full pt-4 justify-between">
+                <div class="self-center">{ "Page Width" }</div>
+                <select
+                    ref={page_width_ref}
+                    onchange={on_page_width_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&page_width_options, &settings.page_width.to_string()) }
+                </select>
+            </div>
+            <div id="language_change" class="flex w-full pt-4 justify-between">
+                <div class="self-center">{ "Spellcheck" }</div>
+                <select
+                    ref={language_ref}
+                    onchange={on_language_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&language_options, &settings.spellcheck_language) }
+                </select>
+            </div>
+            <br />
+            <div id="export_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Default Export Format" }</div>
+                <select
+                    ref={export_ref}
+                    onchange={on_export_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&export_options, settings.export_format.extension()) }
+                </select>
+            </div>
+            <br />
+            <div id="backup_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Keep Backups" }</div>
+                <select
+                    ref={backup_ref}
+                    onchange={on_backup_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&backup_options, &settings.backup_retention.to_string()) }
+                </select>
+            </div>
+            <br />
+            <div id="lock_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Lock Encrypted Projects" }</div>
+                <select
+                    ref={lock_ref}
+                    onchange={on_lock_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&lock_options, &settings.lock_after.to_string()) }
+                </select>
+            </div>
+            <br />
+            <div id="log_level_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Log Level" }</div>
+                <select
+                    ref={log_level_ref}
+                    onchange={on_log_level_change}
+                    class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                >
+                    { options_to_html(&log_level_options, settings.log_level.label()) }
+                </select>
+            </div>
+            <br />
+            <div id="history_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Keep Undo History After Closing" }</div>
+                <div>
+                    <select
+                        ref={history_ref}
+                        onchange={on_history_change}
+                        class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                    >
+                        <option value="true" selected={settings.persist_history}>{ "Yes" }</option>
+                        <option value="false" selected={!settings.persist_history}>{ "No" }</option>
+                    </select>
+                </div>
+            </div>
+            <br />
+            <div id="reopen_change" class="flex w-full pt-8 justify-between">
+                <div class="font-bold self-center">{ "Reopen Last Project On Launch" }</div>
+                <div>
+                    <select
+                        ref={reopen_ref}
+                        onchange={on_reopen_change}
+                        class="bg-base rounded-lg text-text focus:ring-secondary border-1 border-primary"
+                    >
+                        <option value="true" selected={settings.reopen_last_project}>{ "Yes" }</option>
+                        <option value="false" selected={!settings.reopen_last_project}>{ "No" }</option>
+                    </select>
+                </div>
+            </div>
+            <KeybindingEditor />
             <div class="flex justify-end w-full pt-8">
                 <button
                     ref={confirm_button_ref}
@@ -238,21 +605,59 @@ fn themes_to_html(themes: &[String], current: String) -> Html {
         .collect()
 }
 
+/// Options of a select from `(value, label)` pairs
+fn options_to_html(options: &[(String, String)], current: &str) -> Html {
+    options
+        .iter()
+        .map(|(value, label)| {
+            html! { <option value={value.clone()} selected={current == value}>{ label }</option> }
+        })
+        .collect()
+}
+
+/// Writes the value of the select at `select_ref` into the settings with `update`
+fn update_on_change(
+    state: &Rc<State>,
+    dispatch: &Dispatch<State>,
+    select_ref: &NodeRef,
+    update: impl Fn(&mut Settings, String) + 'static,
+) -> Callback<Event> {
+    let state = state.clone();
+    let dispatch = dispatch.clone();
+    let select_ref = select_ref.clone();
+
+    Callback::from(move |_| {
+        if let Some(select) = select_ref.cast::<HtmlSelectElement>() {
+            let mut temp_settings = state.settings.clone().unwrap_or_default();
+
+            update(&mut temp_settings, select.value());
+
+            dispatch.reduce_mut(|state| state.settings = Some(temp_settings));
+        }
+    })
+}
+
 fn get_intervals(intervals: &[i32], current: u32) -> Html {
     intervals
         .iter()
         .map(|interval| {
             let time = *interval as u32;
-            let selected = current == time * 60 * 1_000;
+            let selected = current == time;
             html! { <option value={ interval.to_string()} selected={selected}>{ interval.to_string() + "min" }</option> }
         })
         .collect()
 }
 
-fn switch_theme(theme: String) {
-    let html_doc: HtmlDocument = document().dyn_into().unwrap();
-    let body = html_doc.body().unwrap();
-    let theme = theme.as_str();
-    let theme2 = theme.to_lowercase().replace(' ', "");
-    body.set_class_name(format!("{theme2} bg-crust text-text").as_str());
+fn get_retentions(retentions: &[u32], current: u32) -> Html {
+    retentions
+        .iter()
+        .map(|days| {
+            let label = if *days == 0 {
+                "Forever".to_string()
+            } else {
+                format!("{days} days")
+            };
+            html! { <option value={days.to_string()} selected={current == *days}>{ label }</option> }
+        })
+        .collect()
 }
@@ -0,0 +1,193 @@
+use gloo::utils::document;
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{ThemeFile, BUILTIN_THEMES};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::HtmlDocument;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, PathArgs, State};
+
+#[derive(Serialize)]
+struct ThemeArgs {
+    file: String,
+}
+
+/// Body class of custom themes, their colors are set as CSS variables on the body
+const CUSTOM_THEME_CLASS: &str = "custom";
+
+/// Applies the built-in or custom theme called `name`
+pub fn apply_theme(name: &str, themes: &[ThemeFile]) {
+    let html_doc: HtmlDocument = document().dyn_into().unwrap();
+    let body = html_doc.body().unwrap();
+
+    let custom = themes
+        .iter()
+        .filter_map(|file| file.theme.as_ref())
+        .find(|theme| theme.name == name);
+    if let Some(theme) = custom {
+        body.set_class_name(format!("{CUSTOM_THEME_CLASS} bg-crust text-text").as_str());
+        let _ = body.set_attribute("style", &theme.css_variables());
+    } else {
+        let class = name.to_lowercase().replace(' ', "");
+        body.set_class_name(format!("{class} bg-crust text-text").as_str());
+        let _ = body.remove_attribute("style");
+    }
+}
+
+/// Applies the theme chosen in the settings
+pub fn apply_settings_theme(state: &State) {
+    if let Some(settings) = &state.settings {
+        apply_theme(settings.active_theme(state.dark_mode), &state.themes);
+    }
+}
+
+/// Reads the custom themes again and reapplies the current theme, so edits
+/// to a theme file show up right away
+pub async fn load_themes(dispatch: &Dispatch<State>) {
+    let themes_jsvalue = invoke("list_themes", JsValue::NULL).await;
+    let themes: Vec<ThemeFile> = from_value(themes_jsvalue).unwrap_or_default();
+
+    dispatch.reduce_mut(|state| state.themes = themes);
+    apply_settings_theme(&dispatch.get());
+}
+
+/// Follows the system into light or dark mode
+pub fn set_dark_mode(dispatch: &Dispatch<State>, dark_mode: bool) {
+    dispatch.reduce_mut(|state| state.dark_mode = dark_mode);
+    apply_settings_theme(&dispatch.get());
+}
+
+/// The names of all themes that can be chosen
+pub fn theme_names(themes: &[ThemeFile]) -> Vec<String> {
+    BUILTIN_THEMES
+        .iter()
+        .map(ToString::to_string)
+        .chain(
+            themes
+                .iter()
+                .filter_map(|file| file.theme.as_ref())
+                .map(|theme| theme.name.clone()),
+        )
+        .collect()
+}
+
+/// Lists the theme files with their problems and imports and exports them
+#[function_component(ThemeManager)]
+pub fn theme_manager() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let message = use_state(String::new);
+
+    let on_import = {
+        let dispatch = dispatch.clone();
+        let message = message.clone();
+        Callback::from(move |_: MouseEvent| {
+            let dispatch = dispatch.clone();
+            let message = message.clone();
+            spawn_local(async move {
+                let file_jsvalue = invoke("import_theme", JsValue::NULL).await;
+                let Ok(Some(file)) = from_value::<Option<ThemeFile>>(file_jsvalue) else {
+                    return;
+                };
+                match &file.theme {
+                    Some(theme) => message.set(format!("Imported \"{}\"", theme.name)),
+                    None => message.set(format!(
+                        "{} was not imported: {}",
+                        file.file,
+                        file.errors.join(", ")
+                    )),
+                }
+                load_themes(&dispatch).await;
+            });
+        })
+    };
+
+    let on_open_folder = Callback::from(move |_: MouseEvent| {
+        spawn_local(async move {
+            let path_jsvalue = invoke("get_data_dir", JsValue::NULL).await;
+            let path = format!(
+                "{}/PaperSmith/themes",
+                path_jsvalue.as_string().unwrap_or_default()
+            );
+            invoke("open_explorer", to_value(&PathArgs { path }).unwrap()).await;
+        });
+    });
+
+    let files = state
+        .themes
+        .iter()
+        .map(|file| {
+            let on_export = {
+                let name = file.file.clone();
+                let message = message.clone();
+                Callback::from(move |_: MouseEvent| {
+                    let file = name.clone();
+                    let message = message.clone();
+                    spawn_local(async move {
+                        let exported = invoke(
+                            "export_theme",
+                            to_value(&ThemeArgs { file: file.clone() }).unwrap(),
+                        )
+                        .await;
+                        if exported.as_bool() == Some(true) {
+                            message.set(format!("Exported {file}"));
+                        }
+                    });
+                })
+            };
+
+            html! {
+                <div class="flex justify-between items-center py-1">
+                    <div class="flex flex-col">
+                        <span class="font-bold">
+                            { file.theme.as_ref().map_or(file.file.clone(), |theme| theme.name.clone()) }
+                        </span>
+                        <span class="text-sm text-subtext">{ file.file.clone() }</span>
+                        { file
+                            .errors
+                            .iter()
+                            .map(|error| html! { <span class="text-sm text-secondary">{ error }</span> })
+                            .collect::<Html>() }
+                    </div>
+                    <button
+                        onclick={on_export}
+                        class="rounded-lg px-2 py-1 bg-crust hover:bg-mantle text-inherit border-0 cursor-pointer"
+                    >
+                        { "Export" }
+                    </button>
+                </div>
+            }
+        })
+        .collect::<Html>();
+
+    html!(
+        <div class="flex flex-col w-full pt-8">
+            <div class="flex justify-between">
+                <div class="font-bold self-center">{ "Custom Themes" }</div>
+                <div>
+                    <button
+                        onclick={on_open_folder}
+                        class="rounded-lg px-2 py-1 ml-2 bg-crust hover:bg-mantle text-inherit border-0 cursor-pointer"
+                    >
+                        { "Open Folder" }
+                    </button>
+                    <button
+                        onclick={on_import}
+                        class="rounded-lg px-2 py-1 ml-2 bg-crust hover:bg-mantle text-inherit border-0 cursor-pointer"
+                    >
+                        { "Import" }
+                    </button>
+                </div>
+            </div>
+            if state.themes.is_empty() {
+                <div class="text-sm text-subtext pt-2">
+                    { "Theme files (.toml or .json) in the themes folder show up here." }
+                </div>
+            }
+            { files }
+            <div class="text-sm text-subtext">{ (*message).clone() }</div>
+        </div>
+    )
+}
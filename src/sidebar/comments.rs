@@ -0,0 +1,106 @@
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{ChapterComments, Project};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::invoke;
+use crate::app::open_chapter;
+use crate::app::State;
+
+#[derive(Serialize)]
+struct ProjectArgs {
+    project: Project,
+}
+
+/// The comments of all chapters as they were last saved
+#[function_component(CommentList)]
+pub fn comment_list() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let chapters = use_state(Vec::<ChapterComments>::new);
+
+    // Saving resets `changes`, the list is read again then
+    {
+        let chapters = chapters.clone();
+        use_effect_with(
+            (state.project.clone(), state.changes),
+            move |(project, _)| {
+                if let Some(project) = project.clone() {
+                    spawn_local(async move {
+                        let comments_jsvalue =
+                            invoke("list_comments", to_value(&ProjectArgs { project }).unwrap())
+                                .await;
+                        chapters.set(from_value(comments_jsvalue).unwrap_or_default());
+                    });
+                }
+            },
+        );
+    }
+
+    let title = |chapter: &str| {
+        state
+            .project
+            .as_ref()
+            .map_or_else(|| chapter.to_string(), |project| project.title(chapter))
+    };
+    let active_chapter = state
+        .project
+        .as_ref()
+        .and_then(|project| project.chapters.get(project.active_chapter?).cloned());
+
+    html! {
+        <div class="overflow-scroll grow shrink p-2 flex flex-col">
+            if chapters.is_empty() {
+                <div class="text-subtext text-center my-4">
+                    { "No comments yet. Write them as {>>comment<<} or {==text==}{>>comment<<}." }
+                </div>
+            }
+            { chapters
+                .iter()
+                .map(|chapter| {
+                    let on_open = {
+                        let name = chapter.chapter.clone();
+                        let dispatch = dispatch.clone();
+                        Callback::from(move |_: MouseEvent| {
+                            dispatch.reduce_mut(|x| {
+                                let index = x
+                                    .project
+                                    .as_ref()
+                                    .and_then(|project| project.chapters.iter().position(|x| *x == name));
+                                if let Some(index) = index {
+                                    open_chapter(x, index);
+                                }
+                            });
+                        })
+                    };
+                    let active = active_chapter.as_ref() == Some(&chapter.chapter);
+                    html! {
+                        <div class="flex flex-col mb-2">
+                            <button
+                                class={classes!("text-start", "rounded-lg", "p-2", "cursor-pointer", "border-0", "text-inherit", "text-[length:inherit]", "font-bold",
+                                    if active { "bg-base" } else { "bg-crust hover:bg-mantle" }
+                                )}
+                                onclick={on_open}
+                            >
+                                { format!("{} ({})", title(&chapter.chapter), chapter.comments.len()) }
+                            </button>
+                            { chapter
+                                .comments
+                                .iter()
+                                .map(|comment| html! {
+                                    <div class="flex flex-col px-2 py-1 ml-2 border-0 border-l-2 border-solid border-secondary">
+                                        <div>{ comment.text.clone() }</div>
+                                        <div class="text-subtext text-sm">
+                                            { format!("Line {}", comment.line + 1) }
+                                        </div>
+                                    </div>
+                                })
+                                .collect::<Html>() }
+                        </div>
+                    }
+                })
+                .collect::<Html>() }
+        </div>
+    }
+}
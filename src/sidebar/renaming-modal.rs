@@ -18,6 +18,7 @@ struct RenameArgs {
 
 #[derive(Properties, PartialEq)]
 pub struct Props {
+    /// The folder of the project, or of the chapter
     pub old_name: String,
     pub closing_callback: Callback<MouseEvent>,
     pub is_project: bool,
@@ -38,16 +39,26 @@ pub fn renamingmodal(
     let is_data_valid = use_state(|| true);
     let error_message = use_state(String::new);
 
+    // Chapters are renamed by their title, their folder stays the same
+    let old_title = if *is_project {
+        old_name.clone()
+    } else {
+        state
+            .project
+            .as_ref()
+            .map_or_else(|| old_name.clone(), |project| project.title(old_name))
+    };
+
     let new_name_input = text_input_handler(new_name.clone());
 
     {
-        let old_name = old_name.clone();
+        let old_title = old_title.clone();
         let new_name = new_name.clone();
         let new_name_ref = new_name_ref.clone();
         use_effect_with((), move |()| {
             if let Some(input) = new_name_ref.cast::<HtmlInputElement>() {
-                new_name.set(old_name.clone());
-                input.set_value(&old_name);
+                new_name.set(old_title.clone());
+                input.set_value(&old_title);
             }
         });
     }
@@ -62,21 +73,23 @@ pub fn renamingmodal(
             let is_data_valid = is_data_valid.clone();
             let new_name = new_name.clone();
             let error_message = error_message.clone();
-            if new_name.is_empty() {
+            if new_name.trim().is_empty() {
                 is_data_valid.set(false);
                 error_message.set("Please enter a new name.".to_string());
                 return;
             }
+            // Any title works for a chapter
+            if !is_project {
+                is_data_valid.set(true);
+                error_message.set(String::new());
+                return;
+            }
             spawn_local(async move {
                 let is_data_valid = is_data_valid.clone();
                 let error_message = error_message.clone();
 
                 let mut complete_path = PathBuf::from(&state.project.as_ref().unwrap().path);
-                if is_project {
-                    complete_path.pop();
-                } else {
-                    complete_path.push("Chapters");
-                }
+                complete_path.pop();
                 complete_path.push(&*new_name);
 
                 let result = invoke(
@@ -128,13 +141,18 @@ pub fn renamingmodal(
             if !*is_data_valid {
                 return;
             }
+            let Some(mut temp_project) = state.project.clone() else {
+                return;
+            };
+            if !is_project {
+                temp_project.rename_chapter(&old_name, new_name.trim());
+                dispatch.reduce_mut(|x| x.project = Some(temp_project));
+                on_close.emit(MouseEvent::new("Dummy").unwrap());
+                return;
+            }
             spawn_local(async move {
-                let mut complete_path = PathBuf::from(&state.project.as_ref().unwrap().path);
-                if is_project {
-                    complete_path.pop();
-                } else {
-                    complete_path.push("Chapters");
-                }
+                let mut complete_path = PathBuf::from(&temp_project.path);
+                complete_path.pop();
                 let args = RenameArgs {
                     path: complete_path.clone(),
                     old: old_name.clone(),
@@ -143,25 +161,15 @@ pub fn renamingmodal(
                 let args = to_value(&args).unwrap();
                 invoke("rename_path", args).await;
 
-                if let Some(mut temp_project) = state.project.clone() {
-                    if is_project {
-                        temp_project.path = complete_path.join(&(*new_name));
-                    } else {
-                        for chapter in &mut temp_project.chapters {
-                            if *chapter == old_name {
-                                chapter.clone_from(&*new_name);
-                            }
-                        }
-                    }
-                    dispatch.reduce_mut(|x| x.project = Some(temp_project));
-                }
+                temp_project.path = complete_path.join(&(*new_name));
+                dispatch.reduce_mut(|x| x.project = Some(temp_project));
             });
             on_close.emit(MouseEvent::new("Dummy").unwrap());
         })
     };
     let content = html! {
         <>
-            <div class="text-xl font-bold">{ format!("Rename \"{}\"", old_name) }</div>
+            <div class="text-xl font-bold">{ format!("Rename \"{}\"", old_title) }</div>
             <br />
             <div class="font-semibold">{ "New Name:" }</div>
             <div
@@ -1,9 +1,9 @@
 use std::path::Path;
-use std::path::PathBuf;
 
+use serde::Serialize;
 use serde_wasm_bindgen::to_value;
+use shared::Project;
 use web_sys::Element;
-use web_sys::HtmlTextAreaElement;
 use yew::platform::spawn_local;
 use yew::prelude::*;
 use yew::virtual_dom::VNode;
@@ -19,12 +19,35 @@ pub use buttons::{ButtonContainer, Props as ButtonProps};
 mod renaming_modal;
 use renaming_modal::RenamingModal;
 
+#[path = "trash.rs"]
+pub mod trash;
+use trash::TrashList;
+
+#[path = "comments.rs"]
+mod comments;
+use comments::CommentList;
+
+use crate::app::codex::CodexList;
+use crate::app::commands::use_commands;
 use crate::app::invoke;
 use crate::app::modal::Modal;
+use crate::app::notes::NoteList;
+use crate::app::open_chapter;
 use crate::app::wizard::PathArgs;
-use crate::app::FileWriteData;
 use crate::app::State;
 
+#[derive(Serialize)]
+struct AddChapterArgs {
+    path: String,
+    title: String,
+}
+
+#[derive(Serialize)]
+struct TrashArgs {
+    project: Project,
+    chapter: String,
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
 enum ChapterStatus {
     Normal,
@@ -77,45 +100,13 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
     let (state, dispatch) = use_store::<State>();
     let title = use_state(|| get_file_name(&(state.project).as_ref().unwrap().path));
     let chapters = use_state(Vec::<VNode>::new);
-    let tabs = vec!["Overview".to_string(), "Notes".to_string()];
-    let note_types = vec!["Project".to_string(), "Chapter".to_string()];
+    let tabs = vec![
+        "Overview".to_string(),
+        "Notes".to_string(),
+        "Codex".to_string(),
+        "Comments".to_string(),
+    ];
     let tab = use_state(|| tabs[0].clone());
-    let note_tab = use_state(|| note_types[0].clone());
-    let note_ref = use_node_ref();
-
-    {
-        let state = state.clone();
-        let note_tab = note_tab.clone();
-        let note_types = note_types.clone();
-        let note_ref = note_ref.clone();
-        use_effect_with((tab.clone(), note_tab.clone()), move |_| {
-            if let Some(input) = note_ref.cast::<HtmlTextAreaElement>() {
-                spawn_local(async move {
-                    let mut note_path = state.project.as_ref().unwrap().path.clone();
-                    if *note_tab != note_types[0] {
-                        note_path.push("Chapters");
-                        let project_ref = state.project.as_ref().unwrap();
-                        note_path.push(
-                            project_ref.chapters[project_ref.active_chapter.unwrap()].clone(),
-                        );
-                    }
-                    note_path.push("Note");
-                    note_path.set_extension("md");
-                    let content = invoke(
-                        "get_file_content",
-                        to_value(&PathArgs {
-                            path: note_path.to_str().unwrap().to_string(),
-                        })
-                        .unwrap(),
-                    )
-                    .await
-                    .as_string()
-                    .unwrap();
-                    input.set_value(&content);
-                });
-            }
-        });
-    }
 
     {
         let title = title.clone();
@@ -151,6 +142,7 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
                                 <ChapterComponent
                                     key={chapter.clone()}
                                     chapter={chapter.clone()}
+                                    title={project_data.title(chapter)}
                                     index={index}
                                     status={status}
                                     modal={modal.clone()}
@@ -172,38 +164,31 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
             let state = state.clone();
             let dispatch = dispatch.clone();
             spawn_local(async move {
-                let mut check_path = state.project.as_ref().unwrap().path.clone();
-                check_path.push("Chapters");
-                check_path.push("Untitled");
+                let mut temp_project = state.project.as_ref().unwrap().clone();
+                let titles: Vec<String> = temp_project
+                    .chapters
+                    .iter()
+                    .map(|chapter| temp_project.title(chapter))
+                    .collect();
+                let mut title = "Untitled".to_string();
                 let mut index = 1;
-                while !invoke(
-                    "can_create_path",
-                    to_value(&PathArgs {
-                        path: check_path.to_str().unwrap().to_string().clone(),
-                    })
-                    .unwrap(),
-                )
-                .await
-                .as_string()
-                .unwrap()
-                .is_empty()
-                {
-                    check_path.pop();
-                    check_path.push("Untitled".to_string() + &index.to_string());
+                while titles.contains(&title) {
+                    title = "Untitled".to_string() + &index.to_string();
                     index += 1;
                 }
-                invoke(
+                let folder = invoke(
                     "add_chapter",
-                    to_value(&PathArgs {
-                        path: check_path.to_str().unwrap().to_string(),
+                    to_value(&AddChapterArgs {
+                        path: temp_project.path.to_string_lossy().to_string(),
+                        title: title.clone(),
                     })
                     .unwrap(),
                 )
-                .await;
-                let mut temp_project = state.project.as_ref().unwrap().clone();
-                temp_project
-                    .chapters
-                    .push(check_path.file_name().unwrap().to_string_lossy().into());
+                .await
+                .as_string()
+                .unwrap();
+                temp_project.chapters.push(folder.clone());
+                temp_project.rename_chapter(&folder, &title);
                 dispatch.reduce_mut(|state| state.project = Some(temp_project));
             });
         })
@@ -229,6 +214,19 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
         })
     };
 
+    // The trash is not part of the tab menu, clicking it again goes back to the overview
+    let on_trash = {
+        let tab = tab.clone();
+        let overview = tabs[0].clone();
+        Callback::from(move |_: MouseEvent| {
+            if *tab == "Trash" {
+                tab.set(overview.clone());
+            } else {
+                tab.set("Trash".to_string());
+            }
+        })
+    };
+
     let on_close = {
         let modal = modal.clone();
         Callback::from(move |_| modal.set(html!()))
@@ -236,6 +234,7 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
     let rename_callback = {
         let title = title.clone();
         let modal = modal.clone();
+        let on_close = on_close.clone();
         Callback::from(move |e: MouseEvent| {
             e.stop_propagation();
             let title = title.clone();
@@ -250,37 +249,35 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
         })
     };
 
-    let note_input_handler = {
-        let note_tab = note_tab.clone();
-        let note_types = note_types.clone();
-        Callback::from(move |e: InputEvent| {
-            if let Some(input) = e.target_dyn_into::<HtmlTextAreaElement>() {
-                let text = input.value();
-                let mut note_path = state.project.as_ref().unwrap().path.clone();
-                if *note_tab != note_types[0] {
-                    note_path.push("Chapters");
-                    let project_ref = state.project.as_ref().unwrap();
-                    note_path
-                        .push(project_ref.chapters[project_ref.active_chapter.unwrap()].clone());
-                }
-                note_path.push("Note");
-                note_path.set_extension("md");
-                spawn_local(async move {
-                    let write_data = FileWriteData {
-                        path: note_path.to_str().unwrap().to_string(),
-                        content: text,
-                    };
-
-                    invoke(
-                        "write_to_file",
-                        serde_wasm_bindgen::to_value(&write_data).unwrap(),
-                    )
-                    .await;
-                });
-            }
+    let rename_chapter = {
+        let state = state.clone();
+        let modal = modal.clone();
+        Callback::from(move |()| {
+            let Some(chapter) = state
+                .project
+                .as_ref()
+                .and_then(|project| project.chapters.get(project.active_chapter?).cloned())
+            else {
+                return;
+            };
+            modal.set(html! {
+                <RenamingModal old_name={chapter} closing_callback={on_close.clone()} is_project=false />
+            });
         })
     };
 
+    use_commands(vec![
+        (
+            "new_chapter",
+            on_add_chapter.reform(|()| MouseEvent::new("Dummy").unwrap()),
+        ),
+        ("rename_chapter", rename_chapter),
+        (
+            "rename_project",
+            rename_callback.reform(|()| MouseEvent::new("Dummy").unwrap()),
+        ),
+    ]);
+
     html! {
         <>
             <div
@@ -302,11 +299,19 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
                     // </div>
                     // Space for future buttons
                     <button
-                        class="rounded-full bg-base py-2 px-4 cursor-pointer grow border-0 text-inherit text-[length:inherit] hover:bg-mantle"
+                        class="rounded-full bg-base py-2 px-4 mr-2 cursor-pointer grow border-0 text-inherit text-[length:inherit] hover:bg-mantle"
                         onclick={on_extras}
                     >
                         { "Extras" }
                     </button>
+                    <button
+                        class={classes!("rounded-full", "py-2", "px-4", "cursor-pointer", "grow", "border-0", "text-inherit", "text-[length:inherit]",
+                            if *tab == "Trash" { "bg-primary text-mantle" } else { "bg-base hover:bg-mantle" }
+                        )}
+                        onclick={on_trash}
+                    >
+                        { "Trash" }
+                    </button>
                 </div>
                 <TabMenu tabs={tabs} active_tab={tab.clone()} />
                 if *tab == "Overview" {
@@ -329,13 +334,14 @@ pub fn sidebar(Props { modal }: &Props) -> Html {
                             </div>
                         </button>
                     </div>
+                } else if *tab == "Codex" {
+                    <CodexList modal={modal.clone()} />
+                } else if *tab == "Comments" {
+                    <CommentList />
+                } else if *tab == "Trash" {
+                    <TrashList />
                 } else {
-                    <TabMenu tabs={note_types} active_tab={note_tab.clone()} />
-                    <textarea
-                        class="h-full overflow-scroll grow shrink bg-base resize-none border-0 focus:ring-0 text-text rounded-lg"
-                        oninput={note_input_handler}
-                        ref={note_ref}
-                    />
+                    <NoteList />
                 }
             </div>
         </>
@@ -391,7 +397,9 @@ fn tabmenu(TabMenuProps { tabs, active_tab }: &TabMenuProps) -> Html {
 
 #[derive(Properties, PartialEq)]
 struct ChapterProps {
+    /// Name of the chapter folder
     pub chapter: String,
+    pub title: String,
     pub index: usize,
     pub status: ChapterStatus,
     pub modal: UseStateHandle<VNode>,
@@ -401,6 +409,7 @@ struct ChapterProps {
 fn chapter(
     ChapterProps {
         chapter,
+        title,
         index,
         status,
         modal,
@@ -428,6 +437,7 @@ fn chapter(
     let delete_callback = {
         let modal = modal.clone();
         let chapter = chapter.clone();
+        let title = title.clone();
         let dispatch = dispatch.clone();
         let state = state.clone();
         let on_close = on_close.clone();
@@ -439,17 +449,19 @@ fn chapter(
                 let chapter = chapter.clone();
                 let dispatch = dispatch.clone();
                 spawn_local(async move {
-                    let mut complete_path = PathBuf::from(&state.project.as_ref().unwrap().path);
-                    complete_path.push("Chapters");
-                    complete_path.push(&chapter);
-                    let args = PathArgs {
-                        path: complete_path.to_str().unwrap().to_string(),
+                    let Some(project) = state.project.clone() else {
+                        return;
+                    };
+                    let args = TrashArgs {
+                        project: project.clone(),
+                        chapter: chapter.clone(),
                     };
                     let args = to_value(&args).unwrap();
-                    invoke("delete_path", args).await;
+                    let trashed = invoke("trash_chapter", args).await.as_bool();
 
-                    if let Some(mut temp_project) = state.project.clone() {
-                        temp_project.chapters.retain(|x| **x != chapter);
+                    if trashed == Some(true) {
+                        let mut temp_project = project;
+                        temp_project.remove_chapter(&chapter);
                         dispatch.reduce_mut(|x| x.project = Some(temp_project));
                     }
                 });
@@ -463,7 +475,7 @@ fn chapter(
             let content = html! {
                 <>
                     <div class="text-xl font-bold">
-                        { format!("Do you really want to delete \"{}\"?", chapter) }
+                        { format!("Do you really want to move \"{}\" to the trash?", title) }
                     </div>
                     <br />
                     <div id="footer" class="flex justify-end w-full pt-8">
@@ -486,63 +498,11 @@ fn chapter(
         })
     };
 
+    // Asks first when there are unsaved changes
     let on_load = {
         let index = *index;
-
         let dispatch = dispatch.clone();
-        Callback::from(move |_: MouseEvent| {
-            let dispatch = dispatch.clone();
-            dispatch.reduce_mut(|x| {
-                x.project.as_mut().unwrap().active_chapter = Some(index);
-                x.changes = false;
-            });
-        })
-    };
-    let on_load_and_close = {
-        let on_load = on_load.clone();
-        let on_close = on_close.clone();
-
-        Callback::from(move |_: MouseEvent| {
-            on_load.emit(MouseEvent::new("Dummy").unwrap());
-            on_close.emit(MouseEvent::new("Dummy").unwrap());
-        })
-    };
-    let on_load_wrapper = {
-        let load_modal = html! {
-            <>
-                <div class="text-xl font-bold">
-                    { format!("You have unsaved changes! Do you really want to continue?") }
-                </div>
-                <br />
-                <div id="footer" class="flex justify-end w-full pt-8">
-                    <button
-                        onclick={on_load_and_close.clone()}
-                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
-                    >
-                        { "Continue" }
-                    </button>
-                    <button
-                        onclick={on_close.clone()}
-                        class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
-                    >
-                        { "Cancel" }
-                    </button>
-                </div>
-            </>
-        };
-        let modal = modal.clone();
-        let state = state.clone();
-        Callback::from(move |_: MouseEvent| {
-            let load_modal = load_modal.clone();
-            let state = state.clone();
-            let modal = modal.clone();
-            let on_load = on_load.clone();
-            if state.changes {
-                modal.set(html! { <Modal content={load_modal} /> });
-            } else {
-                on_load.emit(MouseEvent::new("Dummy").unwrap());
-            }
-        })
+        Callback::from(move |_: MouseEvent| dispatch.reduce_mut(|x| open_chapter(x, index)))
     };
 
     let button_props = vec![
@@ -588,7 +548,7 @@ fn chapter(
                 if *status==ChapterStatus::ActiveChanges {"italic"} else {""}
             )}
             draggable="true"
-            onclick={on_load_wrapper}
+            onclick={on_load}
             ondragstart={ondragstart}
             ondragend={ondragend}
         >
@@ -598,7 +558,7 @@ fn chapter(
             >
                 { *index+1 }
             </div>
-            <div class={classes!("flex", "items-center")}>{ chapter.clone() }</div>
+            <div class={classes!("flex", "items-center")}>{ title.clone() }</div>
             <ButtonContainer button_props={button_props} />
         </button>
     }
@@ -638,35 +598,13 @@ fn draghandler(DragHandlerProps { index }: &DragHandlerProps) -> Html {
             e.prevent_default();
             dispatch.reduce_mut(|x| {
                 // Get references to the state
-                let (Some(project), Some(dragger_index)) = (x.project.as_mut(), x.dragger.as_ref())
+                let (Some(project), Some(dragger_index)) = (x.project.as_mut(), x.dragger)
                 else {
                     return;
                 };
-                let Some(dragger) = project.chapters.get(*dragger_index).cloned() else {
-                    return;
-                };
-
-                // Adjust index if it would be moved my removing an the chapter
-                let adjusted_index = if *dragger_index < index {
-                    index - 1
-                } else {
-                    index
-                };
 
-                // Move the chapter
-                project.chapters.remove(*dragger_index);
-                project.chapters.insert(adjusted_index, dragger);
-
-                // Adjust active chapter if it's been moved
-                let Some(active_chapter) = project.active_chapter else {
-                    return;
-                };
-                if active_chapter == adjusted_index {
-                    project.active_chapter = Some(*dragger_index);
-                }
-                if active_chapter == *dragger_index {
-                    project.active_chapter = Some(adjusted_index);
-                }
+                // Move the chapter, the active chapter is kept in place
+                project.move_chapter(dragger_index, index);
 
                 // Clean up drag handling
                 x.dragger = None;
@@ -0,0 +1,194 @@
+use std::path::PathBuf;
+
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{Settings, TrashItem};
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yew_icons::IconId;
+use yewdux::prelude::*;
+
+use crate::app::invoke;
+use crate::app::sidebar::buttons::Button;
+use crate::app::{PathArgs, State};
+
+#[derive(Serialize)]
+struct RestoreArgs {
+    path: String,
+    id: String,
+}
+
+#[derive(Serialize)]
+pub struct EmptyTrashArgs {
+    pub path: String,
+    pub days: Option<u32>,
+}
+
+fn format_deleted(deleted: i64) -> String {
+    Local
+        .timestamp_opt(deleted, 0)
+        .single()
+        .map_or_else(String::new, |time| {
+            time.format("%Y-%m-%d %H:%M").to_string()
+        })
+}
+
+#[function_component(TrashList)]
+pub fn trash_list() -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let items = use_state(Vec::<TrashItem>::new);
+    let reload = use_state(|| 0_u32);
+
+    let project_path = state
+        .project
+        .as_ref()
+        .map(|project| project.path.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    {
+        let items = items.clone();
+        use_effect_with((project_path.clone(), *reload), move |(project_path, _)| {
+            let project_path = project_path.clone();
+            spawn_local(async move {
+                let items_jsvalue = invoke(
+                    "list_trash",
+                    to_value(&PathArgs { path: project_path }).unwrap(),
+                )
+                .await;
+                items.set(from_value(items_jsvalue).unwrap_or_default());
+            });
+        });
+    }
+
+    let on_empty = {
+        let project_path = project_path.clone();
+        let reload = reload.clone();
+        Callback::from(move |_: MouseEvent| {
+            let project_path = project_path.clone();
+            let reload = reload.clone();
+            spawn_local(async move {
+                invoke(
+                    "empty_trash",
+                    to_value(&EmptyTrashArgs {
+                        path: project_path,
+                        days: None,
+                    })
+                    .unwrap(),
+                )
+                .await;
+                reload.set(*reload + 1);
+            });
+        })
+    };
+
+    let retention = state.settings.as_ref().map_or_else(
+        || Settings::default().trash_retention,
+        |x| x.trash_retention,
+    );
+
+    html! {
+        <div class="overflow-scroll grow shrink p-2 flex flex-col">
+            if items.is_empty() {
+                <div class="text-subtext text-center my-4">{ "The trash is empty." }</div>
+            }
+            { items
+                .iter()
+                .map(|item| {
+                    let on_restore = {
+                        let item = item.clone();
+                        let project_path = project_path.clone();
+                        let dispatch = dispatch.clone();
+                        let reload = reload.clone();
+                        Callback::from(move |_: MouseEvent| {
+                            let args = RestoreArgs {
+                                path: project_path.clone(),
+                                id: item.id.clone(),
+                            };
+                            let dispatch = dispatch.clone();
+                            let reload = reload.clone();
+                            spawn_local(async move {
+                                let restored_jsvalue =
+                                    invoke("restore_chapter", to_value(&args).unwrap()).await;
+                                let restored: Option<TrashItem> =
+                                    from_value(restored_jsvalue).unwrap_or_default();
+                                if let Some(restored) = restored {
+                                    dispatch.reduce_mut(|x| {
+                                        if let Some(project) = x.project.as_mut() {
+                                            project.insert_chapter(
+                                                restored.position,
+                                                restored.name,
+                                                restored.meta,
+                                            );
+                                        }
+                                    });
+                                }
+                                reload.set(*reload + 1);
+                            });
+                        })
+                    };
+                    let on_delete = {
+                        let mut item_path = PathBuf::from(&project_path);
+                        item_path.push(".trash");
+                        item_path.push(&item.id);
+                        let reload = reload.clone();
+                        Callback::from(move |_: MouseEvent| {
+                            let item_path = item_path.clone();
+                            let reload = reload.clone();
+                            spawn_local(async move {
+                                invoke(
+                                    "delete_path",
+                                    to_value(&PathArgs {
+                                        path: item_path.to_string_lossy().to_string(),
+                                    })
+                                    .unwrap(),
+                                )
+                                .await;
+                                reload.set(*reload + 1);
+                            });
+                        })
+                    };
+                    html! {
+                        <div class="flex items-center rounded-lg hover:bg-mantle p-2">
+                            <div class="flex flex-col">
+                                <div>{ item.title().to_string() }</div>
+                                <div class="text-subtext text-sm">
+                                    { format!("Deleted {}", format_deleted(item.deleted)) }
+                                </div>
+                            </div>
+                            <div class="flex ml-auto">
+                                <Button
+                                    callback={on_restore}
+                                    icon={IconId::LucideRotateCcw}
+                                    title="Restore"
+                                    size=1.3
+                                />
+                                <Button
+                                    callback={on_delete}
+                                    icon={IconId::LucideTrash2}
+                                    title="Delete Forever"
+                                    size=1.3
+                                />
+                            </div>
+                        </div>
+                    }
+                })
+                .collect::<Html>() }
+            <div class="text-subtext text-sm text-center my-2">
+                { if retention == 0 {
+                    "Deleted chapters are kept until the trash is emptied.".to_string()
+                } else {
+                    format!("Deleted chapters are removed after {retention} days.")
+                } }
+            </div>
+            if !items.is_empty() {
+                <button
+                    class="rounded-full bg-base py-2 px-4 cursor-pointer border-0 text-inherit text-[length:inherit] hover:bg-mantle"
+                    onclick={on_empty}
+                >
+                    { "Empty Trash" }
+                </button>
+            }
+        </div>
+    }
+}
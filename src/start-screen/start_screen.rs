@@ -0,0 +1,213 @@
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use serde_wasm_bindgen::{from_value, to_value};
+use shared::{Project, RecentProject};
+use wasm_bindgen::JsValue;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::{invoke, open_project, PathArgs, State};
+
+#[derive(Serialize)]
+struct PinArgs {
+    path: String,
+    pinned: bool,
+}
+
+fn format_time(time: i64) -> String {
+    Local
+        .timestamp_opt(time, 0)
+        .single()
+        .map_or_else(String::new, |time| {
+            time.format("%Y-%m-%d %H:%M").to_string()
+        })
+}
+
+fn path_args(project: &RecentProject) -> JsValue {
+    to_value(&PathArgs {
+        path: project.path.to_string_lossy().to_string(),
+    })
+    .unwrap()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct StartScreenProps {
+    pub open_folder: Callback<MouseEvent>,
+    pub open_archive: Callback<MouseEvent>,
+    pub create_project: Callback<MouseEvent>,
+    pub closing_callback: Callback<MouseEvent>,
+}
+
+/// Shown while no project is open, lists the recent projects to pick up where
+/// the writing stopped
+#[function_component(StartScreen)]
+pub fn start_screen(
+    StartScreenProps {
+        open_folder,
+        open_archive,
+        create_project,
+        closing_callback: on_close,
+    }: &StartScreenProps,
+) -> Html {
+    let (_state, dispatch) = use_store::<State>();
+    let projects = use_state(|| None::<Vec<RecentProject>>);
+    let message = use_state(String::new);
+    let reload = use_state(|| 0_u32);
+
+    {
+        let projects = projects.clone();
+        use_effect_with(*reload, move |_| {
+            spawn_local(async move {
+                let projects_jsvalue = invoke("list_recent_projects", JsValue::NULL).await;
+                projects.set(Some(from_value(projects_jsvalue).unwrap_or_default()));
+            });
+        });
+    }
+
+    let rows = projects
+        .as_ref()
+        .map(|projects| {
+            projects
+                .iter()
+                .map(|project| {
+                    let on_open = {
+                        let project = project.clone();
+                        let dispatch = dispatch.clone();
+                        let on_close = on_close.clone();
+                        let message = message.clone();
+                        let reload = reload.clone();
+                        Callback::from(move |_: MouseEvent| {
+                            let project = project.clone();
+                            let dispatch = dispatch.clone();
+                            let on_close = on_close.clone();
+                            let message = message.clone();
+                            let reload = reload.clone();
+                            spawn_local(async move {
+                                let project_jsvalue =
+                                    invoke("open_project", path_args(&project)).await;
+                                match from_value::<Option<Project>>(project_jsvalue) {
+                                    Ok(Some(opened)) => {
+                                        dispatch.reduce_mut(|state| open_project(state, opened));
+                                        on_close.emit(MouseEvent::new("Dummy").unwrap());
+                                    }
+                                    _ => {
+                                        message.set(format!("{} could not be opened", project.name));
+                                        reload.set(*reload + 1);
+                                    }
+                                }
+                            });
+                        })
+                    };
+                    let on_pin = {
+                        let project = project.clone();
+                        let reload = reload.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.stop_propagation();
+                            let args = PinArgs {
+                                path: project.path.to_string_lossy().to_string(),
+                                pinned: !project.pinned,
+                            };
+                            let reload = reload.clone();
+                            spawn_local(async move {
+                                invoke("pin_recent_project", to_value(&args).unwrap()).await;
+                                reload.set(*reload + 1);
+                            });
+                        })
+                    };
+                    let on_remove = {
+                        let project = project.clone();
+                        let reload = reload.clone();
+                        Callback::from(move |e: MouseEvent| {
+                            e.stop_propagation();
+                            let args = path_args(&project);
+                            let reload = reload.clone();
+                            spawn_local(async move {
+                                invoke("remove_recent_project", args).await;
+                                reload.set(*reload + 1);
+                            });
+                        })
+                    };
+
+                    let edited = project
+                        .edited
+                        .map_or_else(String::new, |edited| {
+                            format!(", last edited {}", format_time(edited))
+                        });
+
+                    html! {
+                        <div
+                            onclick={on_open}
+                            class="flex justify-between items-center p-2 rounded-lg cursor-pointer hover:bg-crust"
+                        >
+                            <div class="flex flex-col min-w-0">
+                                <div class="text-lg font-bold truncate">
+                                    { &project.name }
+                                    if project.pinned {
+                                        <span class="text-sm font-normal text-primary ml-2">
+                                            { "Pinned" }
+                                        </span>
+                                    }
+                                </div>
+                                <div class="text-sm text-subtext truncate">
+                                    { project.path.to_string_lossy().to_string() }
+                                </div>
+                                <div class="text-sm">
+                                    { format!("{} words{edited}", project.words) }
+                                </div>
+                            </div>
+                            <div class="flex shrink-0 ml-4">
+                                <button
+                                    onclick={on_pin}
+                                    class="rounded-lg px-2 py-1 bg-crust hover:bg-mantle text-inherit border-0 cursor-pointer"
+                                >
+                                    { if project.pinned { "Unpin" } else { "Pin" } }
+                                </button>
+                                <button
+                                    onclick={on_remove}
+                                    title="Remove from this list, the project stays on disk"
+                                    class="rounded-lg px-2 py-1 ml-2 bg-crust hover:bg-mantle text-inherit border-0 cursor-pointer"
+                                >
+                                    { "Remove" }
+                                </button>
+                            </div>
+                        </div>
+                    }
+                })
+                .collect::<Html>()
+        })
+        .unwrap_or_default();
+
+    html! {
+        <div class="flex flex-col w-[40rem] max-w-full">
+            <div class="text-3xl text-center">{ "Please select or create a project" }</div>
+            <div class="flex justify-evenly w-full text-xl pt-4">
+                <button
+                    class="bg-primary text-mantle p-2 rounded-lg cursor-pointer border-0 text-inherit text-[length:inherit] hover:ring-1 hover:ring-primary"
+                    onclick={open_folder}
+                >
+                    { "Open Project" }
+                </button>
+                <button
+                    class="bg-primary text-mantle p-2 rounded-lg cursor-pointer border-0 text-inherit text-[length:inherit] hover:ring-1 hover:ring-primary"
+                    onclick={open_archive}
+                >
+                    { "Open Archive" }
+                </button>
+                <button
+                    class="bg-secondary text-mantle p-2 rounded-lg cursor-pointer border-0 text-inherit text-[length:inherit] hover:ring-1 hover:ring-secondary"
+                    onclick={create_project}
+                >
+                    { "Create Project" }
+                </button>
+            </div>
+            if !message.is_empty() {
+                <div class="text-secondary pt-4">{ (*message).clone() }</div>
+            }
+            if projects.as_ref().is_some_and(|projects| !projects.is_empty()) {
+                <div class="font-bold pt-8">{ "Recent Projects" }</div>
+                <div class="flex flex-col pt-2 max-h-96 overflow-y-auto">{ rows }</div>
+            }
+        </div>
+    }
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+use serde_wasm_bindgen::to_value;
+use shared::Project;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::JsValue;
+use yew::platform::spawn_local;
+use yew::prelude::*;
+use yewdux::prelude::*;
+
+use crate::app::editor::EditorHandle;
+use crate::app::encryption::is_readable;
+use crate::app::modal::Modal;
+use crate::app::{invoke, listen, save_chapter, PathArgs, State};
+
+/// What waits for the user to decide about the unsaved changes of the open chapter
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Leave {
+    /// Opening the chapter at the index
+    Chapter(usize),
+    /// Opening another project
+    Project(Project),
+    /// Closing the window
+    Close,
+}
+
+#[derive(Serialize)]
+struct UnsavedArgs {
+    changes: bool,
+}
+
+/// Lets the backend hold the window open while there are unsaved changes, the
+/// close request then waits for the unsaved changes prompt
+#[hook]
+pub fn use_close_guard() {
+    let (state, dispatch) = use_store::<State>();
+
+    use_effect_with(state.changes, |&changes| {
+        spawn_local(async move {
+            invoke("set_unsaved", to_value(&UnsavedArgs { changes }).unwrap()).await;
+        });
+    });
+
+    use_effect_with((), move |()| {
+        spawn_local(async move {
+            let on_close_request = Closure::<dyn FnMut(JsValue)>::new(move |_| {
+                dispatch.reduce_mut(|state| state.leaving = Some(Leave::Close));
+            });
+            listen("close-requested", &on_close_request).await;
+            on_close_request.forget();
+        });
+    });
+}
+
+/// Does what waited for the decision, the changes are saved or discarded by now
+fn leave(leave: Leave, dispatch: &Dispatch<State>) {
+    match leave {
+        Leave::Chapter(index) => dispatch.reduce_mut(|state| {
+            state.leaving = None;
+            state.changes = false;
+            if let Some(project) = state.project.as_mut() {
+                if index < project.chapters.len() {
+                    project.active_chapter = Some(index);
+                }
+            }
+        }),
+        Leave::Project(project) => dispatch.reduce_mut(|state| {
+            state.leaving = None;
+            state.changes = false;
+            state.project = Some(project);
+        }),
+        Leave::Close => {
+            dispatch.reduce_mut(|state| state.leaving = None);
+            spawn_local(async {
+                invoke("close_window", JsValue::NULL).await;
+            });
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct UnsavedPromptProps {
+    pub editor: EditorHandle,
+}
+
+/// Asks whether to save or discard the unsaved changes before switching chapters
+/// or projects or closing, or to stay
+#[function_component(UnsavedPrompt)]
+pub fn unsaved_prompt(UnsavedPromptProps { editor }: &UnsavedPromptProps) -> Html {
+    let (state, dispatch) = use_store::<State>();
+    let Some(leaving) = state.leaving.clone() else {
+        return html! {};
+    };
+    let title = state.project.as_ref().and_then(|project| {
+        let chapter = project.chapters.get(project.active_chapter?)?;
+        Some(project.title(chapter))
+    });
+
+    let on_save = {
+        let dispatch = dispatch.clone();
+        let editor = editor.clone();
+        let leaving = leaving.clone();
+        Callback::from(move |_: MouseEvent| {
+            let dispatch = dispatch.clone();
+            let editor = editor.clone();
+            let leaving = leaving.clone();
+            spawn_local(async move {
+                save_chapter(dispatch.clone(), editor).await;
+                leave(leaving, &dispatch);
+            });
+        })
+    };
+
+    // The journal of the changes goes with them, they aren't offered again after a crash
+    let on_discard = {
+        let dispatch = dispatch.clone();
+        let editor = editor.clone();
+        let leaving = leaving.clone();
+        Callback::from(move |_: MouseEvent| {
+            if let Some(path) = editor.borrow().active() {
+                let args = PathArgs {
+                    path: path.to_string_lossy().to_string(),
+                };
+                spawn_local(async move {
+                    invoke("discard_journal", to_value(&args).unwrap()).await;
+                });
+            }
+            leave(leaving.clone(), &dispatch);
+        })
+    };
+
+    let on_cancel = Callback::from(move |_: MouseEvent| {
+        dispatch.reduce_mut(|state| state.leaving = None);
+    });
+
+    // Nothing can be saved while the project is locked
+    let can_save = is_readable(&state);
+    let question = match leaving {
+        Leave::Chapter(_) => "Save them before opening the other chapter?",
+        Leave::Project(_) => "Save them before opening the other project?",
+        Leave::Close => "Save them before closing PaperSmith?",
+    };
+
+    html! {
+        <Modal
+            content={html! {
+                <>
+                    <div class="text-xl font-bold">
+                        { title.map_or_else(
+                            || "You have unsaved changes".to_string(),
+                            |title| format!("\"{title}\" has unsaved changes"),
+                        ) }
+                    </div>
+                    <br />
+                    <div>{ question }</div>
+                    <div class="flex justify-end w-full pt-8">
+                        if can_save {
+                            <button
+                                onclick={on_save}
+                                class="rounded-lg text-lg px-2 py-1 ml-4 bg-primary text-crust hover:scale-105 border-0"
+                            >
+                                { "Save" }
+                            </button>
+                        }
+                        <button
+                            onclick={on_discard}
+                            class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                        >
+                            { "Discard" }
+                        </button>
+                        <button
+                            onclick={on_cancel}
+                            class="rounded-lg text-lg px-2 py-1 ml-4 bg-secondary text-crust hover:scale-105 border-0"
+                        >
+                            { "Cancel" }
+                        </button>
+                    </div>
+                </>
+            }}
+        />
+    }
+}